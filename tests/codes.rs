@@ -0,0 +1,126 @@
+use vdns_lib::{
+    common::{class::Class, q_class::QClass, rr_type::RRType},
+    messages::{
+        edns::edns::Edns,
+        header::flags::{Flags, OpCode, RCode},
+        message::Message,
+        parsing::Reader,
+        serializing::Writer,
+    },
+};
+
+fn round_trip<T>(
+    parse: impl Fn(&mut Reader) -> T,
+    serialize: impl Fn(&T, &mut Writer),
+    into: impl Fn(&T) -> u16,
+) {
+    for val in 0..=u16::MAX {
+        let bytes = val.to_be_bytes();
+        let parsed = parse(&mut Reader::new(&bytes));
+        assert_eq!(into(&parsed), val);
+
+        let mut writer = Writer::new();
+        serialize(&parsed, &mut writer);
+        assert_eq!(writer.get_serialized_message(), bytes);
+    }
+}
+
+#[test]
+fn rr_type_round_trips_every_value() {
+    round_trip(
+        |r| RRType::parse(r).unwrap(),
+        RRType::serialize,
+        |t| t.into(),
+    );
+}
+
+#[test]
+fn rr_type_presentation_parses_back() {
+    for val in 0..=u16::MAX {
+        let rr_type = RRType::from(val);
+        assert_eq!(rr_type.to_string().parse::<RRType>().unwrap(), rr_type);
+    }
+    assert_eq!(RRType::NsapPtr.to_string(), "NSAP-PTR");
+    assert_eq!(RRType::All.to_string(), "ANY");
+    assert_eq!(RRType::from(65280).to_string(), "TYPE65280");
+}
+
+#[test]
+fn class_round_trips_every_value() {
+    round_trip(|r| Class::parse(r).unwrap(), Class::serialize, |c| c.into());
+}
+
+#[test]
+fn q_class_round_trips_every_value() {
+    round_trip(
+        |r| QClass::parse(r).unwrap(),
+        QClass::serialize,
+        |c| c.into(),
+    );
+}
+
+#[test]
+fn rr_type_trust_anchors_use_their_assigned_values() {
+    assert_eq!(u16::from(RRType::TA), 32768);
+    assert_eq!(u16::from(RRType::DLV), 32769);
+    assert_eq!(RRType::from(32768), RRType::TA);
+    assert_eq!(RRType::from(32769), RRType::DLV);
+}
+
+#[test]
+fn r_code_keeps_every_value() {
+    for val in 0..=u16::MAX {
+        assert_eq!(u16::from(RCode::from(val)), val);
+    }
+    assert_eq!(RCode::from(8), RCode::NXRRSet);
+    assert_eq!(RCode::from(23), RCode::BadCookie);
+}
+
+#[test]
+fn extended_r_code_is_split_between_header_and_opt_record() {
+    let mut message = Message::new_query(
+        &"example.com".parse().unwrap(),
+        RRType::A,
+        true,
+        Some(Edns::default()),
+    );
+    message.header.flags.r_code = RCode::BadCookie;
+
+    let serialized = message.serialize();
+    assert_eq!(serialized[3] & 0b1111, 23 & 0b1111);
+
+    let parsed = Message::parse(&serialized).unwrap();
+    assert_eq!(parsed.header.flags.r_code, RCode::BadCookie);
+    assert_eq!(parsed.edns.unwrap().extended_rcode, 1);
+}
+
+#[test]
+fn extended_r_code_without_edns_becomes_server_failure() {
+    let mut message = Message::new_query(&"example.com".parse().unwrap(), RRType::A, true, None);
+    message.header.flags.r_code = RCode::BadVers;
+
+    let parsed = Message::parse(&message.serialize()).unwrap();
+    assert_eq!(parsed.header.flags.r_code, RCode::ServerFailure);
+}
+
+#[test]
+fn flags_round_trip_every_value() {
+    for val in 0..=u16::MAX {
+        let bytes = val.to_be_bytes();
+        let flags = Flags::parse(&mut Reader::new(&bytes)).unwrap();
+
+        let mut writer = Writer::new();
+        flags.serialize(&mut writer);
+        assert_eq!(writer.get_serialized_message(), bytes);
+    }
+}
+
+#[test]
+fn op_code_keeps_every_value() {
+    for val in 0..=0b1111 {
+        assert_eq!(u8::from(OpCode::from(val)), val);
+    }
+    assert_eq!(OpCode::from(4), OpCode::Notify);
+    assert_eq!(OpCode::from(5), OpCode::Update);
+    assert_eq!(OpCode::from(6), OpCode::DSO);
+}
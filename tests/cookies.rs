@@ -0,0 +1,79 @@
+use std::net::IpAddr;
+
+use vdns_lib::messages::edns::cookie::Cookie;
+
+#[path = "../vdns_server/src/cookies.rs"]
+mod cookies;
+
+use cookies::CookieSecrets;
+
+fn address(address: &str) -> IpAddr {
+    address.parse().unwrap()
+}
+
+fn client_cookie() -> Cookie {
+    Cookie::new([1, 2, 3, 4, 5, 6, 7, 8], None).unwrap()
+}
+
+#[test]
+fn issued_cookies_are_valid_for_the_same_client() {
+    let mut secrets = CookieSecrets::new();
+    let client = address("192.0.2.1");
+
+    let cookie = secrets.response_cookie(&client_cookie(), client);
+    assert_eq!(cookie.client(), client_cookie().client());
+    let server = cookie.server().unwrap();
+    // Version 1, three reserved bytes, a timestamp and the hash (RFC 9018 section 4)
+    assert_eq!(server.len(), 16);
+    assert_eq!(server[..4], [1, 0, 0, 0]);
+
+    assert!(secrets.is_valid(&cookie, client));
+    // A fresh server cookie is issued for every response, each of them valid
+    let next = secrets.response_cookie(&cookie, client);
+    assert!(secrets.is_valid(&next, client));
+    assert!(secrets.is_valid(&cookie, client));
+}
+
+#[test]
+fn cookies_are_bound_to_the_client() {
+    let mut secrets = CookieSecrets::new();
+    let cookie = secrets.response_cookie(&client_cookie(), address("192.0.2.1"));
+
+    assert!(!secrets.is_valid(&cookie, address("192.0.2.2")));
+    assert!(!secrets.is_valid(&cookie, address("2001:db8::1")));
+
+    let other_client = Cookie::new([8; 8], cookie.server().map(|s| s.to_vec())).unwrap();
+    assert!(!secrets.is_valid(&other_client, address("192.0.2.1")));
+
+    // Another server doesn't know our secret
+    assert!(!CookieSecrets::new().is_valid(&cookie, address("192.0.2.1")));
+}
+
+#[test]
+fn malformed_and_tampered_cookies_are_invalid() {
+    let mut secrets = CookieSecrets::new();
+    let client = address("192.0.2.1");
+    let cookie = secrets.response_cookie(&client_cookie(), client);
+    let server = cookie.server().unwrap().to_vec();
+    let with_server = |server: Vec<u8>| Cookie::new(*cookie.client(), Some(server)).unwrap();
+
+    assert!(!secrets.is_valid(&client_cookie(), client));
+
+    let mut tampered_hash = server.clone();
+    tampered_hash[15] ^= 1;
+    assert!(!secrets.is_valid(&with_server(tampered_hash), client));
+
+    let mut other_version = server.clone();
+    other_version[0] = 2;
+    assert!(!secrets.is_valid(&with_server(other_version), client));
+
+    // The timestamp is covered by the hash
+    let mut moved_timestamp = server.clone();
+    moved_timestamp[7] ^= 1;
+    assert!(!secrets.is_valid(&with_server(moved_timestamp), client));
+
+    let mut longer = server.clone();
+    longer.push(0);
+    assert!(!secrets.is_valid(&with_server(longer), client));
+    assert!(!secrets.is_valid(&with_server(server[..8].to_vec()), client));
+}
@@ -0,0 +1,156 @@
+use vdns_lib::{
+    common::rr_type::RRType,
+    messages::{
+        parsing::Reader,
+        resource_record::{
+            dnskey::DNSKEY,
+            nsec3::{NSEC3, NSEC3PARAM},
+            rrsig::{format_timestamp, parse_timestamp},
+            type_bitmap::TypeBitmap,
+        },
+        serializing::Writer,
+    },
+};
+
+fn bitmap_wire(bitmap: &TypeBitmap) -> Vec<u8> {
+    let mut writer = Writer::new();
+    bitmap.serialize(&mut writer);
+    writer.get_serialized_message()
+}
+
+fn type_numbers(bitmap: &TypeBitmap) -> Vec<u16> {
+    bitmap.types().iter().map(u16::from).collect()
+}
+
+#[test]
+fn key_tags_match_the_rfc_examples() {
+    // RFC 4034 section 5.4, the key the example DS record refers to
+    let key: DNSKEY = "256 3 5 AQOeiiR0GOMYkDshWoSKz9Xz fwJr1AYtsmx3TGkJaNXVbfi/
+                       2pHm822aJ5iI9BMzNXxeYCmZ DRD99WYwYqUSdjMmmAphXdvx
+                       egXd/M5+X7OrzKBaMbCVdFLU Uh6DhweJBjEVv5f2wwjM9Xzc
+                       nOf+EPbtG9DMBmADjFDc2w/r ljwvFw=="
+        .parse()
+        .unwrap();
+    assert_eq!(key.key_tag(), 60485);
+    assert!(key.is_zone_key());
+    assert!(!key.is_secure_entry_point());
+
+    // RFC 4034 section 2.3, which section 3.3 signs with key tag 2642
+    let key: DNSKEY = "256 3 5 AQPSKmynfzW4kyBv015MUG2DeIQ3 Cbl+BBZH4b/0PY1kxkmvHjcZc8no
+                       kfzj31GajIQKY+5CptLr3buXA10h WqTkF7H6RfoRqXQeogmMHfpftf6z
+                       Mv1LyBUgia7za6ZEzOJBOztyvhjL 742iU/TpPSEDhm2SNKLijfUppn1U
+                       aNvv4w=="
+        .parse()
+        .unwrap();
+    assert_eq!(key.key_tag(), 2642);
+}
+
+#[test]
+fn rsa_md5_key_tags_use_the_modulus() {
+    // RFC 4034 appendix B.1, the second to last two octets of the key
+    let key: DNSKEY = "257 3 1 AQIDEjRW".parse().unwrap();
+    assert_eq!(key.key_tag(), 0x1234);
+    assert!(key.is_secure_entry_point());
+}
+
+#[test]
+fn type_bitmap_matches_the_rfc_example() {
+    // RFC 4034 section 4.3, `A MX RRSIG NSEC TYPE1234`
+    let mut expected = vec![0x00, 0x06, 0x40, 0x01, 0x00, 0x00, 0x00, 0x03, 0x04, 0x1b];
+    expected.extend_from_slice(&[0; 26]);
+    expected.push(0x20);
+
+    let bitmap: TypeBitmap = "A MX RRSIG NSEC TYPE1234".parse().unwrap();
+    assert_eq!(bitmap_wire(&bitmap), expected);
+
+    let parsed = TypeBitmap::parse(&mut Reader::new(&expected), expected.len()).unwrap();
+    assert_eq!(type_numbers(&parsed), vec![1, 15, 46, 47, 1234]);
+    assert!(parsed.contains(&RRType::MX));
+    assert!(!parsed.contains(&RRType::AAAA));
+}
+
+#[test]
+fn type_bitmap_round_trips_over_several_windows() {
+    let bitmap = TypeBitmap::new(
+        [65280, 257, 1, 256, 2, 46, 32768, 6, 255, 1]
+            .into_iter()
+            .map(RRType::from)
+            .collect(),
+    );
+    assert_eq!(
+        type_numbers(&bitmap),
+        vec![1, 2, 6, 46, 255, 256, 257, 32768, 65280]
+    );
+
+    let wire = bitmap_wire(&bitmap);
+    let mut expected = vec![0x00, 0x20, 0x62];
+    expected.extend_from_slice(&[0; 4]);
+    expected.push(0x02);
+    expected.extend_from_slice(&[0; 25]);
+    expected.push(0x01);
+    expected.extend_from_slice(&[0x01, 0x01, 0xc0, 0x80, 0x01, 0x80, 0xff, 0x01, 0x80]);
+    assert_eq!(wire, expected);
+
+    let parsed = TypeBitmap::parse(&mut Reader::new(&wire), wire.len()).unwrap();
+    assert_eq!(type_numbers(&parsed), type_numbers(&bitmap));
+    assert_eq!(bitmap_wire(&parsed), wire);
+}
+
+#[test]
+fn malformed_type_bitmaps_are_rejected() {
+    let invalid: [&[u8]; 4] = [
+        // Windows out of order
+        &[0x01, 0x01, 0x80, 0x00, 0x01, 0x40],
+        // A window repeated
+        &[0x00, 0x01, 0x40, 0x00, 0x01, 0x20],
+        // Bitmap lengths of 0 and 33
+        &[0x00, 0x00],
+        &[0x00, 0x21],
+    ];
+    for bytes in invalid {
+        assert!(
+            TypeBitmap::parse(&mut Reader::new(bytes), bytes.len()).is_err(),
+            "{bytes:?}"
+        );
+    }
+
+    // The bitmap is longer than the data
+    let bytes = [0x00, 0x04, 0x40];
+    assert!(TypeBitmap::parse(&mut Reader::new(&bytes), bytes.len()).is_err());
+}
+
+#[test]
+fn nsec3_salts_and_hashes_must_fit_a_length_octet() {
+    let salt = "AB".repeat(255);
+    let hash = "0".repeat(408);
+    assert!(format!("1 0 10 {salt}").parse::<NSEC3PARAM>().is_ok());
+    assert!(format!("1 0 10 {salt} {hash} A").parse::<NSEC3>().is_ok());
+
+    let long_salt = "AB".repeat(256);
+    // 416 base32hex characters decode to 260 bytes
+    let long_hash = "0".repeat(416);
+    assert!(format!("1 0 10 {long_salt}").parse::<NSEC3PARAM>().is_err());
+    assert!(format!("1 0 10 {long_salt} {hash} A")
+        .parse::<NSEC3>()
+        .is_err());
+    assert!(format!("1 0 10 {salt} {long_hash} A")
+        .parse::<NSEC3>()
+        .is_err());
+}
+
+#[test]
+fn timestamps_are_formatted_as_utc_dates() {
+    assert_eq!(format_timestamp(0), "19700101000000");
+    assert_eq!(format_timestamp(951782400), "20000229000000");
+    assert_eq!(format_timestamp(1706745600 + 3661), "20240201010101");
+    assert_eq!(format_timestamp(1709251199), "20240229235959");
+    assert_eq!(format_timestamp(u32::MAX), "21060207062815");
+
+    for timestamp in [0, 68169600, 951782400, 1709251199, u32::MAX] {
+        assert_eq!(
+            parse_timestamp(&format_timestamp(timestamp)).unwrap(),
+            timestamp
+        );
+    }
+    assert_eq!(parse_timestamp("1706745600").unwrap(), 1706745600);
+}
@@ -0,0 +1,328 @@
+use std::collections::HashSet;
+
+use vdns_lib::{
+    common::{domain_name::DomainName, parse_error::ParseError},
+    messages::{message::Message, parsing::Reader, serializing::Writer},
+};
+
+// Names in these packets start after a 12 byte header, as they would in a message
+const HEADER: [u8; 12] = [0; 12];
+
+fn parse_at(packet: &[u8], index: usize) -> Result<DomainName, ParseError> {
+    let mut reader = Reader::new(packet);
+    reader.set_index(index);
+    DomainName::parse(&mut reader)
+}
+
+fn packet(name: &[u8]) -> Vec<u8> {
+    let mut packet = HEADER.to_vec();
+    packet.extend_from_slice(name);
+    packet
+}
+
+#[test]
+fn compressed_name_is_followed() {
+    let packet = packet(b"\x07example\x03com\x00\x03www\xC0\x0C");
+    let mut reader = Reader::new(&packet);
+    reader.set_index(25);
+    let name = DomainName::parse(&mut reader).unwrap();
+
+    assert_eq!(name.to_string(), "www.example.com.");
+    // Reading continues after the pointer, not where it pointed
+    assert_eq!(reader.get_index(), packet.len());
+}
+
+#[test]
+fn pointer_to_itself_is_rejected() {
+    let packet = packet(b"\xC0\x0C");
+    assert!(matches!(
+        parse_at(&packet, 12),
+        Err(ParseError::ForwardPointer {
+            offset: 12,
+            target: 12
+        })
+    ));
+}
+
+#[test]
+fn forward_pointer_is_rejected() {
+    let packet = packet(b"\xC0\x0E\x01a\x00");
+    assert!(matches!(
+        parse_at(&packet, 12),
+        Err(ParseError::ForwardPointer {
+            offset: 12,
+            target: 14
+        })
+    ));
+}
+
+#[test]
+fn pointer_back_into_the_name_is_rejected() {
+    // a -> b -> pointer back to a
+    let packet = packet(b"\x01a\x01b\xC0\x0C");
+    assert!(matches!(
+        parse_at(&packet, 12),
+        Err(ParseError::PointerLoop {
+            offset: 16,
+            target: 12
+        })
+    ));
+}
+
+#[test]
+fn pointers_pointing_at_each_other_are_rejected() {
+    // The name starts at the second pointer, which jumps to the first one, which jumps back
+    let packet = packet(b"\xC0\x0E\xC0\x0C");
+    assert!(matches!(
+        parse_at(&packet, 14),
+        Err(ParseError::ForwardPointer {
+            offset: 12,
+            target: 14
+        })
+    ));
+}
+
+#[test]
+fn long_pointer_chain_is_cut_off_by_the_name_length() {
+    // Every name is a label followed by a pointer to the name before it
+    let mut packet = packet(b"\x01a\x00");
+    let mut previous = 12u16;
+    for _ in 0..2000 {
+        let start = packet.len() as u16;
+        packet.extend_from_slice(b"\x01a");
+        packet.extend_from_slice(&(previous | 0xC000).to_be_bytes());
+        previous = start;
+    }
+
+    assert!(matches!(
+        parse_at(&packet, previous as usize),
+        Err(ParseError::NameTooLong(_))
+    ));
+}
+
+#[test]
+fn name_longer_than_255_bytes_is_rejected() {
+    let mut name = vec![];
+    for _ in 0..5 {
+        name.push(63);
+        name.extend_from_slice(&[b'a'; 63]);
+    }
+    name.push(0);
+
+    assert!(matches!(
+        parse_at(&packet(&name), 12),
+        Err(ParseError::NameTooLong(_))
+    ));
+}
+
+#[test]
+fn reserved_label_types_are_rejected() {
+    for prefix in [0x40, 0x80] {
+        let packet = packet(&[prefix | 1, b'a', 0]);
+        assert!(matches!(
+            parse_at(&packet, 12),
+            Err(ParseError::InvalidLabelType { offset: 12, .. })
+        ));
+    }
+}
+
+#[test]
+fn truncated_label_is_rejected() {
+    let packet = packet(b"\x07exam");
+    assert!(matches!(
+        parse_at(&packet, 12),
+        Err(ParseError::BufferReadError(_))
+    ));
+}
+
+#[test]
+fn missing_root_label_is_rejected() {
+    let packet = packet(b"\x03com");
+    assert!(parse_at(&packet, 12).is_err());
+}
+
+#[test]
+fn binary_labels_are_kept_and_escaped() {
+    let packet = packet(b"\x04a.\xFF\\\x03com\x00");
+    let name = parse_at(&packet, 12).unwrap();
+    assert_eq!(name.labels()[0], b"a.\xFF\\");
+    assert_eq!(name.to_string(), "a\\.\\255\\\\.com.");
+
+    let mut writer = Writer::new();
+    name.serialize(&mut writer);
+    assert_eq!(writer.get_serialized_message(), &packet[12..]);
+}
+
+#[test]
+fn label_and_name_limits_are_enforced() {
+    assert!(matches!(
+        DomainName::from_labels(vec![vec![b'a'; 64]]),
+        Err(ParseError::LabelTooLong(64))
+    ));
+    assert!(matches!(
+        DomainName::from_labels(vec![vec![b'a'; 63]; 4]),
+        Err(ParseError::NameTooLong(257))
+    ));
+    assert!(matches!(
+        DomainName::from_labels(vec![b"a".to_vec(), vec![]]),
+        Err(ParseError::EmptyLabel)
+    ));
+    assert!(DomainName::from_labels(vec![
+        vec![b'a'; 63],
+        vec![b'a'; 63],
+        vec![b'a'; 63],
+        vec![b'a'; 61]
+    ])
+    .is_ok());
+}
+
+#[test]
+fn message_with_pointer_loop_fails_to_parse() {
+    // One question whose name points to itself
+    let mut packet = vec![0, 1, 1, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+    packet.extend_from_slice(b"\xC0\x0C\x00\x01\x00\x01");
+    assert!(Message::parse(&packet).is_err());
+}
+
+fn name(name: &str) -> DomainName {
+    name.parse().unwrap()
+}
+
+#[test]
+fn names_are_equal_and_hashed_ignoring_case() {
+    assert_eq!(name("WWW.Example.COM"), name("www.example.com"));
+    assert_ne!(name("www.example.com"), name("example.com"));
+
+    let names: HashSet<DomainName> = [name("Example.com"), name("EXAMPLE.COM")].into();
+    assert_eq!(names.len(), 1);
+}
+
+#[test]
+fn names_are_sorted_in_canonical_order() {
+    // The example from RFC 4034 section 6.1
+    let expected = [
+        "example.",
+        "a.example.",
+        "yljkjljk.a.example.",
+        "Z.a.example.",
+        "zABC.a.EXAMPLE.",
+        "z.example.",
+        "\\001.z.example.",
+        "*.z.example.",
+        "\\200.z.example.",
+    ];
+    let mut names = vec![
+        DomainName::from_labels(vec![vec![200], b"z".to_vec(), b"example".to_vec()]).unwrap(),
+        name("*.z.example."),
+        name("z.example."),
+        name("zABC.a.EXAMPLE."),
+        name("Z.a.example."),
+        DomainName::from_labels(vec![vec![1], b"z".to_vec(), b"example".to_vec()]).unwrap(),
+        name("yljkjljk.a.example."),
+        name("a.example."),
+        name("example."),
+    ];
+    names.sort();
+
+    let sorted = names.iter().map(|n| n.to_string()).collect::<Vec<String>>();
+    assert_eq!(sorted, expected);
+}
+
+#[test]
+fn compression_ignores_case() {
+    let mut writer = Writer::new();
+    name("Example.COM").serialize(&mut writer);
+    name("www.example.com").serialize(&mut writer);
+
+    assert_eq!(
+        writer.get_serialized_message(),
+        b"\x07Example\x03COM\x00\x03www\xC0\x00"
+    );
+}
+
+#[test]
+fn name_helpers() {
+    let www = name("www.example.com");
+    assert_eq!(www.num_labels(), 3);
+    assert_eq!(www.parent(), Some(name("example.com")));
+    assert_eq!(DomainName::root().parent(), None);
+    assert!(www.is_subdomain_of(&name("EXAMPLE.com")));
+    assert!(!name("example.com").is_subdomain_of(&www));
+    assert_eq!(
+        www.labels_rev().collect::<Vec<&[u8]>>(),
+        vec![&b"com"[..], b"example", b"www"]
+    );
+    assert_eq!(name("www").append(&name("example.com")).unwrap(), www);
+    assert!(name("*.example.com").is_wildcard());
+    assert!(!www.is_wildcard());
+}
+
+#[test]
+fn presentation_format_is_parsed() {
+    let absolute = name("www.example.com.");
+    assert!(absolute.is_absolute());
+    assert_eq!(absolute.num_labels(), 3);
+    assert_eq!(absolute.to_string(), "www.example.com.");
+
+    let relative = name("www.example.com");
+    assert!(!relative.is_absolute());
+    assert_eq!(relative.to_string(), "www.example.com");
+
+    let root = name(".");
+    assert!(root.is_root());
+    assert_eq!(root.to_string(), ".");
+
+    let escaped = name("my\\.host\\032\\255.example.");
+    assert_eq!(escaped.labels()[0], b"my.host \xFF");
+    assert_eq!(escaped.num_labels(), 2);
+    assert_eq!(escaped.to_string(), "my\\.host\\032\\255.example.");
+    assert_eq!(name(&escaped.to_string()).labels(), escaped.labels());
+}
+
+#[test]
+fn invalid_presentation_format_is_rejected() {
+    for invalid in ["", "..", "a..b", ".a", "a\\", "a\\256", "a\\12b"] {
+        assert!(invalid.parse::<DomainName>().is_err(), "{invalid}");
+    }
+    assert!(matches!(
+        "a".repeat(64).parse::<DomainName>(),
+        Err(ParseError::LabelTooLong(64))
+    ));
+    assert!(matches!(
+        [&"a".repeat(63); 4]
+            .map(|l| l.as_str())
+            .join(".")
+            .parse::<DomainName>(),
+        Err(ParseError::NameTooLong(257))
+    ));
+}
+
+#[test]
+fn internationalized_names_are_converted_to_punycode() {
+    let name = DomainName::from_unicode("Räksmörgås.se.").unwrap();
+    assert_eq!(name.to_string(), "xn--rksmrgs-5wao1o.se.");
+    assert_eq!(name.to_unicode(), "räksmörgås.se.");
+
+    // The ideographic full stop separates labels too
+    let name = DomainName::from_unicode("例え。テスト").unwrap();
+    assert_eq!(name.num_labels(), 2);
+    assert!(!name.is_absolute());
+    assert_eq!(name.to_unicode(), "例え.テスト");
+
+    let ascii = DomainName::from_unicode("www.Example.com").unwrap();
+    assert_eq!(ascii.labels()[1], b"Example");
+}
+
+#[test]
+fn invalid_punycode_is_displayed_as_is() {
+    let name = name("xn--a.example.");
+    assert_eq!(name.to_unicode(), "xn--a.example.");
+}
+
+#[test]
+fn invalid_internationalized_name_is_rejected() {
+    assert!(matches!(
+        DomainName::from_unicode("a\u{0080}b.se"),
+        Err(ParseError::InvalidIdn(_))
+    ));
+}
@@ -0,0 +1,384 @@
+use std::net::{IpAddr, Ipv4Addr};
+
+use vdns_lib::{
+    check_response_cookie,
+    common::{class::Class, domain_name::DomainName, rr_type::RRType, ttl::TTL},
+    cookie_for,
+    messages::{
+        edns::{
+            client_subnet::{is_private_address, ClientSubnet},
+            cookie::Cookie,
+            edns::Edns,
+            edns_option::EdnsOption,
+            extended_error::{ExtendedError, ExtendedErrorCode},
+        },
+        message::{Message, MessageBuilder},
+        parsing::Reader,
+        question::question::Question,
+        resource_record::{a::A, resource_record::ResourceRecord, rr_data::RRData},
+        serializing::Writer,
+    },
+    LookupError,
+};
+
+fn name(name: &str) -> DomainName {
+    name.parse().unwrap()
+}
+
+fn edns() -> Edns {
+    Edns {
+        udp_payload_size: 4096,
+        dnssec_ok: true,
+        options: vec![EdnsOption::Unknown {
+            code: 65001,
+            data: vec![1, 2, 3],
+        }],
+        ..Edns::default()
+    }
+}
+
+fn address(address: &str) -> IpAddr {
+    address.parse().unwrap()
+}
+
+fn parse_subnet(data: &[u8]) -> Result<ClientSubnet, String> {
+    ClientSubnet::parse(&mut Reader::new(data), data.len() as u16).map_err(|e| e.to_string())
+}
+
+fn subnet_wire(subnet: &ClientSubnet) -> Vec<u8> {
+    let mut writer = Writer::new();
+    subnet.serialize(&mut writer);
+    writer.get_serialized_message()
+}
+
+fn parse_cookie(data: &[u8]) -> Result<Cookie, String> {
+    Cookie::parse(&mut Reader::new(data), data.len() as u16).map_err(|e| e.to_string())
+}
+
+fn ar_count(buf: &[u8]) -> u16 {
+    u16::from_be_bytes([buf[10], buf[11]])
+}
+
+#[test]
+fn opt_record_round_trips() {
+    let query = Message::new_query(&name("example.com."), RRType::A, true, Some(edns()));
+    let buf = query.serialize();
+
+    assert_eq!(ar_count(&buf), 1);
+    // Root owner, type 41, the payload size as the class, the DO bit in the TTL and a single option
+    assert!(buf.ends_with(&[
+        0x00, 0x00, 0x29, 0x10, 0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x07, 0xfd, 0xe9, 0x00, 0x03,
+        0x01, 0x02, 0x03
+    ]));
+
+    let parsed = Message::parse(&buf).unwrap();
+    assert!(parsed.additional.is_empty());
+    let parsed_edns = parsed.edns.as_ref().unwrap();
+    assert_eq!(parsed_edns.udp_payload_size, 4096);
+    assert_eq!(parsed_edns.extended_rcode, 0);
+    assert_eq!(parsed_edns.version, 0);
+    assert!(parsed_edns.dnssec_ok);
+    assert_eq!(parsed_edns.z, 0);
+    assert_eq!(parsed_edns.options, edns().options);
+    assert_eq!(parsed_edns.max_udp_payload_size(), 4096);
+
+    assert_eq!(parsed.serialize(), buf);
+}
+
+#[test]
+fn small_payload_sizes_are_raised_to_512() {
+    let edns = Edns {
+        udp_payload_size: 100,
+        ..Edns::default()
+    };
+    let query = Message::new_query(&name("example.com."), RRType::A, true, Some(edns));
+    let parsed = Message::parse(&query.serialize()).unwrap();
+    assert_eq!(parsed.edns.unwrap().max_udp_payload_size(), 512);
+
+    let query = Message::new_query(&name("example.com."), RRType::A, true, None);
+    assert_eq!(query.max_udp_payload_size(), 512);
+}
+
+#[test]
+fn header_counts_are_those_of_the_written_sections() {
+    let mut query = MessageBuilder::query()
+        .question(Question::new(name("example.com."), RRType::A))
+        .edns(edns())
+        .build();
+    assert_eq!(query.header.ar_count, 1);
+
+    // Counts that don't match the sections, e.g. after changing them, are not written
+    query.header.qd_count = 3;
+    query.header.ar_count = 0;
+    query.answer.push(ResourceRecord::new(
+        name("example.com."),
+        Class::IN,
+        TTL::from(300),
+        RRData::A(A::new(Ipv4Addr::new(192, 0, 2, 1))),
+    ));
+    let buf = query.serialize();
+    assert_eq!(buf[4..12], [0, 1, 0, 1, 0, 0, 0, 1]);
+    let parsed = Message::parse(&buf).unwrap();
+    assert_eq!(parsed.answer.len(), 1);
+    assert!(parsed.edns.is_some());
+
+    // A lenient parse keeps the counts as received, but only what could be parsed is written
+    let without_opt = &buf[..buf.len() - 18];
+    let partial = Message::parse_lenient(without_opt).unwrap();
+    assert!(!partial.is_complete());
+    assert_eq!(partial.message.header.ar_count, 1);
+    let reserialized = partial.message.serialize();
+    assert_eq!(reserialized[4..12], [0, 1, 0, 1, 0, 0, 0, 0]);
+    assert!(Message::parse(&reserialized).is_ok());
+}
+
+#[test]
+fn invalid_opt_records_are_rejected() {
+    let query = Message::new_query(&name("example.com."), RRType::A, true, Some(edns()));
+    let buf = query.serialize();
+
+    // A second OPT record
+    let mut opt = Writer::new();
+    Edns::default().serialize(&mut opt);
+    let mut twice = [buf.as_slice(), &opt.get_serialized_message()].concat();
+    twice[11] = 2;
+    assert!(Message::parse(&twice).is_err());
+
+    // An OPT record that isn't owned by the root
+    let query = Message::new_query(&name("example.com."), RRType::A, true, None);
+    let mut owned = query.serialize();
+    owned[11] = 1;
+    owned.extend_from_slice(&[1, b'a', 0]);
+    owned.extend_from_slice(&opt.get_serialized_message()[1..]);
+    assert!(Message::parse(&owned).is_err());
+}
+
+#[test]
+fn client_subnets_round_trip() {
+    let subnet = parse_subnet(&[0, 1, 24, 0, 192, 0, 2]).unwrap();
+    assert_eq!(subnet.address(), address("192.0.2.0"));
+    assert_eq!((subnet.source_prefix(), subnet.scope_prefix()), (24, 0));
+    assert_eq!(subnet_wire(&subnet), vec![0, 1, 24, 0, 192, 0, 2]);
+
+    // Only the bytes covered by the prefix are sent
+    let subnet = ClientSubnet::new(address("2001:db8:1234:5678::1"), 56).unwrap();
+    assert_eq!(subnet.address(), address("2001:db8:1234:5600::"));
+    let wire = subnet_wire(&subnet);
+    assert_eq!(
+        wire,
+        vec![0, 2, 56, 0, 0x20, 0x01, 0x0d, 0xb8, 0x12, 0x34, 0x56]
+    );
+    assert_eq!(parse_subnet(&wire).unwrap(), subnet);
+
+    let subnet = parse_subnet(&[0, 1, 0, 0]).unwrap();
+    assert_eq!(subnet.address(), address("0.0.0.0"));
+    let subnet = parse_subnet(&[
+        0, 2, 128, 64, 0x20, 1, 0xd, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+    ])
+    .unwrap();
+    assert_eq!(subnet.address(), address("2001:db8::1"));
+    assert_eq!(subnet.scope_prefix(), 64);
+
+    assert!(ClientSubnet::new(address("192.0.2.1"), 33).is_none());
+    assert!(ClientSubnet::new(address("2001:db8::1"), 129).is_none());
+}
+
+#[test]
+fn invalid_client_subnets_are_rejected() {
+    let invalid: [&[u8]; 9] = [
+        // Unknown family
+        &[0, 3, 8, 0, 10],
+        // Prefixes longer than the address
+        &[0, 1, 33, 0, 1, 2, 3, 4, 5],
+        &[0, 1, 24, 33, 192, 0, 2],
+        &[0, 2, 8, 129, 0x20],
+        // More or fewer address bytes than the prefix covers
+        &[0, 1, 24, 0, 192, 0, 2, 1],
+        &[0, 1, 24, 0, 192, 0],
+        &[0, 1, 0, 0, 192],
+        // Bits set past the prefix
+        &[0, 1, 23, 0, 192, 0, 3],
+        // Too short for the family and prefixes
+        &[0, 1, 24],
+    ];
+    for data in invalid {
+        assert!(parse_subnet(data).is_err(), "{data:?}");
+    }
+}
+
+#[test]
+fn client_subnets_are_shortened_to_a_maximum_prefix() {
+    let subnet = ClientSubnet::new(address("198.51.100.77"), 32).unwrap();
+    let shortened = subnet.with_max_source_prefix(24);
+    assert_eq!(shortened.address(), address("198.51.100.0"));
+    assert_eq!(shortened.source_prefix(), 24);
+    assert_eq!(subnet_wire(&shortened), vec![0, 1, 24, 0, 198, 51, 100]);
+
+    // Shorter subnets are left as they are
+    let subnet = ClientSubnet::new(address("198.51.0.0"), 16).unwrap();
+    assert_eq!(subnet.with_max_source_prefix(24), subnet);
+
+    let subnet = ClientSubnet::new(address("2001:db8:1234:5678::"), 64).unwrap();
+    let shortened = subnet.with_max_source_prefix(48);
+    assert_eq!(shortened.address(), address("2001:db8:1234::"));
+    assert_eq!(shortened.with_max_source_prefix(0).address(), address("::"));
+}
+
+#[test]
+fn private_addresses_are_recognized() {
+    for private in [
+        "10.1.2.3",
+        "172.16.0.1",
+        "172.31.255.255",
+        "192.168.1.20",
+        "127.0.0.1",
+        "169.254.0.1",
+        "100.64.0.1",
+        "0.0.0.0",
+        "::1",
+        "::",
+        "fd00::1",
+        "fe80::1",
+        "::ffff:192.168.1.20",
+    ] {
+        assert!(is_private_address(&address(private)), "{private}");
+    }
+    for public in [
+        "8.8.8.8",
+        "172.32.0.1",
+        "100.128.0.1",
+        "198.51.100.1",
+        "2001:4860:4860::8888",
+        "::ffff:8.8.8.8",
+    ] {
+        assert!(!is_private_address(&address(public)), "{public}");
+    }
+}
+
+#[test]
+fn cookies_round_trip() {
+    let client = [1, 2, 3, 4, 5, 6, 7, 8];
+    let cookie = parse_cookie(&client).unwrap();
+    assert_eq!(cookie.client(), &client);
+    assert_eq!(cookie.server(), None);
+
+    for server_length in [8, 16, 32] {
+        let data = [client.to_vec(), vec![0xAB; server_length]].concat();
+        let cookie = parse_cookie(&data).unwrap();
+        assert_eq!(cookie.server(), Some(&data[8..]));
+
+        let mut writer = Writer::new();
+        EdnsOption::Cookie(cookie).serialize(&mut writer);
+        let wire = writer.get_serialized_message();
+        assert_eq!(wire[..4], [0, 10, 0, data.len() as u8]);
+        assert_eq!(wire[4..], data);
+        assert!(matches!(
+            EdnsOption::parse(&mut Reader::new(&wire)).unwrap(),
+            EdnsOption::Cookie(parsed) if parsed.server() == Some(&data[8..])
+        ));
+    }
+}
+
+#[test]
+fn invalid_cookie_lengths_are_rejected() {
+    // Client cookies are 8 bytes and server cookies between 8 and 32 bytes (RFC 7873 section 4)
+    for length in [0, 7, 9, 15, 41, 48] {
+        assert!(parse_cookie(&vec![0; length]).is_err(), "{length}");
+    }
+    assert!(Cookie::new([0; 8], Some(vec![0; 7])).is_none());
+    assert!(Cookie::new([0; 8], Some(vec![0; 33])).is_none());
+    assert!(Cookie::new([0; 8], Some(vec![0; 32])).is_some());
+}
+
+#[test]
+fn client_cookie_is_kept_per_server() {
+    let server = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 53));
+    let cookie = cookie_for(server);
+    assert_eq!(cookie.server(), None);
+    assert_eq!(cookie_for(server), cookie);
+
+    let other = cookie_for(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 54)));
+    assert_ne!(other.client(), cookie.client());
+}
+
+#[test]
+fn replies_without_a_cookie_are_from_servers_without_cookie_support() {
+    let server = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 55));
+    let sent = cookie_for(server);
+    assert!(check_response_cookie(server, Some(&sent), None).is_ok());
+    assert!(check_response_cookie(server, None, None).is_ok());
+
+    // Once the server has sent a cookie it must keep doing so
+    let received = Cookie::new(*sent.client(), Some(vec![0xAB; 8])).unwrap();
+    assert!(check_response_cookie(server, Some(&sent), Some(&received)).is_ok());
+    assert_eq!(cookie_for(server), received);
+    assert!(matches!(
+        check_response_cookie(server, Some(&received), None),
+        Err(LookupError::MissingCookie)
+    ));
+
+    // The client cookie must always be echoed
+    let other = Cookie::new([9; 8], Some(vec![0xAB; 8])).unwrap();
+    assert!(matches!(
+        check_response_cookie(server, Some(&received), Some(&other)),
+        Err(LookupError::CookieMismatch)
+    ));
+}
+
+#[test]
+fn extended_errors_round_trip() {
+    let error = ExtendedError::new(
+        ExtendedErrorCode::NetworkError,
+        Some("upstream timed out".to_string()),
+    );
+    let mut writer = Writer::new();
+    EdnsOption::ExtendedError(error.clone()).serialize(&mut writer);
+    let wire = writer.get_serialized_message();
+    // Option code 15, the length, the INFO-CODE and then the text
+    assert_eq!(wire[..6], [0, 15, 0, 20, 0, 23]);
+    assert_eq!(&wire[6..], b"upstream timed out");
+    assert!(matches!(
+        EdnsOption::parse(&mut Reader::new(&wire)).unwrap(),
+        EdnsOption::ExtendedError(parsed) if parsed == error
+    ));
+
+    // The text is optional and a trailing NUL is dropped
+    let parse = |data: &[u8]| ExtendedError::parse(&mut Reader::new(data), data.len() as u16);
+    let parsed = parse(&[0, 15]).unwrap();
+    assert_eq!(parsed.code(), &ExtendedErrorCode::Blocked);
+    assert_eq!(parsed.extra_text(), None);
+    assert_eq!(parse(b"\x00\x03old\x00").unwrap().extra_text(), Some("old"));
+
+    let parsed = parse(&[0xff, 0x00]).unwrap();
+    assert_eq!(parsed.code(), &ExtendedErrorCode::Unassigned(65280));
+    assert_eq!(parsed.to_string(), "Extended DNS Error 65280 (Unassigned)");
+
+    assert!(parse(&[]).is_err());
+    assert!(parse(&[0]).is_err());
+}
+
+#[test]
+fn extended_errors_are_carried_in_the_opt_record() {
+    let mut edns = edns();
+    edns.add_extended_error(ExtendedError::new(ExtendedErrorCode::StaleAnswer, None));
+    edns.add_extended_error(ExtendedError::new(
+        ExtendedErrorCode::Prohibited,
+        Some("not allowed".to_string()),
+    ));
+    let response = MessageBuilder::query()
+        .question(Question::new(name("example.com."), RRType::A))
+        .edns(edns)
+        .build();
+
+    let parsed = Message::parse(&response.serialize()).unwrap();
+    let errors = parsed.edns.as_ref().unwrap().extended_errors();
+    let codes = errors.iter().map(|e| e.code()).collect::<Vec<_>>();
+    assert_eq!(
+        codes,
+        vec![
+            &ExtendedErrorCode::StaleAnswer,
+            &ExtendedErrorCode::Prohibited
+        ]
+    );
+    assert_eq!(errors[1].extra_text(), Some("not allowed"));
+}
@@ -0,0 +1,108 @@
+use std::net::Ipv4Addr;
+
+use vdns_lib::{
+    common::{class::Class, domain_name::DomainName, rr_type::RRType, ttl::TTL},
+    messages::{
+        edns::edns::Edns,
+        header::{
+            flags::{OpCode, RCode},
+            message_header::MessageHeader,
+        },
+        message::{Message, MessageBuilder},
+        parsing::Reader,
+        question::question::Question,
+        resource_record::{a::A, resource_record::ResourceRecord, rr_data::RRData},
+    },
+};
+
+fn name(name: &str) -> DomainName {
+    name.parse().unwrap()
+}
+
+fn a_record(owner: &str, last: u8) -> ResourceRecord {
+    ResourceRecord::new(
+        name(owner),
+        Class::IN,
+        TTL::from(300),
+        RRData::A(A::new(Ipv4Addr::new(10, 0, 0, last))),
+    )
+}
+
+fn query() -> Message {
+    MessageBuilder::query()
+        .recursion_desired(true)
+        .checking_disabled(true)
+        .question(Question::new(name("www.example.com."), RRType::A))
+        .question(Question::new(name("www.example.com."), RRType::AAAA))
+        .edns(Edns::default())
+        .build()
+}
+
+#[test]
+fn counts_are_derived_from_the_sections() {
+    let message = query();
+    assert_eq!(message.header.qd_count, 2);
+    assert_eq!(message.header.an_count, 0);
+    // The OPT record counts towards the additional section
+    assert_eq!(message.header.ar_count, 1);
+
+    let response = MessageBuilder::response(&message)
+        .answers(vec![
+            a_record("www.example.com.", 1),
+            a_record("www.example.com.", 2),
+        ])
+        .authority(a_record("ns.example.com.", 3))
+        .additional(a_record("ns.example.com.", 4))
+        .build();
+    assert_eq!(response.header.qd_count, 2);
+    assert_eq!(response.header.an_count, 2);
+    assert_eq!(response.header.ns_count, 1);
+    assert_eq!(response.header.ar_count, 2);
+
+    // The header is written as it was built
+    let serialized = response.clone().serialize();
+    let header = MessageHeader::parse(&mut Reader::new(&serialized)).unwrap();
+    assert_eq!(header.qd_count, response.header.qd_count);
+    assert_eq!(header.an_count, response.header.an_count);
+    assert_eq!(header.ns_count, response.header.ns_count);
+    assert_eq!(header.ar_count, response.header.ar_count);
+}
+
+#[test]
+fn response_follows_the_query() {
+    let query = query();
+    let response = MessageBuilder::response(&query)
+        .authoritative(true)
+        .r_code(RCode::NameError)
+        .build();
+
+    let parsed = Message::parse(&response.serialize()).unwrap();
+    assert!(!parsed.is_query());
+    assert_eq!(parsed.header.id, query.header.id);
+    assert!(parsed.header.flags.rd);
+    assert!(parsed.header.flags.cd);
+    assert!(parsed.header.flags.aa);
+    assert_eq!(parsed.header.flags.r_code, RCode::NameError);
+    assert_eq!(parsed.question_names(), query.question_names());
+    assert!(parsed.edns.is_some());
+
+    // Only clients that speak EDNS get it back
+    let plain = Message::new_query(&name("example.com."), RRType::A, true, None);
+    assert!(MessageBuilder::response(&plain).build().edns.is_none());
+}
+
+#[test]
+fn existing_messages_can_be_extended() {
+    let query = MessageBuilder::query()
+        .id(4711)
+        .op_code(OpCode::Notify)
+        .build();
+    let message = MessageBuilder::from(query)
+        .answer(a_record("www.example.com.", 1))
+        .build();
+
+    assert_eq!(message.header.id, 4711);
+    assert_eq!(message.header.flags.op_code, OpCode::Notify);
+    assert_eq!(message.header.qd_count, 0);
+    assert_eq!(message.header.an_count, 1);
+}
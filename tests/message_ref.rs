@@ -0,0 +1,157 @@
+use std::net::Ipv4Addr;
+
+use vdns_lib::{
+    common::{
+        class::Class,
+        domain_name::DomainName,
+        parse_error::{ParseError, Section},
+        rr_type::RRType,
+        ttl::TTL,
+    },
+    messages::{
+        edns::edns::Edns,
+        header::flags::RCode,
+        message::{Message, MessageBuilder},
+        message_ref::MessageRef,
+        resource_record::{a::A, resource_record::ResourceRecord, rr_data::RRData},
+        serializing::Writer,
+    },
+};
+
+fn name(name: &str) -> DomainName {
+    name.parse().unwrap()
+}
+
+fn response() -> Vec<u8> {
+    let query = Message::new_query(
+        &name("www.example.com."),
+        RRType::A,
+        true,
+        Some(Edns::default()),
+    );
+    let answers = (1..=3)
+        .map(|i| {
+            ResourceRecord::new(
+                name("WWW.example.com."),
+                Class::IN,
+                TTL::from(300),
+                RRData::A(A::new(Ipv4Addr::new(10, 0, 0, i))),
+            )
+        })
+        .collect();
+    Message::new_response(&query, answers).serialize()
+}
+
+#[test]
+fn sections_are_read_from_the_buffer() {
+    let buf = response();
+    let message = MessageRef::parse(&buf).unwrap();
+    assert!(!message.header().is_query());
+
+    let question = message.questions().next().unwrap();
+    assert_eq!(question.q_type(), RRType::A);
+    assert!(question.name().eq_name(&name("www.EXAMPLE.com.")));
+    assert!(question.name().is_subdomain_of(&name("example.com.")));
+    assert!(!question
+        .name()
+        .is_subdomain_of(&name("www.www.example.com.")));
+
+    let answers = message.answer().collect::<Vec<_>>();
+    assert_eq!(answers.len(), 3);
+    // The owners are compressed to point at the question
+    assert_eq!(answers[0].name().to_domain_name(), name("www.example.com."));
+    assert_eq!(answers[2].ttl(), 300);
+    assert_eq!(answers[2].rdata(), &[10, 0, 0, 3]);
+    assert_eq!(message.authority().count(), 0);
+
+    let opt = message.additional().next().unwrap();
+    assert_eq!(opt.rr_type(), RRType::OPT);
+}
+
+#[test]
+fn owned_message_matches_the_view() {
+    let buf = response();
+    let message = MessageRef::parse(&buf).unwrap();
+    let owned = message.to_message().unwrap();
+
+    assert_eq!(owned.answer.len(), message.answer().count());
+    let record = message.answer().nth(1).unwrap().to_record().unwrap();
+    assert_eq!(record.name(), owned.answer[1].name());
+    assert_eq!(
+        message.questions().next().unwrap().to_question().q_name(),
+        owned.questions[0].q_name()
+    );
+
+    // The OPT record is taken out of the additional section as when parsing the whole message
+    let parsed = Message::parse(&buf).unwrap();
+    assert!(owned.additional.is_empty());
+    assert_eq!(
+        owned.edns.as_ref().unwrap().options,
+        parsed.edns.unwrap().options
+    );
+    assert_eq!(owned.header.flags.r_code, parsed.header.flags.r_code);
+    assert_eq!(owned.serialize(), buf);
+}
+
+#[test]
+fn extended_rcode_and_edns_are_read_from_the_opt_record() {
+    let query = Message::new_query(
+        &name("www.example.com."),
+        RRType::A,
+        true,
+        Some(Edns::default()),
+    );
+    let response = MessageBuilder::response(&query)
+        .r_code(RCode::BadCookie)
+        .build()
+        .serialize();
+
+    let message = MessageRef::parse(&response).unwrap();
+    assert_eq!(message.edns().unwrap().unwrap().extended_rcode, 1);
+    assert_eq!(
+        message.to_message().unwrap().header.flags.r_code,
+        RCode::BadCookie
+    );
+
+    let plain = Message::new_query(&name("www.example.com."), RRType::A, true, None).serialize();
+    assert!(MessageRef::parse(&plain).unwrap().edns().unwrap().is_none());
+}
+
+#[test]
+fn second_opt_record_fails_to_decode() {
+    let mut buf = response();
+    let mut opt = Writer::new();
+    Edns::default().serialize(&mut opt);
+    buf.extend(opt.get_serialized_message());
+    buf[11] = 2;
+
+    // The framing is fine, only decoding the records finds the problem
+    let message = MessageRef::parse(&buf).unwrap();
+    let err = message.to_message().unwrap_err();
+    assert!(matches!(
+        err,
+        ParseError::InSection {
+            section: Section::Additional,
+            index: 1,
+            ..
+        }
+    ));
+    assert!(Message::parse(&buf).is_err());
+}
+
+#[test]
+fn malformed_messages_are_rejected() {
+    let buf = response();
+    // Cut off in the middle of the last record
+    assert!(MessageRef::parse(&buf[..buf.len() - 3]).is_err());
+
+    // A question whose name points to itself
+    let mut looping = vec![0, 1, 1, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+    looping.extend_from_slice(b"\xC0\x0C\x00\x01\x00\x01");
+    assert!(MessageRef::parse(&looping).is_err());
+
+    // More answers than there are in the message
+    let mut missing = buf.clone();
+    missing[7] += 1;
+    assert!(MessageRef::parse(&missing).is_err());
+}
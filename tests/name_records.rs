@@ -0,0 +1,71 @@
+use vdns_lib::{
+    common::{class::Class, domain_name::DomainName, rr_type::RRType, ttl::TTL},
+    messages::{
+        message::Message,
+        message_ref::MessageRef,
+        resource_record::{resource_record::ResourceRecord, rr_data::RRData},
+    },
+};
+
+fn name(name: &str) -> DomainName {
+    name.parse().unwrap()
+}
+
+fn record(rdata: RRData) -> ResourceRecord {
+    ResourceRecord::new(name("example.com."), Class::IN, TTL::from(300), rdata)
+}
+
+fn response() -> Message {
+    let query = Message::new_query(&name("example.com."), RRType::MX, true, None);
+    Message::new_response(
+        &query,
+        vec![
+            record(RRData::MX("10 mail.example.com.".parse().unwrap())),
+            record(RRData::NS(name("ns1.example.com."))),
+            record(RRData::PTR(name("host.example.com."))),
+        ],
+    )
+}
+
+#[test]
+fn targets_are_compressed_on_the_wire() {
+    let buf = response().serialize();
+    let view = MessageRef::parse(&buf).unwrap();
+    let rdata = view
+        .answer()
+        .map(|r| r.rdata().to_vec())
+        .collect::<Vec<Vec<u8>>>();
+
+    // example.com. is first written by the question, right after the 12 byte header
+    assert_eq!(rdata[0], b"\x00\x0a\x04mail\xc0\x0c");
+    assert_eq!(rdata[1], b"\x03ns1\xc0\x0c");
+    assert_eq!(rdata[2], b"\x04host\xc0\x0c");
+}
+
+#[test]
+fn compressed_targets_round_trip() {
+    let buf = response().serialize();
+    let parsed = Message::parse(&buf).unwrap();
+
+    let targets = parsed
+        .answer
+        .iter()
+        .map(|r| match r.rdata() {
+            RRData::MX(mx) => mx.to_string(),
+            RRData::NS(ns) => ns.to_string(),
+            RRData::PTR(ptr) => ptr.to_string(),
+            other => panic!("Unexpected data {other}"),
+        })
+        .collect::<Vec<String>>();
+    assert_eq!(
+        targets,
+        vec![
+            "MX { preference: 10, exchange: mail.example.com. }",
+            "ns1.example.com.",
+            "host.example.com.",
+        ]
+    );
+
+    // Serializing what was parsed gives the same bytes again
+    assert_eq!(parsed.serialize(), buf);
+}
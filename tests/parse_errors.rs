@@ -0,0 +1,187 @@
+use std::net::Ipv4Addr;
+
+use vdns_lib::{
+    common::{
+        class::Class,
+        domain_name::DomainName,
+        parse_error::{ParseError, Section},
+        rr_type::RRType,
+        ttl::TTL,
+    },
+    messages::{
+        edns::edns::Edns,
+        message::{Message, MessageBuilder},
+        message_ref::MessageRef,
+        parsing::{HexDump, Reader},
+        question::question::Question,
+        resource_record::{a::A, resource_record::ResourceRecord, rr_data::RRData},
+    },
+};
+
+fn name(name: &str) -> DomainName {
+    name.parse().unwrap()
+}
+
+fn a_record(owner: &str, last: u8) -> ResourceRecord {
+    ResourceRecord::new(
+        name(owner),
+        Class::IN,
+        TTL::from(300),
+        RRData::A(A::new(Ipv4Addr::new(10, 0, 0, last))),
+    )
+}
+
+fn response() -> Vec<u8> {
+    MessageBuilder::query()
+        .question(Question::new(name("example.com."), RRType::A))
+        .answers((1..=2).map(|i| a_record("example.com.", i)))
+        .build()
+        .serialize()
+}
+
+/// A response whose first additional record has a name in its data that uses a reserved label type
+fn response_with_malformed_additional() -> Vec<u8> {
+    let mut buf = MessageBuilder::query()
+        .question(Question::new(name("example.com."), RRType::A))
+        .answer(a_record("example.com.", 1))
+        .additional(ResourceRecord::new(
+            name("alias.example.com."),
+            Class::IN,
+            TTL::from(300),
+            RRData::CNAME(name("x.example.com.")),
+        ))
+        .additional(a_record("ns.example.com.", 2))
+        .edns(Edns::default())
+        .build()
+        .serialize();
+
+    let target = buf.windows(3).position(|w| w == b"\x01x\xC0").unwrap();
+    buf[target] |= 0x40;
+    buf
+}
+
+#[test]
+fn errors_point_to_the_failing_entry() {
+    let buf = response();
+    // Cut off within the data of the second answer
+    let truncated = &buf[..buf.len() - 2];
+
+    for err in [
+        Message::parse(truncated).err().unwrap(),
+        MessageRef::parse(truncated).err().unwrap(),
+    ] {
+        assert!(matches!(
+            err,
+            ParseError::InSection {
+                section: Section::Answer,
+                index: 1,
+                ..
+            }
+        ));
+        assert!(matches!(err.cause(), ParseError::BufferReadError(_)));
+        assert!(err.offset().unwrap() < truncated.len());
+    }
+}
+
+#[test]
+fn name_errors_keep_their_own_offset() {
+    let mut buf = response();
+    // The first label of the question uses a reserved label type
+    buf[12] |= 0x40;
+
+    let err = Message::parse(&buf).err().unwrap();
+    assert!(matches!(
+        err,
+        ParseError::InSection {
+            section: Section::Question,
+            index: 0,
+            ..
+        }
+    ));
+    assert!(matches!(
+        err.cause(),
+        ParseError::InvalidLabelType { offset: 12, .. }
+    ));
+    assert_eq!(err.offset(), Some(12));
+    assert!(err.to_string().contains("question entry 0"));
+}
+
+#[test]
+fn short_header_is_reported() {
+    let err = Message::parse(&[0, 1, 0]).err().unwrap();
+    assert!(matches!(
+        err,
+        ParseError::InSection {
+            section: Section::Header,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn hex_dump_highlights_the_offset() {
+    let buf = b"\x00\x01\x02abcdefghijklmnopq";
+    let dump = HexDump::new(buf, Some(17)).to_string();
+    let lines = dump.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("0000: 00  01  02  61 "));
+    assert!(lines[0].ends_with("|...abcdefghijklm|"));
+    assert!(lines[1].starts_with("0010: 6e [6f] 70  71 "));
+    assert!(lines[1].ends_with("|nopq|"));
+    assert_eq!(lines[0].find('|'), lines[1].find('|'));
+
+    let mut reader = Reader::new(buf);
+    reader.set_index(buf.len());
+    assert!(reader.hex_dump().to_string().contains("past the end"));
+}
+
+#[test]
+fn lenient_parse_skips_malformed_records() {
+    let buf = response_with_malformed_additional();
+    assert!(Message::parse(&buf).is_err());
+
+    let partial = Message::parse_lenient(&buf).unwrap();
+    assert!(!partial.is_complete());
+    assert!(partial.has_complete_answer());
+    assert_eq!(partial.errors.len(), 1);
+    assert!(matches!(
+        partial.errors[0],
+        ParseError::InSection {
+            section: Section::Additional,
+            index: 0,
+            ..
+        }
+    ));
+
+    // The records after the malformed one are still read
+    let message = partial.message;
+    assert_eq!(message.answer.len(), 1);
+    assert_eq!(message.additional.len(), 1);
+    assert_eq!(*message.additional[0].name(), name("ns.example.com."));
+    assert!(message.edns.is_some());
+}
+
+#[test]
+fn lenient_parse_stops_where_the_framing_is_lost() {
+    let buf = response();
+    // Cut off within the data of the second answer
+    let partial = Message::parse_lenient(&buf[..buf.len() - 2]).unwrap();
+    assert!(!partial.has_complete_answer());
+    assert_eq!(partial.errors.len(), 1);
+    assert_eq!(partial.message.questions.len(), 1);
+    assert_eq!(partial.message.answer.len(), 1);
+
+    // Only a broken header makes it fail
+    assert!(Message::parse_lenient(&buf[..5]).is_err());
+}
+
+#[test]
+fn complete_messages_parse_the_same_in_both_modes() {
+    let buf = response();
+    let partial = Message::parse_lenient(&buf).unwrap();
+    assert!(partial.is_complete());
+    assert_eq!(
+        partial.message.to_string(),
+        Message::parse(&buf).unwrap().to_string()
+    );
+}
@@ -0,0 +1,142 @@
+use rand::{rngs::StdRng, SeedableRng};
+use vdns_lib::messages::{
+    parsing::Reader,
+    resource_record::{
+        naptr::NAPTR,
+        srv::{order_targets_with, SRV},
+    },
+    serializing::Writer,
+};
+
+fn srv(text: &str) -> SRV {
+    text.parse().unwrap()
+}
+
+fn targets(records: &[SRV]) -> Vec<String> {
+    records.iter().map(|r| r.target().to_string()).collect()
+}
+
+#[test]
+fn lower_priorities_are_contacted_first() {
+    let records = vec![
+        srv("20 10 5060 c.example.com."),
+        srv("10 60 5060 a.example.com."),
+        srv("30 0 5060 d.example.com."),
+        srv("10 40 5060 b.example.com."),
+    ];
+
+    for seed in 0..100 {
+        let ordered = order_targets_with(&records, &mut StdRng::seed_from_u64(seed));
+        let priorities = ordered.iter().map(SRV::priority).collect::<Vec<u16>>();
+        assert_eq!(priorities, vec![10, 10, 20, 30]);
+
+        let mut first_group = targets(&ordered[..2]);
+        first_group.sort();
+        assert_eq!(first_group, vec!["a.example.com.", "b.example.com."]);
+    }
+}
+
+#[test]
+fn weights_decide_the_order_within_a_priority() {
+    let records = vec![
+        srv("10 1 5060 light.example.com."),
+        srv("10 9 5060 heavy.example.com."),
+    ];
+    let mut rng = StdRng::seed_from_u64(2782);
+
+    let heavy_first = (0..1000)
+        .filter(|_| {
+            let ordered = order_targets_with(&records, &mut rng);
+            ordered[0].target().to_string() == "heavy.example.com."
+        })
+        .count();
+    assert!((800..=970).contains(&heavy_first), "{heavy_first}");
+}
+
+#[test]
+fn weight_zero_is_rarely_first() {
+    let records = vec![
+        srv("10 100 5060 weighted.example.com."),
+        srv("10 0 5060 zero.example.com."),
+    ];
+    let mut rng = StdRng::seed_from_u64(2782);
+
+    let zero_first = (0..1000)
+        .filter(|_| {
+            let ordered = order_targets_with(&records, &mut rng);
+            assert_eq!(ordered.len(), 2);
+            ordered[0].weight() == 0
+        })
+        .count();
+    assert!(zero_first < 50, "{zero_first}");
+
+    // With only weight 0 records there is nothing to weigh and the order is kept
+    let records = vec![
+        srv("10 0 5060 a.example.com."),
+        srv("10 0 5060 b.example.com."),
+        srv("10 0 5060 c.example.com."),
+    ];
+    let ordered = order_targets_with(&records, &mut StdRng::seed_from_u64(1));
+    assert_eq!(
+        targets(&ordered),
+        vec!["a.example.com.", "b.example.com.", "c.example.com."]
+    );
+}
+
+#[test]
+fn a_lone_root_target_means_no_service() {
+    let mut rng = StdRng::seed_from_u64(0);
+    assert!(order_targets_with(&[srv("0 0 0 .")], &mut rng).is_empty());
+    assert!(order_targets_with(&[], &mut rng).is_empty());
+
+    let records = vec![srv("0 0 0 ."), srv("10 0 5060 a.example.com.")];
+    assert_eq!(order_targets_with(&records, &mut rng).len(), 2);
+}
+
+#[test]
+fn naptr_presentation_and_wire_formats() {
+    let naptr: NAPTR =
+        r#"100 10 "S" "SIP+D2U" "!^.*$!sip:info@example.com!" _sip._udp.example.com."#
+            .parse()
+            .unwrap();
+    assert_eq!(
+        naptr.to_string(),
+        r#"100 10 "S" "SIP+D2U" "!^.*$!sip:info@example.com!" _sip._udp.example.com."#
+    );
+
+    let mut writer = Writer::new();
+    naptr.serialize(&mut writer);
+    let bytes = writer.get_serialized_message();
+    let mut expected = vec![0, 100, 0, 10, 1, b'S', 7];
+    expected.extend_from_slice(b"SIP+D2U");
+    expected.push(27);
+    expected.extend_from_slice(b"!^.*$!sip:info@example.com!");
+    expected.extend_from_slice(b"\x04_sip\x04_udp\x07example\x03com\x00");
+    assert_eq!(bytes, expected);
+
+    let parsed = NAPTR::parse(&mut Reader::new(&bytes)).unwrap();
+    assert_eq!(parsed.to_string(), naptr.to_string());
+}
+
+#[test]
+fn naptr_with_empty_strings_and_root_replacement() {
+    let naptr: NAPTR = r#"10 0 "" "" "" ."#.parse().unwrap();
+    let mut writer = Writer::new();
+    naptr.serialize(&mut writer);
+    assert_eq!(
+        writer.get_serialized_message(),
+        vec![0, 10, 0, 0, 0, 0, 0, 0]
+    );
+}
+
+#[test]
+fn malformed_naptr_is_rejected() {
+    assert!(r#"100 10 "S" "SIP+D2U" """#.parse::<NAPTR>().is_err());
+    assert!(r#"100 10 "S" "SIP+D2U" "" . extra"#.parse::<NAPTR>().is_err());
+    assert!(r#"order 10 "S" "SIP+D2U" "" ."#.parse::<NAPTR>().is_err());
+    assert!(r#"70000 10 "S" "SIP+D2U" "" ."#.parse::<NAPTR>().is_err());
+
+    // The regexp claims more bytes than there are
+    let bytes = [0, 100, 0, 10, 1, b'S', 0, 30, b'!'];
+    assert!(NAPTR::parse(&mut Reader::new(&bytes)).is_err());
+}
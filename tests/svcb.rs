@@ -0,0 +1,221 @@
+use std::net::Ipv4Addr;
+
+use vdns_lib::messages::{
+    parsing::Reader,
+    resource_record::svcb::{SvcParam, SvcParamKey, SVCB},
+    serializing::Writer,
+};
+
+fn wire(svcb: &SVCB) -> Vec<u8> {
+    let mut writer = Writer::new();
+    svcb.serialize(&mut writer);
+    writer.get_serialized_message()
+}
+
+fn from_wire(bytes: &[u8]) -> Result<SVCB, String> {
+    SVCB::parse(&mut Reader::new(bytes), bytes.len() as u16).map_err(|e| e.to_string())
+}
+
+/// Checks the presentation and wire formats against each other and that both round trip
+fn assert_formats(text: &str, expected_text: &str, expected_wire: &[u8]) {
+    let svcb: SVCB = text.parse().unwrap();
+    assert_eq!(svcb.to_string(), expected_text);
+    assert_eq!(wire(&svcb), expected_wire);
+
+    let reparsed: SVCB = svcb.to_string().parse().unwrap();
+    assert_eq!(wire(&reparsed), expected_wire);
+    let parsed = from_wire(expected_wire).unwrap();
+    assert_eq!(parsed.to_string(), expected_text);
+}
+
+// The test vectors are from RFC 9460 appendix D
+#[test]
+fn alias_form() {
+    assert_formats(
+        "0 foo.example.com.",
+        "0 foo.example.com.",
+        b"\x00\x00\x03foo\x07example\x03com\x00",
+    );
+    assert!("0 foo.example.com.".parse::<SVCB>().unwrap().is_alias());
+}
+
+#[test]
+fn port_and_generic_keys() {
+    assert_formats(
+        "16 foo.example.com. port=53",
+        "16 foo.example.com. port=53",
+        b"\x00\x10\x03foo\x07example\x03com\x00\x00\x03\x00\x02\x00\x35",
+    );
+    assert_formats(
+        "1 foo.example.com. key667=hello",
+        "1 foo.example.com. key667=hello",
+        b"\x00\x01\x03foo\x07example\x03com\x00\x02\x9b\x00\x05hello",
+    );
+    assert_formats(
+        r#"1 foo.example.com. key667="hello\210qoo""#,
+        r#"1 foo.example.com. key667=hello\210qoo"#,
+        b"\x00\x01\x03foo\x07example\x03com\x00\x02\x9b\x00\x09hello\xd2qoo",
+    );
+}
+
+#[test]
+fn address_hints() {
+    assert_formats(
+        r#"1 foo.example.com. ipv6hint="2001:db8::1,2001:db8::53:1""#,
+        "1 foo.example.com. ipv6hint=2001:db8::1,2001:db8::53:1",
+        b"\x00\x01\x03foo\x07example\x03com\x00\x00\x06\x00\x20\
+          \x20\x01\x0d\xb8\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x01\
+          \x20\x01\x0d\xb8\x00\x00\x00\x00\x00\x00\x00\x00\x00\x53\x00\x01",
+    );
+
+    let svcb: SVCB = "1 . ipv4hint=192.0.2.1,192.0.2.2".parse().unwrap();
+    match svcb.params() {
+        [SvcParam::Ipv4Hint(addresses)] => assert_eq!(
+            addresses,
+            &vec![Ipv4Addr::new(192, 0, 2, 1), Ipv4Addr::new(192, 0, 2, 2)]
+        ),
+        other => panic!("Expected an ipv4hint, got {other:?}"),
+    }
+}
+
+#[test]
+fn params_are_sorted_and_mandatory_keys_are_present() {
+    assert_formats(
+        r#"16 foo.example.org. alpn=h2,h3-19 mandatory=ipv4hint,alpn ipv4hint=192.0.2.1"#,
+        "16 foo.example.org. mandatory=alpn,ipv4hint alpn=h2,h3-19 ipv4hint=192.0.2.1",
+        b"\x00\x10\x03foo\x07example\x03org\x00\
+          \x00\x00\x00\x04\x00\x01\x00\x04\
+          \x00\x01\x00\x09\x02h2\x05h3-19\
+          \x00\x04\x00\x04\xc0\x00\x02\x01",
+    );
+}
+
+#[test]
+fn alpn_values_escape_commas_and_backslashes() {
+    let expected_wire = b"\x00\x10\x03foo\x07example\x03org\x00\
+          \x00\x01\x00\x0c\x08f\\oo,bar\x02h2";
+    assert_formats(
+        r#"16 foo.example.org. alpn="f\\\\oo\\,bar,h2""#,
+        r#"16 foo.example.org. alpn=f\\\\oo\\,bar,h2"#,
+        expected_wire,
+    );
+    let svcb: SVCB = r#"16 foo.example.org. alpn=f\\\092oo\092,bar,h2"#.parse().unwrap();
+    assert_eq!(wire(&svcb), expected_wire);
+}
+
+#[test]
+fn no_default_alpn_and_ech() {
+    let svcb: SVCB = "1 svc.example.com. alpn=h2 no-default-alpn ech=AEP+DQA/"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        svcb.to_string(),
+        "1 svc.example.com. alpn=h2 no-default-alpn ech=AEP+DQA/"
+    );
+    let keys = svcb
+        .params()
+        .iter()
+        .map(SvcParam::key)
+        .collect::<Vec<SvcParamKey>>();
+    assert_eq!(
+        keys,
+        vec![
+            SvcParamKey::Alpn,
+            SvcParamKey::NoDefaultAlpn,
+            SvcParamKey::Ech
+        ]
+    );
+    assert_eq!(
+        from_wire(&wire(&svcb)).unwrap().to_string(),
+        svcb.to_string()
+    );
+}
+
+#[test]
+fn invalid_presentation_is_rejected() {
+    let invalid = [
+        // Repeated keys
+        "1 foo.example.com. key123=abc key123=def",
+        "1 foo.example.com. port=53 key3=abc",
+        // Missing values
+        "1 foo.example.com. mandatory",
+        "1 foo.example.com. alpn",
+        "1 foo.example.com. port",
+        "1 foo.example.com. ipv4hint",
+        "1 foo.example.com. ipv6hint",
+        "1 foo.example.com. no-default-alpn=abc",
+        // Mandatory listing itself, a missing key or a key twice
+        "1 foo.example.com. mandatory=mandatory",
+        "1 foo.example.com. mandatory=key123",
+        "1 foo.example.com. mandatory=port port=443 alpn=h2 mandatory=alpn",
+        "1 foo.example.com. mandatory=key123,key123 key123=abc",
+        // Malformed values
+        "1 foo.example.com. port=https",
+        "1 foo.example.com. ipv4hint=2001:db8::1",
+        "1 foo.example.com. key65536=abc",
+        "1 foo.example.com. key0123=abc",
+        "1 foo.example.com. alpn=h2,,h3",
+        "one foo.example.com.",
+        "1",
+    ];
+    for text in invalid {
+        assert!(text.parse::<SVCB>().is_err(), "{text}");
+    }
+}
+
+#[test]
+fn new_checks_the_keys() {
+    let target = || ".".parse().unwrap();
+
+    assert!(SVCB::new(1, target(), vec![SvcParam::Port(53), SvcParam::Port(443)]).is_err());
+    assert!(SVCB::new(
+        1,
+        target(),
+        vec![SvcParam::Mandatory(vec![SvcParamKey::Port])]
+    )
+    .is_err());
+    assert!(SVCB::new(
+        1,
+        target(),
+        vec![
+            SvcParam::Mandatory(vec![SvcParamKey::Port, SvcParamKey::Port]),
+            SvcParam::Port(53)
+        ]
+    )
+    .is_err());
+
+    let svcb = SVCB::new(
+        1,
+        target(),
+        vec![
+            SvcParam::Port(53),
+            SvcParam::Mandatory(vec![SvcParamKey::Port, SvcParamKey::Alpn]),
+            SvcParam::Alpn(vec![b"h2".to_vec()]),
+        ],
+    )
+    .unwrap();
+    assert_eq!(svcb.to_string(), "1 . mandatory=alpn,port alpn=h2 port=53");
+}
+
+#[test]
+fn invalid_wire_format_is_rejected() {
+    let name = b"\x00\x01\x03foo\x07example\x03com\x00".to_vec();
+    let with = |params: &[u8]| [name.as_slice(), params].concat();
+
+    let invalid = [
+        // Keys out of order and repeated
+        with(b"\x00\x03\x00\x02\x00\x35\x00\x01\x00\x03\x02h2"),
+        with(b"\x00\x03\x00\x02\x00\x35\x00\x03\x00\x02\x00\x35"),
+        // A mandatory key that is missing
+        with(b"\x00\x00\x00\x02\x00\x03"),
+        // A port that isn't two bytes and an address list with trailing bytes
+        with(b"\x00\x03\x00\x03\x00\x35\x00"),
+        with(b"\x00\x04\x00\x05\xc0\x00\x02\x01\x01"),
+        // An empty alpn list and a value that is longer than the data
+        with(b"\x00\x01\x00\x00"),
+        with(b"\x00\x01\x00\x09\x02h2"),
+    ];
+    for bytes in invalid {
+        assert!(from_wire(&bytes).is_err(), "{bytes:?}");
+    }
+}
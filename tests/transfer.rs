@@ -0,0 +1,220 @@
+use std::{
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use vdns_lib::{
+    common::{class::Class, domain_name::DomainName, rr_type::RRType, ttl::TTL},
+    messages::{
+        header::flags::RCode,
+        message::{Message, MessageBuilder},
+        resource_record::{
+            a::A, resource_record::ResourceRecord, rr_data::RRData, soa::SOA, tsig::TSIG,
+        },
+        tsig::{
+            keyring::{Keyring, TsigAlgorithm, TsigKey},
+            signing::{TsigError, TsigSession},
+        },
+    },
+    read_tcp_message, write_tcp_message,
+};
+
+#[allow(dead_code)]
+#[path = "../vdns_server/src/zones.rs"]
+mod zones;
+
+#[path = "../vdns_server/src/transfer.rs"]
+mod transfer;
+
+use zones::{Zone, ZoneStore};
+
+// Enough records that the transfer needs three messages
+const ADDRESS_COUNT: u8 = 250;
+
+fn name(name: &str) -> DomainName {
+    name.parse().unwrap()
+}
+
+fn key() -> TsigKey {
+    TsigKey::new(
+        name("transfer.key"),
+        TsigAlgorithm::HmacSha256,
+        b"not a very secret secret".to_vec(),
+    )
+}
+
+fn zone() -> Zone {
+    let soa = SOA::new(
+        name("ns.home.lan."),
+        name("hostmaster.home.lan."),
+        1,
+        3600,
+        600,
+        86400,
+        300,
+    );
+    let records = (0..ADDRESS_COUNT).map(|i| {
+        ResourceRecord::new(
+            name(&format!("host{i}.home.lan.")),
+            Class::IN,
+            TTL::from(300),
+            RRData::A(A::new(Ipv4Addr::new(192, 168, 1, i))),
+        )
+    });
+    let soa = ResourceRecord::new(
+        name("home.lan."),
+        Class::IN,
+        TTL::from(3600),
+        RRData::SOA(soa),
+    );
+    Zone::from_records(
+        name("home.lan."),
+        [soa].into_iter().chain(records).collect(),
+    )
+    .unwrap()
+}
+
+/// Starts serving transfers of the zone on a free port
+fn serve() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let zones = Arc::new(Mutex::new(ZoneStore::new(vec![zone()])));
+    let keyring = Keyring::new(vec![key()]);
+    thread::spawn(move || transfer::serve_transfers(listener, zones, keyring));
+    address
+}
+
+fn connect(address: SocketAddr) -> TcpStream {
+    let stream = TcpStream::connect(address).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    stream
+}
+
+fn axfr_query() -> Vec<u8> {
+    Message::new_query(&name("home.lan."), RRType::AXFR, false, None).serialize()
+}
+
+/// Sends the request and returns the first message of the response
+fn exchange(address: SocketAddr, request: &[u8]) -> Vec<u8> {
+    let mut stream = connect(address);
+    write_tcp_message(&mut stream, request).unwrap();
+    read_tcp_message(&mut stream).unwrap()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+fn tsig_of(buf: &[u8]) -> TSIG {
+    let message = Message::parse(buf).unwrap();
+    match message.additional.last().map(|r| r.rdata()) {
+        Some(RRData::TSIG(tsig)) => tsig.clone(),
+        other => panic!("Expected a TSIG record, got {other:?}"),
+    }
+}
+
+#[test]
+fn signed_transfer_spans_several_signed_messages() {
+    let address = serve();
+    // A client that never sends anything doesn't hold up the others
+    let _idle = connect(address);
+
+    let mut session = TsigSession::new(key());
+    let mut stream = connect(address);
+    write_tcp_message(&mut stream, &session.sign(axfr_query())).unwrap();
+
+    let mut messages = 0;
+    let mut records = vec![];
+    while records.len() < ADDRESS_COUNT as usize + 2 {
+        let read = read_tcp_message(&mut stream).unwrap();
+        // Every message is signed and covers the one before it
+        session.verify(&read).unwrap();
+        let response = Message::parse(&read).unwrap();
+        assert_eq!(response.header.flags.r_code, RCode::NoError);
+        records.extend(response.answer);
+        messages += 1;
+    }
+
+    assert_eq!(messages, 3);
+    assert_eq!(*records[0].record_type(), RRType::SOA);
+    assert_eq!(*records[records.len() - 1].record_type(), RRType::SOA);
+    let soa_count = records
+        .iter()
+        .filter(|r| *r.record_type() == RRType::SOA)
+        .count();
+    assert_eq!(soa_count, 2);
+}
+
+#[test]
+fn signatures_outside_the_fudge_get_badtime() {
+    let address = serve();
+    let mut session = TsigSession::new(key());
+    let request = session.sign_at(axfr_query(), now() - 3600);
+
+    let read = exchange(address, &request);
+    let response = Message::parse(&read).unwrap();
+    assert_eq!(response.header.flags.r_code, RCode::NotAuth);
+    assert!(response.answer.is_empty());
+
+    // The response is signed and tells us what time the server thinks it is
+    let tsig = tsig_of(&read);
+    assert_eq!(tsig.error(), RCode::BadTime);
+    assert_eq!(tsig.other_data().len(), 6);
+    assert!(matches!(
+        session.verify(&read),
+        Err(TsigError::Rejected(RCode::BadTime))
+    ));
+}
+
+#[test]
+fn truncated_signatures_get_badtrunc() {
+    let mut session = TsigSession::new(key());
+    let mut request = Message::parse(&session.sign(axfr_query())).unwrap();
+    let record = request.additional.pop().unwrap();
+    let tsig = match record.rdata() {
+        RRData::TSIG(tsig) => tsig,
+        other => panic!("Expected a TSIG record, got {other:?}"),
+    };
+    // Shorter than the 10 bytes a MAC must keep
+    let truncated = TSIG::new(
+        tsig.algorithm().clone(),
+        tsig.time_signed(),
+        tsig.fudge(),
+        tsig.mac()[..8].to_vec(),
+        tsig.original_id(),
+        RCode::NoError,
+        vec![],
+    );
+    request.additional.push(ResourceRecord::new(
+        record.name().clone(),
+        Class::Any,
+        TTL::NoCache,
+        RRData::TSIG(truncated),
+    ));
+    let request = MessageBuilder::from(request).build().serialize();
+
+    let read = exchange(serve(), &request);
+    let response = Message::parse(&read).unwrap();
+    assert_eq!(response.header.flags.r_code, RCode::NotAuth);
+    assert_eq!(tsig_of(&read).error(), RCode::BadTrunc);
+    assert!(tsig_of(&read).mac().is_empty());
+    assert!(matches!(
+        session.verify(&read),
+        Err(TsigError::Rejected(RCode::BadTrunc))
+    ));
+}
+
+#[test]
+fn unsigned_transfers_are_refused() {
+    let read = exchange(serve(), &axfr_query());
+    let response = Message::parse(&read).unwrap();
+    assert_eq!(response.header.flags.r_code, RCode::Refused);
+    assert!(response.answer.is_empty());
+}
@@ -0,0 +1,98 @@
+use std::net::Ipv4Addr;
+
+use vdns_lib::{
+    common::{class::Class, domain_name::DomainName, rr_type::RRType, ttl::TTL},
+    messages::{
+        edns::edns::Edns,
+        message::Message,
+        resource_record::{a::A, resource_record::ResourceRecord, rr_data::RRData},
+    },
+};
+
+fn name(name: &str) -> DomainName {
+    name.parse().unwrap()
+}
+
+fn a_records(owner: &str, count: u8) -> Vec<ResourceRecord> {
+    (0..count)
+        .map(|i| {
+            ResourceRecord::new(
+                name(owner),
+                Class::IN,
+                TTL::from(300),
+                RRData::A(A::new(Ipv4Addr::new(10, 0, 0, i))),
+            )
+        })
+        .collect()
+}
+
+fn response(edns: Option<Edns>) -> Message {
+    let query = Message::new_query(&name("www.example.com."), RRType::A, true, edns);
+    let mut response = Message::new_response(&query, a_records("www.example.com.", 10));
+    response.answer.extend(a_records("cdn.example.com.", 10));
+    response.additional = a_records("ns.example.com.", 10);
+    response
+}
+
+#[test]
+fn message_within_the_limit_is_unchanged() {
+    let message = response(None);
+    let unlimited = message.clone().serialize();
+    assert_eq!(message.serialize_with_limit(unlimited.len()), unlimited);
+
+    let parsed = Message::parse(&unlimited).unwrap();
+    assert!(!parsed.header.flags.tc);
+    assert_eq!(parsed.answer.len(), 20);
+    assert_eq!(parsed.additional.len(), 10);
+}
+
+#[test]
+fn additional_records_are_dropped_first() {
+    let message = response(None);
+    let without_additional = {
+        let mut message = message.clone();
+        message.additional.clear();
+        message.serialize()
+    };
+
+    let serialized = message.serialize_with_limit(without_additional.len() + 10);
+    let parsed = Message::parse(&serialized).unwrap();
+    assert!(!parsed.header.flags.tc);
+    assert_eq!(parsed.answer.len(), 20);
+    assert!(parsed.additional.is_empty());
+}
+
+#[test]
+fn rrsets_that_do_not_fit_are_dropped_and_truncation_is_signalled() {
+    let message = response(Some(Edns::default()));
+    let first_rrset = {
+        let mut message = message.clone();
+        message.answer.truncate(10);
+        message.additional.clear();
+        message.serialize()
+    };
+
+    // Room for the first RRset but not for all of the second
+    let serialized = message.serialize_with_limit(first_rrset.len() + 20);
+    assert!(serialized.len() <= first_rrset.len() + 20);
+
+    let parsed = Message::parse(&serialized).unwrap();
+    assert!(parsed.header.flags.tc);
+    assert_eq!(parsed.answer.len(), 10);
+    assert!(parsed
+        .answer
+        .iter()
+        .all(|r| *r.name() == name("www.example.com.")));
+    assert!(parsed.additional.is_empty());
+    // The OPT record is kept
+    assert!(parsed.edns.is_some());
+}
+
+#[test]
+fn question_is_kept_even_if_nothing_fits() {
+    let serialized = response(None).serialize_with_limit(0);
+    let parsed = Message::parse(&serialized).unwrap();
+    assert!(parsed.header.flags.tc);
+    assert_eq!(parsed.questions.len(), 1);
+    assert!(parsed.answer.is_empty());
+}
@@ -0,0 +1,88 @@
+use vdns_lib::{
+    common::rr_type::RRType,
+    messages::{
+        message::Message,
+        tsig::{
+            keyring::{Keyring, TsigAlgorithm, TsigKey},
+            signing::{is_signed, TsigError, TsigSession},
+        },
+    },
+};
+
+fn key(name: &str) -> TsigKey {
+    TsigKey::new(
+        name.parse().unwrap(),
+        TsigAlgorithm::HmacSha256,
+        b"not a very secret secret".to_vec(),
+    )
+}
+
+fn signed_query(key: TsigKey) -> (TsigSession, Vec<u8>) {
+    let mut session = TsigSession::new(key);
+    let query = Message::new_query(&"home.lan".parse().unwrap(), RRType::AXFR, false, None);
+    let signed = session.sign(query.serialize());
+    (session, signed)
+}
+
+#[test]
+fn signed_request_and_responses_are_verified() {
+    let (mut client, request) = signed_query(key("transfer.key"));
+    assert!(is_signed(&request));
+    assert!(Message::parse(&request).is_ok());
+
+    let keyring = Keyring::new(vec![key("Transfer.Key")]);
+    let mut server = TsigSession::accept_request(&request, &keyring).unwrap();
+
+    // Every message of a multi-message response covers the one before it
+    let query = Message::parse(&request).unwrap();
+    for _ in 0..3 {
+        let response = server.sign(Message::new_response(&query, vec![]).serialize());
+        client.verify(&response).unwrap();
+    }
+}
+
+#[test]
+fn tampered_request_is_rejected() {
+    let (_, mut request) = signed_query(key("transfer.key"));
+    // Flip the recursion desired bit
+    request[2] ^= 1;
+
+    let keyring = Keyring::new(vec![key("transfer.key")]);
+    assert!(matches!(
+        TsigSession::accept_request(&request, &keyring),
+        Err(TsigError::BadSig)
+    ));
+}
+
+#[test]
+fn unknown_key_is_rejected() {
+    let (_, request) = signed_query(key("transfer.key"));
+
+    let keyring = Keyring::new(vec![key("other.key")]);
+    assert!(matches!(
+        TsigSession::accept_request(&request, &keyring),
+        Err(TsigError::BadKey(_))
+    ));
+}
+
+#[test]
+fn key_is_parsed_from_the_command_line_form() {
+    let key: TsigKey = "hmac-sha512:transfer.key:c2VjcmV0".parse().unwrap();
+    assert_eq!(key.name().to_string(), "transfer.key");
+    assert_eq!(*key.algorithm(), TsigAlgorithm::HmacSha512);
+    assert_eq!(key.secret(), b"secret");
+
+    let key: TsigKey = "transfer.key:c2VjcmV0".parse().unwrap();
+    assert_eq!(*key.algorithm(), TsigAlgorithm::HmacSha256);
+}
+
+#[test]
+fn signature_fits_in_the_reserved_length() {
+    let mut session = TsigSession::new(key("transfer.key"));
+    let query = Message::new_query(&"home.lan".parse().unwrap(), RRType::A, false, None);
+    let unsigned = query.serialize();
+    let reserved = session.signature_length();
+
+    let signed = session.sign(unsigned.clone());
+    assert!(signed.len() - unsigned.len() <= reserved);
+}
@@ -0,0 +1,108 @@
+use vdns_lib::{
+    common::character_string::{CharacterString, MAX_CHARACTER_STRING_LENGTH},
+    messages::{parsing::Reader, resource_record::txt::TXT, serializing::Writer},
+};
+
+fn lengths(txt: &TXT) -> Vec<usize> {
+    txt.strings().iter().map(|s| s.data().len()).collect()
+}
+
+fn wire(txt: &TXT) -> Vec<u8> {
+    let mut writer = Writer::new();
+    txt.serialize(&mut writer);
+    writer.get_serialized_message()
+}
+
+#[test]
+fn long_data_is_split_into_character_strings() {
+    let cases = [
+        (0, vec![0]),
+        (1, vec![1]),
+        (254, vec![254]),
+        (255, vec![255]),
+        (256, vec![255, 1]),
+        (510, vec![255, 255]),
+        (511, vec![255, 255, 1]),
+    ];
+    for (length, expected) in cases {
+        let data = (0..length).map(|i| (i % 251) as u8).collect::<Vec<u8>>();
+        let txt = TXT::from_bytes(&data);
+        assert_eq!(lengths(&txt), expected, "{length}");
+        assert_eq!(txt.data(), data);
+
+        // Every string is prefixed by its length on the wire
+        let bytes = wire(&txt);
+        assert_eq!(bytes.len(), length + expected.len());
+        let parsed = TXT::parse(&mut Reader::new(&bytes), bytes.len() as u16).unwrap();
+        assert_eq!(parsed.strings(), txt.strings());
+    }
+}
+
+#[test]
+fn character_strings_hold_at_most_255_bytes() {
+    assert!(CharacterString::new(vec![b'a'; MAX_CHARACTER_STRING_LENGTH]).is_some());
+    assert!(CharacterString::new(vec![b'a'; MAX_CHARACTER_STRING_LENGTH + 1]).is_none());
+
+    let exactly = format!("\"{}\"", "a".repeat(255));
+    assert_eq!(lengths(&exactly.parse().unwrap()), vec![255]);
+    let too_long = format!("\"{}\"", "a".repeat(256));
+    assert!(too_long.parse::<TXT>().is_err());
+    // Escapes count as the byte they stand for
+    let escaped = format!("\"{}\"", "\\\"".repeat(255));
+    assert_eq!(lengths(&escaped.parse().unwrap()), vec![255]);
+}
+
+#[test]
+fn wire_strings_must_fit_in_the_data() {
+    let bytes = [3, b'a', b'b', b'c', 2, b'd', b'e'];
+    let txt = TXT::parse(&mut Reader::new(&bytes), bytes.len() as u16).unwrap();
+    assert_eq!(lengths(&txt), vec![3, 2]);
+    assert_eq!(txt.data(), b"abcde");
+
+    let bytes = [3, b'a', b'b', b'c', 5, b'd', b'e'];
+    assert!(TXT::parse(&mut Reader::new(&bytes), bytes.len() as u16).is_err());
+}
+
+#[test]
+fn presentation_format_is_escaped() {
+    let txt = TXT::new(vec![
+        CharacterString::new(b"say \"hi\"".to_vec()).unwrap(),
+        CharacterString::new(b"back\\slash; (semi)".to_vec()).unwrap(),
+        CharacterString::new(vec![b'a', 0, 0x7f, 0xc3, 0xa9]).unwrap(),
+        CharacterString::default(),
+    ]);
+    let text = txt.to_string();
+    assert_eq!(
+        text,
+        r#""say \"hi\"" "back\\slash; (semi)" "a\000\127\195\169" """#
+    );
+
+    let parsed: TXT = text.parse().unwrap();
+    assert_eq!(parsed.strings(), txt.strings());
+}
+
+#[test]
+fn presentation_format_is_parsed() {
+    let txt: TXT = r#"plain "two words" "with \"quotes\"" \065\066C """#
+        .parse()
+        .unwrap();
+    let strings = txt
+        .strings()
+        .iter()
+        .map(|s| s.data().to_vec())
+        .collect::<Vec<Vec<u8>>>();
+    assert_eq!(
+        strings,
+        vec![
+            b"plain".to_vec(),
+            b"two words".to_vec(),
+            b"with \"quotes\"".to_vec(),
+            b"ABC".to_vec(),
+            vec![],
+        ]
+    );
+
+    assert!("".parse::<TXT>().is_err());
+    assert!(r#""unterminated"#.parse::<TXT>().is_err());
+    assert!(r#""bad \999 escape""#.parse::<TXT>().is_err());
+}
@@ -0,0 +1,84 @@
+use std::net::Ipv4Addr;
+
+use vdns_lib::{
+    common::{domain_name::DomainName, rr_type::RRType, ttl::TTL},
+    messages::{
+        header::flags::OpCode,
+        message::Message,
+        resource_record::{a::A, rr_data::RRData},
+        update::{Prerequisite, Update, UpdateMessage},
+    },
+};
+
+fn name(name: &str) -> DomainName {
+    name.parse().unwrap()
+}
+
+#[test]
+fn update_round_trips_through_the_wire_format() {
+    let address = RRData::A(A::new(Ipv4Addr::new(192, 168, 1, 20)));
+    let update = UpdateMessage::builder(name("home.lan"))
+        .require_name_not_in_use(name("printer.home.lan"))
+        .require_rrset_exists(name("home.lan"), RRType::SOA)
+        .delete_rrset(name("printer.home.lan"), RRType::AAAA)
+        .delete_name(name("old.home.lan"))
+        .delete_record(name("nas.home.lan"), address.clone())
+        .add(name("printer.home.lan"), TTL::from(300), address)
+        .build();
+
+    let parsed = UpdateMessage::parse(&update.serialize()).unwrap();
+    assert_eq!(parsed.header.flags.op_code, OpCode::Update);
+    assert_eq!(parsed.zone.to_string(), "home.lan.");
+
+    assert!(matches!(
+        parsed.prerequisites.as_slice(),
+        [
+            Prerequisite::NameNotInUse(_),
+            Prerequisite::RRSetExists(_, RRType::SOA)
+        ]
+    ));
+    assert!(matches!(
+        parsed.updates.as_slice(),
+        [
+            Update::DeleteRRSet(_, RRType::AAAA),
+            Update::DeleteName(_),
+            Update::DeleteRecord(_, RRData::A(_)),
+            Update::Add(_)
+        ]
+    ));
+}
+
+#[test]
+fn update_requires_a_single_soa_zone() {
+    let query = Message::new_query(&name("home.lan"), RRType::SOA, false, None);
+    assert!(UpdateMessage::parse(&query.serialize()).is_err());
+
+    let mut message = UpdateMessage::builder(name("home.lan"))
+        .build()
+        .into_message();
+    message.questions.clear();
+    assert!(UpdateMessage::parse(&message.serialize()).is_err());
+}
+
+#[test]
+fn only_update_records_may_have_empty_data() {
+    let query = Message::new_query(&name("home.lan"), RRType::A, false, None).serialize();
+    let with_answer = |class: u16| {
+        let mut buf = query.clone();
+        buf[7] = 1;
+        // A pointer to the question name, type A, the class, a TTL of 0 and no data
+        buf.extend_from_slice(&[0xc0, 0x0c, 0, 1]);
+        buf.extend_from_slice(&class.to_be_bytes());
+        buf.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+        buf
+    };
+
+    assert!(Message::parse(&with_answer(1)).is_err());
+    for class in [254, 255] {
+        let message = Message::parse(&with_answer(class)).unwrap();
+        assert!(matches!(
+            message.answer[0].rdata(),
+            RRData::Unknown { data, .. } if data.is_empty()
+        ));
+    }
+}
@@ -0,0 +1,297 @@
+use std::{fs, path::PathBuf};
+
+use vdns_lib::{
+    common::{class::Class, domain_name::DomainName, rr_type::RRType},
+    messages::resource_record::{resource_record::ResourceRecord, rr_data::RRData},
+    zone_file::{
+        error::ZoneFileError,
+        parser::{ZoneFileParser, MAX_GENERATE_RECORDS},
+    },
+};
+
+fn name(name: &str) -> DomainName {
+    name.parse().unwrap()
+}
+
+fn parse(text: &str) -> Result<Vec<ResourceRecord>, ZoneFileError> {
+    ZoneFileParser::new(name("example.com.")).parse_str(text)
+}
+
+fn ttl(record: &ResourceRecord) -> usize {
+    record.seconds_until_expiration()
+}
+
+fn temp_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vdns-zone-{test}-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn parses_defaults_relative_names_and_multi_line_records() {
+    let records = parse(
+        r#"$TTL 1h30m
+@   IN  SOA ns1 hostmaster (
+            2024010101 ; serial
+            1h         ; refresh
+            15m        ; retry
+            1w         ; expire
+            300 )      ; minimum
+        NS  ns1
+ns1 600 A   192.0.2.1
+    IN 60 AAAA 2001:db8::1
+$ORIGIN sub.example.com.
+www CNAME   ns1.example.com.
+mail    MX  10 @
+"#,
+    )
+    .unwrap();
+
+    let names = records
+        .iter()
+        .map(|r| (r.name().to_string(), r.record_type().clone()))
+        .collect::<Vec<(String, RRType)>>();
+    assert_eq!(
+        names,
+        vec![
+            ("example.com.".to_string(), RRType::SOA),
+            ("example.com.".to_string(), RRType::NS),
+            ("ns1.example.com.".to_string(), RRType::A),
+            ("ns1.example.com.".to_string(), RRType::AAAA),
+            ("www.sub.example.com.".to_string(), RRType::CNAME),
+            ("mail.sub.example.com.".to_string(), RRType::MX),
+        ]
+    );
+    assert_eq!(
+        records.iter().map(ttl).collect::<Vec<usize>>(),
+        vec![5400, 5400, 600, 60, 5400, 5400]
+    );
+    assert!(records.iter().all(|r| *r.class() == Class::IN));
+
+    match records[0].rdata() {
+        RRData::SOA(soa) => {
+            assert_eq!(soa.serial(), 2024010101);
+            assert_eq!(soa.minimum(), 300);
+        }
+        other => panic!("Expected SOA data, got {other}"),
+    }
+    match records[1].rdata() {
+        RRData::NS(ns) => assert_eq!(ns, &name("ns1.example.com.")),
+        other => panic!("Expected NS data, got {other}"),
+    }
+    match records[5].rdata() {
+        RRData::MX(mx) => assert!(mx.to_string().contains("sub.example.com.")),
+        other => panic!("Expected MX data, got {other}"),
+    }
+}
+
+#[test]
+fn parses_every_supported_type() {
+    let records = parse(
+        r#"$TTL 300
+@       SOA     ns1 hostmaster 1 3600 600 86400 300
+@       NS      ns1
+ns1     A       192.0.2.1
+ns1     AAAA    2001:db8::1
+www     CNAME   ns1
+txt     TXT     "hello world" "with \"quotes\"" plain
+@       MX      10 mail
+1       PTR     host
+_sip._udp SRV   10 20 5060 sip
+@       NAPTR   100 10 "S" "SIP+D2U" "" _sip._udp
+_svc    SVCB    1 . alpn=h2,h3 port=443
+@       HTTPS   1 . alpn=h2
+@       DNSKEY  257 3 13 ( AwEAAcXq
+                           Yw== )
+@       CDNSKEY 257 3 13 AwEAAcXqYw==
+@       DS      12345 13 2 0123456789ABCDEF 0123456789abcdef
+@       CDS     12345 13 2 0123456789ABCDEF
+@       RRSIG   A 13 2 300 20240201000000 20240101000000 12345 @ c2lnbmF0dXJl
+@       NSEC    www A NS SOA MX RRSIG NSEC DNSKEY
+@       NSEC3   1 0 10 AABBCCDD 2vptu5timamqttgl4luu9kg21e0aor3s A RRSIG
+@       NSEC3PARAM 1 0 10 -
+unknown TYPE65280 \# 3 abcdef
+generic A       \# 4 C0000202
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(records.len(), 22);
+    assert!(matches!(
+        records[20].rdata(),
+        RRData::Unknown { rr_type: 65280, data } if data == &vec![0xAB, 0xCD, 0xEF]
+    ));
+    assert_eq!(records[21].rdata().to_string(), "A(Address = 192.0.2.2)");
+
+    match records[16].rdata() {
+        RRData::RRSIG(sig) => {
+            let text = sig.to_string();
+            assert!(text.contains("20240201000000"), "{text}");
+            assert!(text.contains("example.com."), "{text}");
+        }
+        other => panic!("Expected RRSIG data, got {other}"),
+    }
+    match records[17].rdata() {
+        RRData::NSEC(nsec) => assert!(nsec.to_string().contains("www.example.com.")),
+        other => panic!("Expected NSEC data, got {other}"),
+    }
+}
+
+#[test]
+fn generate_expands_templates() {
+    let records = parse(
+        "$TTL 60\n$GENERATE 1-3 host-$ A 192.0.2.$\n$GENERATE 8-16/8 ${10,3,x} CNAME host\\$$\n",
+    )
+    .unwrap();
+
+    let names = records
+        .iter()
+        .map(|r| r.name().to_string())
+        .collect::<Vec<String>>();
+    assert_eq!(
+        names,
+        vec![
+            "host-1.example.com.",
+            "host-2.example.com.",
+            "host-3.example.com.",
+            "012.example.com.",
+            "01a.example.com.",
+        ]
+    );
+    assert_eq!(records[2].rdata().to_string(), "A(Address = 192.0.2.3)");
+    match records[4].rdata() {
+        RRData::CNAME(target) => assert_eq!(target.to_string(), "host\\$16.example.com."),
+        other => panic!("Expected CNAME data, got {other}"),
+    }
+}
+
+#[test]
+fn generate_is_limited_in_how_many_records_it_creates() {
+    let records = parse("$TTL 60\n$GENERATE 1-65536 host-$ A 192.0.2.1\n").unwrap();
+    assert_eq!(records.len() as u64, MAX_GENERATE_RECORDS);
+    assert_eq!(
+        records.len(),
+        parse("$TTL 60\n$GENERATE 0-131071/2 host-$ A 192.0.2.1\n")
+            .unwrap()
+            .len()
+    );
+
+    let error = parse("$TTL 60\n\n$GENERATE 0-4294967295 host-$ A 192.0.2.1\n").unwrap_err();
+    let position = error.position().unwrap();
+    assert_eq!((position.line, position.column), (3, 11));
+    assert!(error.to_string().contains("4294967296 records"));
+
+    assert!(parse("$TTL 60\n$GENERATE 0-65536 host-$ A 192.0.2.1\n").is_err());
+}
+
+#[test]
+fn generate_limit_counts_every_directive() {
+    let error =
+        parse("$TTL 60\n$GENERATE 1-40000 a-$ A 192.0.2.1\n$GENERATE 1-40000 b-$ A 192.0.2.1\n")
+            .unwrap_err();
+    assert_eq!(error.position().unwrap().line, 3);
+    assert!(error.to_string().contains("only 25536"));
+
+    // Included files count towards the same total
+    let dir = temp_dir("generate");
+    fs::write(
+        dir.join("hosts.zone"),
+        "$GENERATE 1-40000 b-$ A 192.0.2.1\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("main.zone"),
+        "$TTL 60\n$GENERATE 1-40000 a-$ A 192.0.2.1\n$INCLUDE hosts.zone\n",
+    )
+    .unwrap();
+    let result = ZoneFileParser::new(name("example.com.")).parse_file(dir.join("main.zone"));
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(result.unwrap_err().to_string().contains("only 25536"));
+}
+
+#[test]
+fn include_uses_its_own_origin_and_restores_the_outer_one() {
+    let dir = temp_dir("include");
+    fs::write(
+        dir.join("hosts.zone"),
+        "printer A 192.0.2.10\n        AAAA 2001:db8::10\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("main.zone"),
+        "$TTL 1d\n@ SOA ns1 hostmaster 1 1h 15m 1w 5m\n$INCLUDE hosts.zone lan\nnas A 192.0.2.20\n",
+    )
+    .unwrap();
+
+    let records = ZoneFileParser::new(name("example.com."))
+        .parse_file(dir.join("main.zone"))
+        .unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    let names = records
+        .iter()
+        .map(|r| r.name().to_string())
+        .collect::<Vec<String>>();
+    assert_eq!(
+        names,
+        vec![
+            "example.com.",
+            "printer.lan.example.com.",
+            "printer.lan.example.com.",
+            "nas.example.com.",
+        ]
+    );
+}
+
+#[test]
+fn include_of_itself_is_stopped() {
+    let dir = temp_dir("recursive");
+    fs::write(dir.join("loop.zone"), "$INCLUDE loop.zone\n").unwrap();
+
+    let result = ZoneFileParser::new(name("example.com.")).parse_file(dir.join("loop.zone"));
+    fs::remove_dir_all(&dir).unwrap();
+
+    match result {
+        Err(ZoneFileError::Syntax { message, .. }) => assert!(message.contains("nested")),
+        other => panic!("Expected a syntax error, got {other:?}"),
+    }
+}
+
+#[test]
+fn errors_report_line_and_column() {
+    let error = parse("$TTL 300\nwww A 192.0.2.1\nmail MX ten mail\n").unwrap_err();
+    let position = error.position().unwrap();
+    assert_eq!((position.line, position.column), (3, 9));
+    assert!(error.to_string().starts_with("line 3, column 9: "));
+
+    let error = parse("$TTL 300\n\nwww A 192.0.2.1\n  ; comment\nhost BOGUS data\n").unwrap_err();
+    let position = error.position().unwrap();
+    assert_eq!((position.line, position.column), (5, 6));
+
+    let error = parse("$TTL 300\n@ SOA ns1 hostmaster (\n 1 2 3 4 5\n").unwrap_err();
+    let position = error.position().unwrap();
+    assert_eq!((position.line, position.column), (2, 22));
+
+    let error = parse("www A 192.0.2.1\n").unwrap_err();
+    assert!(error.to_string().contains("no TTL"));
+}
+
+#[test]
+fn meta_types_are_rejected() {
+    let error = parse("$TTL 300\nkey TSIG hmac-sha256. 0 300 0 1 0 0\n").unwrap_err();
+    let position = error.position().unwrap();
+    assert_eq!((position.line, position.column), (2, 10));
+    assert!(error.to_string().contains("TSIG"));
+
+    assert!(parse("$TTL 300\nkey TSIG \\# 0\n").is_err());
+    assert!(parse("$TTL 300\nall ANY \\# 0\n").is_err());
+}
+
+#[test]
+fn generic_data_must_match_its_type() {
+    let records = parse("$TTL 300\nwww A \\# 4 C0000201\n").unwrap();
+    assert_eq!(records[0].rdata().to_string(), "A(Address = 192.0.2.1)");
+
+    assert!(parse("$TTL 300\nwww A \\# 0\n").is_err());
+    assert!(parse("$TTL 300\nwww A \\# 5 C000020101\n").is_err());
+}
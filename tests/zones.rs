@@ -0,0 +1,196 @@
+use std::net::Ipv4Addr;
+
+use vdns_lib::{
+    common::{class::Class, domain_name::DomainName, rr_type::RRType, ttl::TTL},
+    messages::{
+        header::flags::RCode,
+        resource_record::{a::A, resource_record::ResourceRecord, rr_data::RRData, soa::SOA},
+        update::{UpdateBuilder, UpdateMessage},
+    },
+};
+
+#[allow(dead_code)]
+#[path = "../vdns_server/src/zones.rs"]
+mod zones;
+
+use zones::Zone;
+
+fn name(name: &str) -> DomainName {
+    name.parse().unwrap()
+}
+
+fn a(last: u8) -> RRData {
+    RRData::A(A::new(Ipv4Addr::new(192, 0, 2, last)))
+}
+
+fn soa(serial: u32) -> RRData {
+    RRData::SOA(SOA::new(
+        name("ns.home.lan."),
+        name("hostmaster.home.lan."),
+        serial,
+        3600,
+        600,
+        86400,
+        300,
+    ))
+}
+
+fn record(owner: &str, rdata: RRData) -> ResourceRecord {
+    ResourceRecord::new(name(owner), Class::IN, TTL::from(300), rdata)
+}
+
+fn zone() -> Zone {
+    Zone::from_records(
+        name("home.lan."),
+        vec![record("home.lan.", soa(10)), record("www.home.lan.", a(1))],
+    )
+    .unwrap()
+}
+
+fn zone_update() -> UpdateBuilder {
+    UpdateMessage::builder(name("home.lan."))
+}
+
+fn serial(zone: &Zone) -> u32 {
+    match zone.soa().unwrap().rdata() {
+        RRData::SOA(soa) => soa.serial(),
+        other => panic!("Expected an SOA record, got {other:?}"),
+    }
+}
+
+fn addresses(zone: &Zone, owner: &str) -> usize {
+    zone.lookup(&name(owner), &RRType::A).len()
+}
+
+#[test]
+fn failed_prerequisites_return_their_rcode() {
+    let cases = [
+        (
+            zone_update().require_name_in_use(name("missing.home.lan.")),
+            RCode::NameError,
+        ),
+        (
+            zone_update().require_name_not_in_use(name("www.home.lan.")),
+            RCode::YXDomain,
+        ),
+        (
+            zone_update().require_rrset_exists(name("www.home.lan."), RRType::AAAA),
+            RCode::NXRRSet,
+        ),
+        (
+            zone_update().require_record(name("www.home.lan."), a(2)),
+            RCode::NXRRSet,
+        ),
+        (
+            zone_update().require_rrset_does_not_exist(name("www.home.lan."), RRType::A),
+            RCode::YXRRSet,
+        ),
+        (
+            zone_update().require_name_in_use(name("www.example.com.")),
+            RCode::NotZone,
+        ),
+    ];
+
+    for (builder, r_code) in cases {
+        let mut zone = zone();
+        let update = builder
+            .add(name("new.home.lan."), TTL::from(300), a(3))
+            .build();
+        assert_eq!(zone.apply_update(&update), r_code);
+        // Nothing is applied when a prerequisite fails
+        assert_eq!(addresses(&zone, "new.home.lan."), 0);
+        assert_eq!(serial(&zone), 10);
+    }
+}
+
+#[test]
+fn updates_outside_the_zone_are_refused() {
+    let mut zone = zone();
+    let update = zone_update()
+        .add(name("new.home.lan."), TTL::from(300), a(3))
+        .add(name("www.example.com."), TTL::from(300), a(4))
+        .build();
+    assert_eq!(zone.apply_update(&update), RCode::NotZone);
+    assert_eq!(addresses(&zone, "new.home.lan."), 0);
+}
+
+#[test]
+fn prerequisites_that_hold_let_the_update_through() {
+    let mut zone = zone();
+    let update = zone_update()
+        .require_name_in_use(name("www.home.lan."))
+        .require_name_not_in_use(name("new.home.lan."))
+        .require_rrset_exists(name("www.home.lan."), RRType::A)
+        .require_rrset_does_not_exist(name("www.home.lan."), RRType::AAAA)
+        .require_record(name("www.home.lan."), a(1))
+        .add(name("new.home.lan."), TTL::from(300), a(3))
+        .delete_record(name("www.home.lan."), a(1))
+        .build();
+    assert_eq!(zone.apply_update(&update), RCode::NoError);
+    assert_eq!(addresses(&zone, "new.home.lan."), 1);
+    assert_eq!(addresses(&zone, "www.home.lan."), 0);
+}
+
+#[test]
+fn serial_is_incremented_once_per_changing_update() {
+    let mut zone = zone();
+    let update = zone_update()
+        .add(name("new.home.lan."), TTL::from(300), a(3))
+        .add(name("new.home.lan."), TTL::from(300), a(4))
+        .build();
+    assert_eq!(zone.apply_update(&update), RCode::NoError);
+    assert_eq!(serial(&zone), 11);
+
+    // Adding what is already there changes nothing
+    assert_eq!(zone.apply_update(&update), RCode::NoError);
+    assert_eq!(serial(&zone), 11);
+}
+
+#[test]
+fn replaced_soa_keeps_its_serial() {
+    let mut zone = zone();
+    let update = zone_update()
+        .add(name("home.lan."), TTL::from(3600), soa(20))
+        .add(name("new.home.lan."), TTL::from(300), a(3))
+        .build();
+    assert_eq!(zone.apply_update(&update), RCode::NoError);
+    assert_eq!(serial(&zone), 20);
+    assert_eq!(zone.lookup(&name("home.lan."), &RRType::SOA).len(), 1);
+
+    // An SOA with an older serial is ignored
+    let update = zone_update()
+        .add(name("home.lan."), TTL::from(3600), soa(15))
+        .build();
+    assert_eq!(zone.apply_update(&update), RCode::NoError);
+    assert_eq!(serial(&zone), 20);
+}
+
+#[test]
+fn names_in_record_data_compare_case_insensitively() {
+    let mut zone = Zone::from_records(
+        name("home.lan."),
+        vec![
+            record("home.lan.", soa(10)),
+            record("sub.home.lan.", RRData::NS(name("ns.home.lan."))),
+        ],
+    )
+    .unwrap();
+    let update = zone_update()
+        .require_record(name("sub.home.lan."), RRData::NS(name("NS.Home.lan.")))
+        .add(
+            name("sub.home.lan."),
+            TTL::from(300),
+            RRData::NS(name("ns.HOME.LAN.")),
+        )
+        .build();
+    assert_eq!(zone.apply_update(&update), RCode::NoError);
+    // The record was already there, so nothing changed
+    assert_eq!(zone.lookup(&name("sub.home.lan."), &RRType::NS).len(), 1);
+    assert_eq!(serial(&zone), 10);
+
+    let update = zone_update()
+        .delete_record(name("sub.home.lan."), RRData::NS(name("Ns.Home.Lan.")))
+        .build();
+    assert_eq!(zone.apply_update(&update), RCode::NoError);
+    assert!(zone.lookup(&name("sub.home.lan."), &RRType::NS).is_empty());
+}
@@ -1,7 +1,7 @@
 use std::net::IpAddr;
 
 use clap::Parser;
-use vdns_lib::common::rr_type::RRType;
+use vdns_lib::{common::rr_type::RRType, messages::tsig::keyring::TsigKey};
 
 /// Program to perform DNS lookups
 #[derive(Parser, Debug)]
@@ -19,6 +19,14 @@ pub struct CLI {
     #[arg(long, short = 'r')]
     pub recurse: bool,
 
+    /// Sign the query with the key and require a signed response, given as [algorithm:]name:base64-secret
+    #[arg(long)]
+    pub tsig_key: Option<TsigKey>,
+
+    /// Send the address exactly as written instead of converting internationalized names to punycode
+    #[arg(long)]
+    pub no_idn: bool,
+
     /// The address to lookup
     #[arg()]
     pub address: String,
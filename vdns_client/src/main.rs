@@ -1,7 +1,8 @@
 use clap::Parser;
+use tracing_subscriber::EnvFilter;
 use vdns_lib::{
-    common::{resolvconf::read_nameserver, rr_type::RRType},
-    lookup,
+    common::{domain_name::DomainName, resolvconf::read_nameserver, rr_type::RRType},
+    lookup, transfer_zone, LookupError,
 };
 
 use crate::cli::CLI;
@@ -11,6 +12,12 @@ pub mod cli;
 fn main() {
     let args = CLI::parse();
 
+    // Parsing diagnostics are enabled with e.g. RUST_LOG=vdns_lib=debug
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+
     let nameserver = args.nameserver.unwrap_or(
         *read_nameserver()
             .expect("Failed to read nameservers file!")
@@ -20,6 +27,42 @@ fn main() {
 
     let rr_type = args.record_type.unwrap_or(RRType::A);
 
-    let message = lookup(&args.address, rr_type, nameserver, args.recurse);
+    let address = if args.no_idn {
+        args.address.parse::<DomainName>()
+    } else {
+        DomainName::from_unicode(&args.address)
+    }
+    .expect("Invalid address");
+    // Show what internationalized names were sent as
+    if address.to_unicode() != address.to_string() {
+        println!("Looking up {} ({address})", address.to_unicode());
+    }
+
+    if rr_type == RRType::AXFR {
+        let records = transfer_zone(&address, nameserver, args.tsig_key.as_ref())
+            .expect("Failed to transfer zone");
+        for record in records.iter() {
+            println!("{record}");
+        }
+        return;
+    }
+
+    let message = match lookup(
+        &address,
+        rr_type,
+        nameserver,
+        args.recurse,
+        args.tsig_key.as_ref(),
+    ) {
+        Ok(message) => message,
+        // Show what could be decoded and why the rest couldn't
+        Err(LookupError::PartialResponse(partial)) => {
+            for err in partial.errors.iter() {
+                println!("Failed to parse part of the response: {err}");
+            }
+            partial.message
+        }
+        Err(err) => panic!("Failed to lookup address: {err}"),
+    };
     println!("Message: {message}");
 }
@@ -0,0 +1,66 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::messages::{parsing::Reader, serializing::Writer};
+
+use super::{
+    parse_error::{ParseError, ParseResult},
+    presentation::{escape_quoted, unescape},
+};
+
+pub const MAX_CHARACTER_STRING_LENGTH: usize = u8::MAX as usize;
+
+/// A <character-string> as defined in RFC 1035 section 3.3, a single length octet followed by up to 255 octets of arbitrary data.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CharacterString {
+    data: Vec<u8>,
+}
+
+impl CharacterString {
+    /// Returns None if the data does not fit in a single character-string
+    pub fn new(data: Vec<u8>) -> Option<Self> {
+        if data.len() > MAX_CHARACTER_STRING_LENGTH {
+            return None;
+        }
+
+        Some(Self { data })
+    }
+
+    pub fn parse(reader: &mut Reader) -> ParseResult<Self> {
+        let length = reader.read_u8()?;
+        let data = reader.read_exact_vec(length as usize)?;
+        Ok(Self { data })
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        writer.write_u8(self.data.len() as u8);
+        for b in self.data.iter() {
+            writer.write_u8(*b);
+        }
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// Presentation format, i.e. quoted with `"` and `\` escaped and non-printable bytes as `\DDD`
+impl Display for CharacterString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\"{}\"", escape_quoted(&self.data))
+    }
+}
+
+/// Parses a single token of presentation format, with its surrounding quotes already removed
+impl FromStr for CharacterString {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let data = unescape(s)?;
+        let length = data.len();
+        CharacterString::new(data).ok_or(ParseError::RRError(format!(
+            "Character-string of {length} bytes is longer than {MAX_CHARACTER_STRING_LENGTH} bytes"
+        )))
+    }
+}
@@ -1,63 +1,111 @@
 use serde::{Deserialize, Serialize};
 
 use crate::messages::{parsing::Reader, serializing::Writer};
-use std::fmt::{Display, Formatter};
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+use tracing::debug;
 
-use super::parse_error::ParseResult;
+use super::parse_error::{ParseError, ParseResult};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Class {
-    Reserved,
+    Reserved(u16),
     IN, // Internet
     CS, // The CSNET class (Obsolete - used only for examples in some obsolete RFCs)
     CH, // Chaos
     HS, // Hesiod
-    Unassigned,
+    None,
+    Any,
+    PrivateUse(u16),
+    Unassigned(u16),
 }
 
 impl Class {
     pub fn parse(reader: &mut Reader) -> ParseResult<Self> {
         let num = reader.read_u16()?;
-        Ok(match num {
-            0 => Class::Reserved,
+        let class = Class::from(num);
+        if let Class::Unassigned(val) = class {
+            debug!("Got unassigned CLASS value {val}");
+        }
+        Ok(class)
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        writer.write_u16(self.into());
+    }
+}
+
+impl From<u16> for Class {
+    fn from(value: u16) -> Self {
+        match value {
+            0 => Class::Reserved(0),
             1 => Class::IN,
             2 => Class::CS,
             3 => Class::CH,
             4 => Class::HS,
-            val => {
-                println!("Got unassigned CLASS value {val}");
-                Class::Unassigned
-            }
-        })
+            254 => Class::None,
+            255 => Class::Any,
+            val @ 65280..=65534 => Class::PrivateUse(val),
+            65535 => Class::Reserved(65535),
+            val => Class::Unassigned(val),
+        }
     }
+}
 
-    pub fn serialize(&self, writer: &mut Writer) {
-        let val: u16 = match self {
-            Class::Reserved => 0,
+impl From<&Class> for u16 {
+    fn from(value: &Class) -> Self {
+        match value {
             Class::IN => 1,
             Class::CS => 2,
             Class::CH => 3,
             Class::HS => 4,
-            Class::Unassigned => 5,
-        };
+            Class::None => 254,
+            Class::Any => 255,
+            Class::Reserved(val) | Class::PrivateUse(val) | Class::Unassigned(val) => *val,
+        }
+    }
+}
 
-        writer.write_u16(val);
+impl From<Class> for u16 {
+    fn from(value: Class) -> Self {
+        u16::from(&value)
     }
 }
 
 impl Display for Class {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Class::Reserved => "Reserved",
-                Class::IN => "IN (Internet)",
-                Class::CS => "CSNET (OBSOLETE!)",
-                Class::CH => "Chaos",
-                Class::HS => "Hesiod",
-                Class::Unassigned => "Unassigned",
-            }
-        )
+        match self {
+            Class::Reserved(val) => write!(f, "Reserved ({val})"),
+            Class::IN => write!(f, "IN (Internet)"),
+            Class::CS => write!(f, "CSNET (OBSOLETE!)"),
+            Class::CH => write!(f, "Chaos"),
+            Class::HS => write!(f, "Hesiod"),
+            Class::None => write!(f, "None"),
+            Class::Any => write!(f, "Any"),
+            Class::PrivateUse(val) => write!(f, "Private Use ({val})"),
+            Class::Unassigned(val) => write!(f, "Unassigned ({val})"),
+        }
+    }
+}
+
+/// Parses the mnemonic of the class, or the generic `CLASSnnn` form for any class (RFC 3597 section 5)
+impl FromStr for Class {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_ascii_uppercase().as_str() {
+            "IN" => Class::IN,
+            "CS" => Class::CS,
+            "CH" => Class::CH,
+            "HS" => Class::HS,
+            "NONE" => Class::None,
+            "ANY" => Class::Any,
+            other => match other.strip_prefix("CLASS").map(|n| n.parse::<u16>()) {
+                Some(Ok(val)) => Class::from(val),
+                _ => return Err(ParseError::RRError(format!("Unknown class '{s}'"))),
+            },
+        })
     }
 }
@@ -1,108 +1,480 @@
-use std::fmt::Display;
+use std::{
+    cmp::Ordering,
+    fmt::{Display, Write},
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
 
+use idna::uts46::{AsciiDenyList, DnsLength, Hyphens, Uts46};
 use serde::{Deserialize, Serialize};
 
-use crate::messages::{parsing::Reader, serializing::Writer};
+use crate::messages::{
+    parsing::{Reader, ReaderError},
+    serializing::Writer,
+};
 
 use super::parse_error::{ParseError, ParseResult};
 
+/// The longest a single label may be (RFC 1035 section 2.3.4)
+pub const MAX_LABEL_LENGTH: usize = 63;
+// The prefix of labels holding punycode (RFC 5890 section 2.3.2.1)
+const ACE_PREFIX: &[u8] = b"xn--";
+
+/// The longest a name may be in the wire format, including the length octets and the root label (RFC 1035 section 2.3.4)
+pub const MAX_NAME_LENGTH: usize = 255;
+
+/// A domain name, kept as the raw bytes of its labels as any octet is allowed in a label (RFC 2181 section 11).
+/// Names are compared and hashed ignoring ASCII case (RFC 4343) and ordered canonically (RFC 4034 section 6.1).
+/// Relative names are compared as if they were relative to the root.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DomainName {
-    pub parts: Vec<String>,
+    labels: Vec<Vec<u8>>,
+    // Whether the name is fully qualified, i.e. ends with the root label
+    absolute: bool,
 }
 
 const MASK: u8 = 0b0011_1111;
 impl DomainName {
+    pub fn root() -> Self {
+        Self {
+            labels: vec![],
+            absolute: true,
+        }
+    }
+
+    /// Creates an absolute name from its labels, most specific first, without the empty root label
+    pub fn from_labels(labels: Vec<Vec<u8>>) -> ParseResult<Self> {
+        if labels.iter().any(|l| l.is_empty()) {
+            return Err(ParseError::EmptyLabel);
+        }
+        if let Some(label) = labels.iter().find(|l| l.len() > MAX_LABEL_LENGTH) {
+            return Err(ParseError::LabelTooLong(label.len()));
+        }
+
+        let name = Self {
+            labels,
+            absolute: true,
+        };
+        if name.wire_length() > MAX_NAME_LENGTH {
+            return Err(ParseError::NameTooLong(name.wire_length()));
+        }
+        Ok(name)
+    }
+
+    /// Parses a name, following compression pointers (RFC 1035 section 4.1.4)
     pub fn parse(reader: &mut Reader) -> ParseResult<DomainName> {
-        let parts = parse_parts(reader)?;
+        let mut iter = LabelIter::new(reader.buffer(), reader.get_index());
+        let labels = iter
+            .by_ref()
+            .map(|label| label.map(|l| l.to_vec()))
+            .collect::<ParseResult<Vec<Vec<u8>>>>()?;
+        reader.set_index(iter.end_index());
 
-        Ok(DomainName { parts })
+        Ok(DomainName {
+            labels,
+            absolute: true,
+        })
     }
 
     pub fn serialize(&self, writer: &mut Writer) {
-        // TODO: Handle when length is more than 6 bits
-        // TODO: Maybe use pointers...
-        for (index, part) in self.parts.iter().enumerate() {
-            let remaining_parts = self.parts.as_slice()[index..].to_vec().join(".");
-            if let Some(ind) = writer.lookup_label(&remaining_parts) {
+        for (index, label) in self.labels.iter().enumerate() {
+            let remaining = DomainName {
+                labels: self.labels[index..].to_vec(),
+                absolute: true,
+            };
+            if let Some(ind) = writer.lookup_label(&remaining) {
                 let goto = (ind as u16) | 0b1100_0000_0000_0000;
                 writer.write_u16(goto);
                 // Pointers always end domain names
                 return;
             }
 
-            writer.track_label(remaining_parts);
-
-            let len = part.len() as u8;
-            let truncated = len & MASK;
-            if truncated != len {
-                panic!("Length of label was too long!");
-            }
-            writer.write_u8(truncated);
-            for b in part.as_bytes() {
-                writer.write_u8(*b);
-            }
+            writer.track_label(remaining);
+            write_label(writer, label);
         }
         // End with an empty len
         writer.write_u8(0);
     }
 
-    pub fn from_string(name: &str) -> Self {
-        Self {
-            parts: name.split(".").map(|s| s.to_string()).collect(),
+    /// Serializes the name without using or registering any compression pointers,
+    /// required for names in the data of most record types (RFC 3597 section 4)
+    pub fn serialize_uncompressed(&self, writer: &mut Writer) {
+        for label in self.labels.iter() {
+            write_label(writer, label);
+        }
+        writer.write_u8(0);
+    }
+
+    pub fn labels(&self) -> &[Vec<u8>] {
+        &self.labels
+    }
+
+    /// The labels from the root and down, the order they are compared in
+    pub fn labels_rev(&self) -> impl Iterator<Item = &[u8]> {
+        self.labels.iter().rev().map(|l| l.as_slice())
+    }
+
+    /// The number of labels, not counting the root
+    pub fn num_labels(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// The name with the first label removed, none for the root
+    pub fn parent(&self) -> Option<DomainName> {
+        if self.is_root() {
+            return None;
+        }
+        Some(DomainName {
+            labels: self.labels[1..].to_vec(),
+            absolute: self.absolute,
+        })
+    }
+
+    /// The name followed by the other name, e.g. a relative `www` followed by `example.com`.
+    /// The result is absolute if the other name is.
+    pub fn append(&self, other: &DomainName) -> ParseResult<DomainName> {
+        let mut labels = self.labels.clone();
+        labels.extend(other.labels.iter().cloned());
+        let mut name = DomainName::from_labels(labels)?;
+        name.absolute = other.absolute;
+        Ok(name)
+    }
+
+    /// Whether the first label is the `*` wildcard label (RFC 4592)
+    pub fn is_wildcard(&self) -> bool {
+        self.labels.first().is_some_and(|l| l == b"*")
+    }
+
+    /// The length of the uncompressed name in the wire format
+    pub fn wire_length(&self) -> usize {
+        self.labels.iter().map(|l| l.len() + 1).sum::<usize>() + 1
+    }
+
+    /// The name with another label in front of it, e.g. `www` in front of `example.com`
+    pub fn prepend_label(&self, label: &[u8]) -> ParseResult<DomainName> {
+        let mut labels = vec![label.to_vec()];
+        labels.extend(self.labels.iter().cloned());
+        let mut name = DomainName::from_labels(labels)?;
+        name.absolute = self.absolute;
+        Ok(name)
+    }
+
+    /// The name with all ASCII letters in lower case
+    pub fn to_lowercase(&self) -> DomainName {
+        DomainName {
+            labels: self.labels.iter().map(|l| l.to_ascii_lowercase()).collect(),
+            absolute: self.absolute,
+        }
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.labels.is_empty() && self.absolute
+    }
+
+    /// Whether the name is fully qualified, names from messages always are
+    pub fn is_absolute(&self) -> bool {
+        self.absolute
+    }
+
+    /// Parses a name that may contain Unicode, converting those labels to A-labels (punycode)
+    /// as described by UTS #46 with the IDNA 2008 rules, e.g. `räksmörgås.se` becomes `xn--rksmrgs-5wao1o.se`
+    pub fn from_unicode(name: &str) -> ParseResult<DomainName> {
+        let parsed: DomainName = name.parse()?;
+        let mut labels = vec![];
+        for label in parsed.labels.into_iter() {
+            if label.is_ascii() {
+                labels.push(label);
+                continue;
+            }
+
+            let ascii = Uts46::new()
+                .to_ascii(
+                    &label,
+                    AsciiDenyList::EMPTY,
+                    Hyphens::Allow,
+                    DnsLength::Ignore,
+                )
+                .map_err(|_| ParseError::InvalidIdn(String::from_utf8_lossy(&label).to_string()))?;
+            // Some full stops, like the ideographic one, separate labels as well
+            labels.extend(ascii.split('.').map(|l| l.as_bytes().to_vec()));
+        }
+
+        let mut name = DomainName::from_labels(labels)?;
+        name.absolute = parsed.absolute;
+        Ok(name)
+    }
+
+    /// The name in the presentation format but with A-labels shown as the Unicode they encode (U-labels),
+    /// labels that aren't valid punycode are shown as they are
+    pub fn to_unicode(&self) -> String {
+        if self.is_root() {
+            return ".".to_string();
+        }
+
+        let mut name = String::new();
+        for (index, label) in self.labels.iter().enumerate() {
+            if index > 0 {
+                name.push('.');
+            }
+
+            let is_a_label = label.len() > ACE_PREFIX.len()
+                && label[..ACE_PREFIX.len()].eq_ignore_ascii_case(ACE_PREFIX);
+            if is_a_label {
+                let (unicode, result) =
+                    Uts46::new().to_unicode(label, AsciiDenyList::EMPTY, Hyphens::Allow);
+                if result.is_ok() {
+                    name.push_str(&unicode);
+                    continue;
+                }
+            }
+            write_escaped_label(&mut name, label).expect("Writing to a string can't fail");
+        }
+
+        if self.absolute {
+            name.push('.');
         }
+        name
+    }
+
+    /// Whether this name is the other name or below it, compared case-insensitively
+    pub fn is_subdomain_of(&self, other: &DomainName) -> bool {
+        self.labels.len() >= other.labels.len()
+            && self
+                .labels
+                .iter()
+                .rev()
+                .zip(other.labels.iter().rev())
+                .all(|(a, b)| a.eq_ignore_ascii_case(b))
     }
 }
 
-impl Display for DomainName {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.parts.join("."))
+/// Parses a name in the presentation format (RFC 1035 section 5.1) where `\.` is a dot within a label and
+/// `\DDD` is the byte with the decimal value DDD. Names ending with a dot are absolute, `.` is the root.
+impl FromStr for DomainName {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "." {
+            return Ok(DomainName::root());
+        }
+
+        let bytes = s.as_bytes();
+        let mut labels = vec![];
+        let mut label = vec![];
+        let mut absolute = false;
+        let mut index = 0;
+        while index < bytes.len() {
+            match bytes[index] {
+                b'\\' => {
+                    let escaped = &bytes[index + 1..];
+                    match escaped {
+                        [] => return Err(ParseError::InvalidEscape(s.to_string())),
+                        [d0, d1, d2, ..] if [d0, d1, d2].iter().all(|d| d.is_ascii_digit()) => {
+                            let value = (*d0 - b'0') as u16 * 100
+                                + (*d1 - b'0') as u16 * 10
+                                + (*d2 - b'0') as u16;
+                            let value = u8::try_from(value)
+                                .map_err(|_| ParseError::InvalidEscape(s.to_string()))?;
+                            label.push(value);
+                            index += 4;
+                        }
+                        [d, ..] if d.is_ascii_digit() => {
+                            return Err(ParseError::InvalidEscape(s.to_string()))
+                        }
+                        [other, ..] => {
+                            label.push(*other);
+                            index += 2;
+                        }
+                    }
+                    continue;
+                }
+                b'.' => {
+                    if label.is_empty() {
+                        return Err(ParseError::EmptyLabel);
+                    }
+                    labels.push(std::mem::take(&mut label));
+                    absolute = index == bytes.len() - 1;
+                }
+                other => label.push(other),
+            }
+            index += 1;
+        }
+
+        if !label.is_empty() {
+            labels.push(label);
+        } else if !absolute {
+            return Err(ParseError::EmptyLabel);
+        }
+
+        let mut name = DomainName::from_labels(labels)?;
+        name.absolute = absolute;
+        Ok(name)
     }
 }
 
-fn parse_parts(reader: &mut Reader) -> ParseResult<Vec<String>> {
-    let mut parts = vec![];
+/// Iterates the labels of a name in a message without copying them, following compression pointers.
+/// Pointers must point to somewhere before everything read for the name so far, which rules out loops.
+pub struct LabelIter<'a> {
+    buf: &'a [u8],
+    index: usize,
+    // Pointers must point before this
+    limit: usize,
+    length: usize,
+    // Where the name ends in the message, known once the first pointer is followed
+    end_index: Option<usize>,
+    done: bool,
+}
 
-    loop {
-        let oct = reader.read_u8()?;
-        if oct == 0 {
-            break;
+impl<'a> LabelIter<'a> {
+    /// Iterates the name starting at the index of the message
+    pub fn new(buf: &'a [u8], index: usize) -> Self {
+        Self {
+            buf,
+            index,
+            limit: index,
+            length: 1,
+            end_index: None,
+            done: false,
         }
+    }
 
-        let remainder = oct & MASK;
+    /// The index right after the name in the message, once all labels have been read
+    pub fn end_index(&self) -> usize {
+        self.end_index.unwrap_or(self.index)
+    }
 
-        match oct >> 6 {
-            0b00 => {
-                // Label
-                parts.push(parse_label(reader, remainder)?);
-            }
-            0b11 => {
-                // Pointer
-                let second_byte = reader.read_u8()?;
-                let new_index = (((0u16 | remainder as u16) << 8) | second_byte as u16) as usize;
-                let old_index = reader.get_index();
-                reader.set_index(new_index);
-                parts.append(&mut parse_parts(reader)?);
-                reader.set_index(old_index);
-                // Always ends after pointer
-                return Ok(parts);
+    fn next_label(&mut self) -> ParseResult<Option<&'a [u8]>> {
+        loop {
+            let offset = self.index;
+            let oct = *self.buf.get(offset).ok_or(ReaderError::U8)?;
+            self.index += 1;
+            if oct == 0 {
+                return Ok(None);
             }
-            bits => {
-                return Err(ParseError::DomainNameError(format!(
-                    "Got invalid name part prefix {bits:0b}, expected label (00) or pointer (11) | currently have parts '{}'", parts.join(", ")
-                )));
+
+            let remainder = oct & MASK;
+
+            match oct >> 6 {
+                0b00 => {
+                    // Label
+                    let end = self.index + remainder as usize;
+                    let label = self.buf.get(self.index..end).ok_or(ReaderError::Vec)?;
+                    self.index = end;
+                    self.length += label.len() + 1;
+                    if self.length > MAX_NAME_LENGTH {
+                        return Err(ParseError::NameTooLong(self.length));
+                    }
+                    return Ok(Some(label));
+                }
+                0b11 => {
+                    // Pointer
+                    let second_byte = *self.buf.get(self.index).ok_or(ReaderError::U8)?;
+                    self.index += 1;
+                    let target = ((remainder as usize) << 8) | second_byte as usize;
+                    if target >= offset {
+                        return Err(ParseError::ForwardPointer { offset, target });
+                    }
+                    if target >= self.limit {
+                        return Err(ParseError::PointerLoop { offset, target });
+                    }
+
+                    self.end_index.get_or_insert(self.index);
+                    self.limit = target;
+                    self.index = target;
+                }
+                bits => return Err(ParseError::InvalidLabelType { offset, bits }),
             }
         }
     }
+}
+
+impl<'a> Iterator for LabelIter<'a> {
+    type Item = ParseResult<&'a [u8]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let next = self.next_label();
+        if !matches!(next, Ok(Some(_))) {
+            self.done = true;
+        }
+        next.transpose()
+    }
+}
 
-    Ok(parts)
+impl PartialEq for DomainName {
+    fn eq(&self, other: &Self) -> bool {
+        self.labels.len() == other.labels.len() && self.is_subdomain_of(other)
+    }
 }
 
-fn parse_label(reader: &mut Reader, length: u8) -> ParseResult<String> {
-    let bytes = reader.read_vec(length as usize)?;
-    Ok(
-        String::from_utf8(bytes.clone()).or(Err(ParseError::DomainNameError(format!(
-            "Failed to parse domain name part {bytes:?} to utf8 string"
-        ))))?,
-    )
+impl Eq for DomainName {}
+
+impl Hash for DomainName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.labels.len().hash(state);
+        for label in self.labels.iter() {
+            label.to_ascii_lowercase().hash(state);
+        }
+    }
+}
+
+impl Ord for DomainName {
+    /// Compares the labels from the root and down as lowercase octet strings,
+    /// a name sorts before the names below it (RFC 4034 section 6.1)
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.labels_rev()
+            .map(|l| l.to_ascii_lowercase())
+            .cmp(other.labels_rev().map(|l| l.to_ascii_lowercase()))
+    }
+}
+
+impl PartialOrd for DomainName {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn write_label(writer: &mut Writer, label: &[u8]) {
+    if label.len() > MAX_LABEL_LENGTH {
+        panic!("Length of label was too long!");
+    }
+    writer.write_u8(label.len() as u8);
+    for b in label {
+        writer.write_u8(*b);
+    }
+}
+
+impl Display for DomainName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_root() {
+            return write!(f, ".");
+        }
+
+        for (index, label) in self.labels.iter().enumerate() {
+            if index > 0 {
+                write!(f, ".")?;
+            }
+            write_escaped_label(f, label)?;
+        }
+
+        if self.absolute {
+            write!(f, ".")?;
+        }
+        Ok(())
+    }
+}
+
+/// Escapes the bytes that can't be written as is in the presentation format (RFC 1035 section 5.1)
+fn write_escaped_label(f: &mut impl Write, label: &[u8]) -> std::fmt::Result {
+    for &b in label {
+        match b {
+            b'.' | b'\\' | b'"' | b'(' | b')' | b';' | b'@' | b'$' => write!(f, "\\{}", b as char)?,
+            0x21..=0x7E => write!(f, "{}", b as char)?,
+            _ => write!(f, "\\{b:03}")?,
+        }
+    }
+    Ok(())
 }
@@ -1,7 +1,9 @@
+pub mod character_string;
 pub mod class;
 pub mod domain_name;
 pub mod formatting;
 pub mod parse_error;
+pub mod presentation;
 pub mod q_class;
 pub mod resolvconf;
 pub mod rr_type;
@@ -1,21 +1,90 @@
+use std::fmt::{Display, Formatter};
+
 use crate::messages::parsing::ReaderError;
 
-#[derive(Debug, Clone, thiserror::Error)]
-pub enum ParseError {
-    #[error("Reader error")]
-    BufferReadError(#[from] ReaderError),
-    #[error("Question error")]
+/// The part of a message that was being parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    Header,
     Question,
-    #[error("Answer error")]
     Answer,
-    #[error("Authority error")]
     Authority,
-    #[error("Additional error")]
     Additional,
-    #[error("Domain name error, '{0}'")]
-    DomainNameError(String),
+}
+
+impl Display for Section {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Section::Header => write!(f, "header"),
+            Section::Question => write!(f, "question"),
+            Section::Answer => write!(f, "answer"),
+            Section::Authority => write!(f, "authority"),
+            Section::Additional => write!(f, "additional"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ParseError {
+    #[error("Reader error")]
+    BufferReadError(#[from] ReaderError),
+    /// The entry at `index` in the section could not be parsed, reading stopped at `offset`
+    #[error("Invalid {section} entry {index} at offset {offset}: {cause}")]
+    InSection {
+        section: Section,
+        index: usize,
+        offset: usize,
+        #[source]
+        cause: Box<ParseError>,
+    },
+    #[error("Invalid label type {bits:#04b} at offset {offset}, expected a label (00) or a pointer (11)")]
+    InvalidLabelType { offset: usize, bits: u8 },
+    #[error("Compression pointer at offset {offset} points forward to {target}")]
+    ForwardPointer { offset: usize, target: usize },
+    #[error("Compression pointer at offset {offset} points back into the name itself at {target}")]
+    PointerLoop { offset: usize, target: usize },
+    #[error("Label of {0} bytes is longer than 63 bytes")]
+    LabelTooLong(usize),
+    #[error("Domain name of {0} bytes is longer than 255 bytes")]
+    NameTooLong(usize),
+    #[error("Domain name has an empty label")]
+    EmptyLabel,
+    #[error("Invalid escape sequence in domain name '{0}'")]
+    InvalidEscape(String),
+    #[error("Invalid internationalized label '{0}'")]
+    InvalidIdn(String),
     #[error("Resource record error, '{0}'")]
     RRError(String),
 }
 
+impl ParseError {
+    pub fn in_section(self, section: Section, index: usize, offset: usize) -> Self {
+        ParseError::InSection {
+            section,
+            index,
+            offset,
+            cause: Box::new(self),
+        }
+    }
+
+    /// The most precise offset in the message that is known for the error
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            ParseError::InSection { offset, cause, .. } => cause.offset().or(Some(*offset)),
+            ParseError::InvalidLabelType { offset, .. }
+            | ParseError::ForwardPointer { offset, .. }
+            | ParseError::PointerLoop { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// The error without the section it occurred in
+    pub fn cause(&self) -> &ParseError {
+        match self {
+            ParseError::InSection { cause, .. } => cause.cause(),
+            _ => self,
+        }
+    }
+}
+
 pub type ParseResult<T> = Result<T, ParseError>;
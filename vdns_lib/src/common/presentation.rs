@@ -0,0 +1,131 @@
+use std::str::FromStr;
+
+use data_encoding::Encoding;
+
+use super::parse_error::{ParseError, ParseResult};
+
+/// Parses a single field of presentation format data, e.g. a number, naming the field on failure
+pub fn parse_field<T: FromStr>(token: &str, field: &str) -> ParseResult<T> {
+    token
+        .parse()
+        .map_err(|_| ParseError::RRError(format!("Invalid {field} '{token}'")))
+}
+
+/// Decodes e.g. the base64 or hex part of presentation format data, which may be split into several tokens
+pub fn decode_field(encoding: &Encoding, tokens: &[String], field: &str) -> ParseResult<Vec<u8>> {
+    let text = tokens.concat();
+    encoding
+        .decode(text.as_bytes())
+        .map_err(|_| ParseError::RRError(format!("Invalid {field} '{text}'")))
+}
+
+/// Splits presentation format text into whitespace separated tokens.
+/// Surrounding quotes are removed but escapes are kept as is, see `unescape`.
+pub fn split_tokens(text: &str) -> ParseResult<Vec<String>> {
+    let mut tokens = vec![];
+    let mut current = String::new();
+    let mut in_token = false;
+    let mut quoted = false;
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                current.push(c);
+                current.push(chars.next().ok_or(ParseError::RRError(format!(
+                    "Text '{text}' ends with an unfinished escape"
+                )))?);
+                in_token = true;
+            }
+            '"' => {
+                quoted = !quoted;
+                in_token = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_token = true;
+            }
+        }
+    }
+
+    if quoted {
+        return Err(ParseError::RRError(format!(
+            "Text '{text}' has an unterminated quote"
+        )));
+    }
+    if in_token {
+        tokens.push(current);
+    }
+
+    Ok(tokens)
+}
+
+/// Resolves the `\X` and `\DDD` escapes of RFC 1035 section 5.1
+pub fn unescape(text: &str) -> ParseResult<Vec<u8>> {
+    let bytes = text.as_bytes();
+    let mut data = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        if bytes[index] != b'\\' {
+            data.push(bytes[index]);
+            index += 1;
+            continue;
+        }
+
+        match bytes.get(index + 1..index + 4) {
+            Some(digits) if digits.iter().all(u8::is_ascii_digit) => {
+                let val = digits
+                    .iter()
+                    .fold(0u16, |acc, d| acc * 10 + (d - b'0') as u16);
+                let val = u8::try_from(val).map_err(|_| {
+                    ParseError::RRError(format!("Escape \\{val} in '{text}' is out of range"))
+                })?;
+                data.push(val);
+                index += 4;
+            }
+            _ => {
+                let escaped = bytes.get(index + 1).ok_or(ParseError::RRError(format!(
+                    "Text '{text}' ends with an unfinished escape"
+                )))?;
+                data.push(*escaped);
+                index += 2;
+            }
+        }
+    }
+
+    Ok(data)
+}
+
+/// Escapes data for use outside of quotes, special characters are escaped with `\` and
+/// whitespace or non-printable bytes as `\DDD`
+pub fn escape(data: &[u8]) -> String {
+    escape_with(data, b"\"\\;() ")
+}
+
+/// Escapes data for use within quotes
+pub fn escape_quoted(data: &[u8]) -> String {
+    escape_with(data, b"\"\\")
+}
+
+fn escape_with(data: &[u8], special: &[u8]) -> String {
+    let mut escaped = String::with_capacity(data.len());
+    for &b in data.iter() {
+        match b {
+            b' ' if special.contains(&b) => escaped.push_str("\\032"),
+            b if special.contains(&b) => {
+                escaped.push('\\');
+                escaped.push(b as char);
+            }
+            0x20..=0x7E => escaped.push(b as char),
+            other => escaped.push_str(&format!("\\{other:03}")),
+        }
+    }
+    escaped
+}
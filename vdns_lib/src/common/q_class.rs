@@ -3,67 +3,76 @@ use std::fmt::{Display, Formatter};
 
 use super::parse_error::ParseResult;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum QClass {
-    Reserved,
+    Reserved(u16),
     IN, // Internet
-    Unassigned,
+    Unassigned(u16),
     CH, // Chaos
     HS, // Hesiod
     None,
     Any,
-    PrivateUse,
+    PrivateUse(u16),
 }
 
 impl QClass {
     pub fn parse(reader: &mut Reader) -> ParseResult<QClass> {
-        let num = reader.read_u16()?;
-        Ok(match num {
-            0 => QClass::Reserved,
+        Ok(QClass::from(reader.read_u16()?))
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        writer.write_u16(self.into());
+    }
+}
+
+impl From<u16> for QClass {
+    fn from(value: u16) -> Self {
+        match value {
+            0 => QClass::Reserved(0),
             1 => QClass::IN,
-            2 => QClass::Unassigned,
+            2 => QClass::Unassigned(2),
             3 => QClass::CH,
             4 => QClass::HS,
-            5..=253 => QClass::Unassigned,
+            val @ 5..=253 => QClass::Unassigned(val),
             254 => QClass::None,
             255 => QClass::Any,
-            256..=65279 => QClass::Unassigned,
-            65280..=65534 => QClass::PrivateUse,
-            65535 => QClass::Reserved,
-        })
+            val @ 256..=65279 => QClass::Unassigned(val),
+            val @ 65280..=65534 => QClass::PrivateUse(val),
+            65535 => QClass::Reserved(65535),
+        }
     }
+}
 
-    pub fn serialize(&self, writer: &mut Writer) {
-        let val: u16 = match self {
-            QClass::Reserved => 0,
+impl From<&QClass> for u16 {
+    fn from(value: &QClass) -> Self {
+        match value {
             QClass::IN => 1,
-            QClass::Unassigned => 2,
             QClass::CH => 3,
             QClass::HS => 4,
             QClass::None => 254,
             QClass::Any => 255,
-            QClass::PrivateUse => 65280,
-        };
+            QClass::Reserved(val) | QClass::Unassigned(val) | QClass::PrivateUse(val) => *val,
+        }
+    }
+}
 
-        writer.write_u16(val);
+impl From<QClass> for u16 {
+    fn from(value: QClass) -> Self {
+        u16::from(&value)
     }
 }
 
 impl Display for QClass {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                QClass::Reserved => "Reserved",
-                QClass::IN => "IN (Internet)",
-                QClass::Unassigned => "Unassigned",
-                QClass::CH => "Chaos",
-                QClass::HS => "Hesiod",
-                QClass::None => "None",
-                QClass::Any => "Any",
-                QClass::PrivateUse => "Private Use",
-            }
-        )
+        match self {
+            QClass::Reserved(val) => write!(f, "Reserved ({val})"),
+            QClass::IN => write!(f, "IN (Internet)"),
+            QClass::Unassigned(val) => write!(f, "Unassigned ({val})"),
+            QClass::CH => write!(f, "Chaos"),
+            QClass::HS => write!(f, "Hesiod"),
+            QClass::None => write!(f, "None"),
+            QClass::Any => write!(f, "Any"),
+            QClass::PrivateUse(val) => write!(f, "Private Use ({val})"),
+        }
     }
 }
@@ -13,7 +13,6 @@ const DEFAULT_FILE_PATH: &str = "/etc/resolv.conf";
 
 pub fn read_nameserver() -> Result<Vec<IpAddr>, ResolvConfErr> {
     let path = Path::new(DEFAULT_FILE_PATH);
-    if path.exists() && path.is_file() {}
     let nameservers = fs::read_to_string(path)?;
     let nameservers = nameservers
         .lines()
@@ -1,14 +1,16 @@
 use serde::{Deserialize, Serialize};
 
 use crate::messages::{parsing::Reader, serializing::Writer};
-use std::fmt::{Display, Formatter};
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
 
-use super::parse_error::ParseResult;
+use super::parse_error::{ParseError, ParseResult};
 
 // Taken from: https://www.iana.org/assignments/dns-parameters/dns-parameters.xhtml#dns-parameters-4
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RRType {
-    Zero,
     A,
     NS,
     MD,
@@ -98,19 +100,41 @@ pub enum RRType {
     AMTRELAY,
     TA,
     DLV,
-    PrivateUse,
-    Reserved,
-    ReservedFuture,
-    ReservedPrivate,
-    ReservedStandardsAction,
-    Unassigned,
+    PrivateUse(u16),
+    Reserved(u16),
+    Unassigned(u16),
 }
 
 impl RRType {
     pub fn parse(reader: &mut Reader) -> ParseResult<RRType> {
-        let val = reader.read_u16()?;
-        Ok(match val {
-            0 => RRType::Reserved,
+        Ok(RRType::from(reader.read_u16()?))
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        writer.write_u16(self.into());
+    }
+
+    /// Whether the type is only used in queries or to carry message metadata, rather than for data
+    /// that can be stored in a zone (RFC 6895 section 3.1)
+    pub fn is_meta(&self) -> bool {
+        matches!(
+            self,
+            RRType::OPT
+                | RRType::TKEY
+                | RRType::TSIG
+                | RRType::IXFR
+                | RRType::AXFR
+                | RRType::MAILB
+                | RRType::MAILA
+                | RRType::All
+        )
+    }
+}
+
+impl From<u16> for RRType {
+    fn from(value: u16) -> Self {
+        match value {
+            0 => RRType::Reserved(0),
             1 => RRType::A,
             2 => RRType::NS,
             3 => RRType::MD,
@@ -164,7 +188,7 @@ impl RRType {
             51 => RRType::NSEC3PARAM,
             52 => RRType::TLSA,
             53 => RRType::SMIMEA,
-            54 => RRType::Unassigned,
+            54 => RRType::Unassigned(54),
             55 => RRType::HIP,
             56 => RRType::NINFO,
             57 => RRType::RKEY,
@@ -176,7 +200,7 @@ impl RRType {
             63 => RRType::ZONEMD,
             64 => RRType::SVCB,
             65 => RRType::HTTPS,
-            66..=98 => RRType::Unassigned,
+            val @ 66..=98 => RRType::Unassigned(val),
             99 => RRType::SPF,
             100 => RRType::UINFO,
             101 => RRType::UID,
@@ -188,7 +212,7 @@ impl RRType {
             107 => RRType::LP,
             108 => RRType::EUI48,
             109 => RRType::EUI64,
-            110..=248 => RRType::Unassigned,
+            val @ 110..=248 => RRType::Unassigned(val),
             249 => RRType::TKEY,
             250 => RRType::TSIG,
             251 => RRType::IXFR,
@@ -201,18 +225,19 @@ impl RRType {
             258 => RRType::AVC,
             259 => RRType::DOA,
             260 => RRType::AMTRELAY,
-            261..=32767 => RRType::Unassigned,
+            val @ 261..=32767 => RRType::Unassigned(val),
             32768 => RRType::TA,
             32769 => RRType::DLV,
-            32770..=65279 => RRType::Unassigned,
-            65280..=65534 => RRType::PrivateUse,
-            65535 => RRType::Reserved,
-        })
+            val @ 32770..=65279 => RRType::Unassigned(val),
+            val @ 65280..=65534 => RRType::PrivateUse(val),
+            65535 => RRType::Reserved(65535),
+        }
     }
+}
 
-    pub fn serialize(&self, writer: &mut Writer) {
-        writer.write_u16(match self {
-            RRType::Reserved => 0,
+impl From<&RRType> for u16 {
+    fn from(value: &RRType) -> Self {
+        match value {
             RRType::A => 1,
             RRType::NS => 2,
             RRType::MD => 3,
@@ -266,7 +291,6 @@ impl RRType {
             RRType::NSEC3PARAM => 51,
             RRType::TLSA => 52,
             RRType::SMIMEA => 53,
-            RRType::Unassigned => 54,
             RRType::HIP => 55,
             RRType::NINFO => 56,
             RRType::RKEY => 57,
@@ -301,33 +325,230 @@ impl RRType {
             RRType::AVC => 258,
             RRType::DOA => 259,
             RRType::AMTRELAY => 260,
-            RRType::TA => 36827,
-            RRType::DLV => 36927,
-            RRType::PrivateUse => 65280, // Can be between 65280-65534, picked one
-            RRType::Zero => todo!("RRType Zero not implemented"),
-            RRType::ReservedFuture => todo!(),
-            RRType::ReservedPrivate => todo!(),
-            RRType::ReservedStandardsAction => todo!(),
-        });
+            RRType::TA => 32768,
+            RRType::DLV => 32769,
+            RRType::PrivateUse(val) | RRType::Reserved(val) | RRType::Unassigned(val) => *val,
+        }
+    }
+}
+
+impl From<RRType> for u16 {
+    fn from(value: RRType) -> Self {
+        u16::from(&value)
     }
 }
 
 impl Display for RRType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        let mnemonic = match self {
+            RRType::A => "A",
+            RRType::NS => "NS",
+            RRType::MD => "MD",
+            RRType::MF => "MF",
+            RRType::CNAME => "CNAME",
+            RRType::SOA => "SOA",
+            RRType::MB => "MB",
+            RRType::MG => "MG",
+            RRType::MR => "MR",
+            RRType::NULL => "NULL",
+            RRType::WKS => "WKS",
+            RRType::PTR => "PTR",
+            RRType::HINFO => "HINFO",
+            RRType::MINFO => "MINFO",
+            RRType::MX => "MX",
+            RRType::TXT => "TXT",
+            RRType::RP => "RP",
+            RRType::AFSDB => "AFSDB",
+            RRType::X25 => "X25",
+            RRType::ISDN => "ISDN",
+            RRType::RT => "RT",
+            RRType::NSAP => "NSAP",
+            RRType::NsapPtr => "NSAP-PTR",
+            RRType::SIG => "SIG",
+            RRType::KEY => "KEY",
+            RRType::PX => "PX",
+            RRType::GPOS => "GPOS",
+            RRType::AAAA => "AAAA",
+            RRType::LOC => "LOC",
+            RRType::NXT => "NXT",
+            RRType::EID => "EID",
+            RRType::NIMLOC => "NIMLOC",
+            RRType::SRV => "SRV",
+            RRType::ATMA => "ATMA",
+            RRType::NAPTR => "NAPTR",
+            RRType::KX => "KX",
+            RRType::CERT => "CERT",
+            RRType::A6 => "A6",
+            RRType::DNAME => "DNAME",
+            RRType::SINK => "SINK",
+            RRType::OPT => "OPT",
+            RRType::APL => "APL",
+            RRType::DS => "DS",
+            RRType::SSHFP => "SSHFP",
+            RRType::IPSECKEY => "IPSECKEY",
+            RRType::RRSIG => "RRSIG",
+            RRType::NSEC => "NSEC",
+            RRType::DNSKEY => "DNSKEY",
+            RRType::DHCID => "DHCID",
+            RRType::NSEC3 => "NSEC3",
+            RRType::NSEC3PARAM => "NSEC3PARAM",
+            RRType::TLSA => "TLSA",
+            RRType::SMIMEA => "SMIMEA",
+            RRType::HIP => "HIP",
+            RRType::NINFO => "NINFO",
+            RRType::RKEY => "RKEY",
+            RRType::TALINK => "TALINK",
+            RRType::CDS => "CDS",
+            RRType::CDNSKEY => "CDNSKEY",
+            RRType::OPENPGPKEY => "OPENPGPKEY",
+            RRType::CSYNC => "CSYNC",
+            RRType::ZONEMD => "ZONEMD",
+            RRType::SVCB => "SVCB",
+            RRType::HTTPS => "HTTPS",
+            RRType::SPF => "SPF",
+            RRType::UINFO => "UINFO",
+            RRType::UID => "UID",
+            RRType::GID => "GID",
+            RRType::UNSPEC => "UNSPEC",
+            RRType::NID => "NID",
+            RRType::L32 => "L32",
+            RRType::L64 => "L64",
+            RRType::LP => "LP",
+            RRType::EUI48 => "EUI48",
+            RRType::EUI64 => "EUI64",
+            RRType::TKEY => "TKEY",
+            RRType::TSIG => "TSIG",
+            RRType::IXFR => "IXFR",
+            RRType::AXFR => "AXFR",
+            RRType::MAILB => "MAILB",
+            RRType::MAILA => "MAILA",
+            RRType::All => "ANY",
+            RRType::URI => "URI",
+            RRType::CAA => "CAA",
+            RRType::AVC => "AVC",
+            RRType::DOA => "DOA",
+            RRType::AMTRELAY => "AMTRELAY",
+            RRType::TA => "TA",
+            RRType::DLV => "DLV",
+            // Generic type representation for types without a mnemonic (RFC 3597)
+            RRType::PrivateUse(val) | RRType::Reserved(val) | RRType::Unassigned(val) => {
+                return write!(f, "TYPE{val}");
+            }
+        };
+        f.write_str(mnemonic)
     }
 }
 
 impl From<&str> for RRType {
     fn from(value: &str) -> Self {
-        match value.to_lowercase().as_str() {
-            "a" => Self::A,
-            "aaaa" => Self::AAAA,
-            "all" => Self::All,
-            "cname" => Self::CNAME,
-            "mx" => Self::MX,
-            "txt" => Self::TXT,
-            other => panic!("Unsupported resource record type {other}"),
-        }
+        value
+            .parse()
+            .unwrap_or_else(|_| panic!("Unsupported resource record type {value}"))
+    }
+}
+
+/// Parses the mnemonic of the type, or the generic `TYPEnnn` form for any type (RFC 3597 section 5)
+impl FromStr for RRType {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.to_ascii_uppercase();
+        Ok(match upper.as_str() {
+            "A" => RRType::A,
+            "NS" => RRType::NS,
+            "MD" => RRType::MD,
+            "MF" => RRType::MF,
+            "CNAME" => RRType::CNAME,
+            "SOA" => RRType::SOA,
+            "MB" => RRType::MB,
+            "MG" => RRType::MG,
+            "MR" => RRType::MR,
+            "NULL" => RRType::NULL,
+            "WKS" => RRType::WKS,
+            "PTR" => RRType::PTR,
+            "HINFO" => RRType::HINFO,
+            "MINFO" => RRType::MINFO,
+            "MX" => RRType::MX,
+            "TXT" => RRType::TXT,
+            "RP" => RRType::RP,
+            "AFSDB" => RRType::AFSDB,
+            "X25" => RRType::X25,
+            "ISDN" => RRType::ISDN,
+            "RT" => RRType::RT,
+            "NSAP" => RRType::NSAP,
+            "NSAP-PTR" => RRType::NsapPtr,
+            "SIG" => RRType::SIG,
+            "KEY" => RRType::KEY,
+            "PX" => RRType::PX,
+            "GPOS" => RRType::GPOS,
+            "AAAA" => RRType::AAAA,
+            "LOC" => RRType::LOC,
+            "NXT" => RRType::NXT,
+            "EID" => RRType::EID,
+            "NIMLOC" => RRType::NIMLOC,
+            "SRV" => RRType::SRV,
+            "ATMA" => RRType::ATMA,
+            "NAPTR" => RRType::NAPTR,
+            "KX" => RRType::KX,
+            "CERT" => RRType::CERT,
+            "A6" => RRType::A6,
+            "DNAME" => RRType::DNAME,
+            "SINK" => RRType::SINK,
+            "OPT" => RRType::OPT,
+            "APL" => RRType::APL,
+            "DS" => RRType::DS,
+            "SSHFP" => RRType::SSHFP,
+            "IPSECKEY" => RRType::IPSECKEY,
+            "RRSIG" => RRType::RRSIG,
+            "NSEC" => RRType::NSEC,
+            "DNSKEY" => RRType::DNSKEY,
+            "DHCID" => RRType::DHCID,
+            "NSEC3" => RRType::NSEC3,
+            "NSEC3PARAM" => RRType::NSEC3PARAM,
+            "TLSA" => RRType::TLSA,
+            "SMIMEA" => RRType::SMIMEA,
+            "HIP" => RRType::HIP,
+            "NINFO" => RRType::NINFO,
+            "RKEY" => RRType::RKEY,
+            "TALINK" => RRType::TALINK,
+            "CDS" => RRType::CDS,
+            "CDNSKEY" => RRType::CDNSKEY,
+            "OPENPGPKEY" => RRType::OPENPGPKEY,
+            "CSYNC" => RRType::CSYNC,
+            "ZONEMD" => RRType::ZONEMD,
+            "SVCB" => RRType::SVCB,
+            "HTTPS" => RRType::HTTPS,
+            "SPF" => RRType::SPF,
+            "UINFO" => RRType::UINFO,
+            "UID" => RRType::UID,
+            "GID" => RRType::GID,
+            "UNSPEC" => RRType::UNSPEC,
+            "NID" => RRType::NID,
+            "L32" => RRType::L32,
+            "L64" => RRType::L64,
+            "LP" => RRType::LP,
+            "EUI48" => RRType::EUI48,
+            "EUI64" => RRType::EUI64,
+            "TKEY" => RRType::TKEY,
+            "TSIG" => RRType::TSIG,
+            "IXFR" => RRType::IXFR,
+            "AXFR" => RRType::AXFR,
+            "MAILB" => RRType::MAILB,
+            "MAILA" => RRType::MAILA,
+            "ANY" => RRType::All,
+            "URI" => RRType::URI,
+            "CAA" => RRType::CAA,
+            "AVC" => RRType::AVC,
+            "DOA" => RRType::DOA,
+            "AMTRELAY" => RRType::AMTRELAY,
+            "TA" => RRType::TA,
+            "DLV" => RRType::DLV,
+            // Also accepted by the client
+            "ALL" => RRType::All,
+            other => match other.strip_prefix("TYPE").map(|n| n.parse::<u16>()) {
+                Some(Ok(val)) => RRType::from(val),
+                _ => return Err(ParseError::RRError(format!("Unknown RRType '{s}'"))),
+            },
+        })
     }
 }
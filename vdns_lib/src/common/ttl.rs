@@ -1,10 +1,13 @@
-use std::{fmt::Display, time::Duration};
+use std::{fmt::Display, str::FromStr, time::Duration};
 
 use serde::{Deserialize, Serialize};
 
 use crate::messages::{parsing::Reader, serializing::Writer};
 
-use super::parse_error::ParseResult;
+use super::parse_error::{ParseError, ParseResult};
+
+/// The largest TTL, larger values are treated as 0 (RFC 2181 section 8)
+pub const MAX_TTL: u32 = i32::MAX as u32;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TTL {
@@ -14,23 +17,11 @@ pub enum TTL {
 
 impl TTL {
     pub fn parse(reader: &mut Reader) -> ParseResult<TTL> {
-        let seconds = reader.read_u32()?;
-        Ok(match seconds {
-            0 => TTL::NoCache,
-            val => TTL::Cache(Duration::from_secs(val as u64)),
-        })
+        Ok(TTL::from(reader.read_u32()?))
     }
 
     pub fn serialize(&self, writer: &mut Writer) {
-        let val = match self {
-            TTL::NoCache => 0,
-            TTL::Cache(duration) => duration.as_secs() as u32,
-        };
-        let [b1, b2, b3, b4] = val.to_be_bytes();
-        writer.write_u8(b1);
-        writer.write_u8(b2);
-        writer.write_u8(b3);
-        writer.write_u8(b4);
+        writer.write_u32(self.into());
     }
 
     pub fn seconds_until_expiration(&self) -> usize {
@@ -41,6 +32,24 @@ impl TTL {
     }
 }
 
+impl From<u32> for TTL {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => TTL::NoCache,
+            val => TTL::Cache(Duration::from_secs(val as u64)),
+        }
+    }
+}
+
+impl From<&TTL> for u32 {
+    fn from(value: &TTL) -> Self {
+        match value {
+            TTL::NoCache => 0,
+            TTL::Cache(duration) => duration.as_secs() as u32,
+        }
+    }
+}
+
 impl Display for TTL {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -53,3 +62,44 @@ impl Display for TTL {
         )
     }
 }
+
+/// Parses a number of seconds, optionally using units as in `1h30m`.
+/// The units are `s`, `m`, `h`, `d` and `w`, a number without a unit is seconds.
+impl FromStr for TTL {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseError::RRError(format!("Invalid TTL '{s}'"));
+        if s.is_empty() {
+            return Err(invalid());
+        }
+
+        let mut total: u64 = 0;
+        let mut number: Option<u64> = None;
+        for c in s.chars() {
+            if let Some(digit) = c.to_digit(10) {
+                number = Some(number.unwrap_or(0) * 10 + digit as u64);
+                if number > Some(MAX_TTL as u64) {
+                    return Err(invalid());
+                }
+                continue;
+            }
+
+            let unit = match c.to_ascii_lowercase() {
+                's' => 1,
+                'm' => 60,
+                'h' => 60 * 60,
+                'd' => 24 * 60 * 60,
+                'w' => 7 * 24 * 60 * 60,
+                _ => return Err(invalid()),
+            };
+            total += number.take().ok_or_else(invalid)? * unit;
+        }
+        total += number.unwrap_or(0);
+
+        if total > MAX_TTL as u64 {
+            return Err(invalid());
+        }
+        Ok(TTL::from(total as u32))
+    }
+}
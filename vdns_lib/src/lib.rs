@@ -1,35 +1,222 @@
-use std::net::{IpAddr, SocketAddr, UdpSocket};
+use std::{
+    collections::HashMap,
+    io::{self, Read, Write},
+    net::{IpAddr, SocketAddr, TcpStream, UdpSocket},
+    sync::{LazyLock, Mutex},
+    time::Duration,
+};
 
-use crate::{common::rr_type::RRType, messages::message::Message};
+use crate::{
+    common::{domain_name::DomainName, parse_error::ParseError, rr_type::RRType},
+    messages::{
+        edns::{cookie::Cookie, edns::Edns},
+        header::flags::RCode,
+        message::{Message, PartialMessage},
+        resource_record::resource_record::ResourceRecord,
+        tsig::{
+            keyring::TsigKey,
+            signing::{TsigError, TsigSession},
+        },
+    },
+};
 
 pub mod common;
 pub mod messages;
+pub mod zone_file;
 
 pub const DNS_PORT: u16 = 53;
 const SEND_FROM_PORT: u16 = 9315;
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
 
-pub fn lookup(name: &str, rr_type: RRType, nameserver: IpAddr, recurse: bool) -> Message {
-    let message = Message::new_query(name, rr_type, recurse);
+/// The cookies we use with each server, along with the server cookie it last gave us (RFC 7873 section 5.1)
+static COOKIE_JAR: LazyLock<Mutex<HashMap<IpAddr, Cookie>>> = LazyLock::new(Default::default);
 
-    let buffer = message.serialize();
+#[derive(Debug, thiserror::Error)]
+pub enum LookupError {
+    #[error("IO error")]
+    IOError(#[from] io::Error),
+    #[error("Failed to parse response")]
+    ParseError(#[from] ParseError),
+    /// Some records of the response could not be parsed, the rest of it may still be usable
+    #[error("Failed to parse {} entries of the response", .0.errors.len())]
+    PartialResponse(Box<PartialMessage>),
+    #[error("Response did not echo our client cookie")]
+    CookieMismatch,
+    #[error("Response did not include a cookie although the server has sent us one before")]
+    MissingCookie,
+    #[error("Transaction signature error")]
+    TsigError(#[from] TsigError),
+    #[error("Zone transfer failed with {0}")]
+    TransferFailed(RCode),
+}
+
+pub fn lookup(
+    name: &DomainName,
+    rr_type: RRType,
+    nameserver: IpAddr,
+    recurse: bool,
+    tsig_key: Option<&TsigKey>,
+) -> Result<Message, LookupError> {
+    let query = || {
+        let mut edns = Edns::default();
+        edns.set_cookie(Some(cookie_for(nameserver)));
+        Message::new_query(name, rr_type.clone(), recurse, Some(edns))
+    };
+    let response = send_query(query(), nameserver, tsig_key)?;
+
+    // A truncated or BADCOOKIE response with a server cookie means that the server wants a valid cookie before
+    // answering in full, which we now have
+    let has_server_cookie = response
+        .edns
+        .as_ref()
+        .and_then(|e| e.cookie())
+        .is_some_and(|c| c.server().is_some());
+    match has_server_cookie
+        && (response.header.flags.tc || response.header.flags.r_code == RCode::BadCookie)
+    {
+        true => send_query(query(), nameserver, tsig_key),
+        false => Ok(response),
+    }
+}
+
+/// The cookie to send to the nameserver, with the same client cookie every time
+/// and the server cookie from its last response if it sent one
+pub fn cookie_for(nameserver: IpAddr) -> Cookie {
+    COOKIE_JAR
+        .lock()
+        .unwrap()
+        .entry(nameserver)
+        .or_insert_with(Cookie::new_client)
+        .clone()
+}
+
+/// Sends the query to the nameserver and waits for the response.
+/// If the query has a cookie, a cookie in the response must echo the client cookie,
+/// and the server cookie is kept for later queries to the nameserver.
+/// If a key is given the query is signed with it and the response must be signed as well.
+/// A response with records that cannot be parsed is returned as a `PartialResponse` error.
+pub fn send_query(
+    message: Message,
+    nameserver: IpAddr,
+    tsig_key: Option<&TsigKey>,
+) -> Result<Message, LookupError> {
+    let sent_cookie = message.edns.as_ref().and_then(|e| e.cookie()).cloned();
+
+    let mut tsig_session = tsig_key.cloned().map(TsigSession::new);
+    let mut buffer = message.serialize();
+    if let Some(session) = tsig_session.as_mut() {
+        buffer = session.sign(buffer);
+    }
 
     // Send the message
-    let socket = UdpSocket::bind(SocketAddr::from(([0, 0, 0, 0], SEND_FROM_PORT)))
-        .expect("Failed to bind to port");
-    socket.set_read_timeout(None).unwrap();
+    let socket = UdpSocket::bind(SocketAddr::from(([0, 0, 0, 0], SEND_FROM_PORT)))?;
+    socket.set_read_timeout(Some(QUERY_TIMEOUT))?;
 
-    socket
-        .connect(SocketAddr::from((nameserver, DNS_PORT)))
-        .expect("Failed to connect to nameserver");
+    socket.connect(SocketAddr::from((nameserver, DNS_PORT)))?;
 
-    socket
-        .send(buffer.as_slice())
-        .expect("Failed to send DNS message");
+    socket.send(buffer.as_slice())?;
 
     // Listen for a response
-    let mut buf = [0u8; 512];
-    let size = socket.recv(&mut buf).expect("Failed to listen for a reply");
+    let mut buf = [0u8; u16::MAX as usize];
+    let size = socket.recv(&mut buf)?;
     let read = &buf[0..size];
+    if let Some(session) = tsig_session.as_mut() {
+        session.verify(read)?;
+    }
+
+    let partial = Message::parse_lenient(read)?;
+
+    let response_cookie = partial.message.edns.as_ref().and_then(|e| e.cookie());
+    check_response_cookie(nameserver, sent_cookie.as_ref(), response_cookie)?;
+
+    match partial.is_complete() {
+        true => Ok(partial.message),
+        false => Err(LookupError::PartialResponse(Box::new(partial))),
+    }
+}
+
+/// Checks the cookie of a response from the nameserver against the one we sent and keeps its server cookie.
+/// A response without a cookie is taken to be from a server that doesn't support cookies (RFC 7873 section 5.3),
+/// unless the server has given us a cookie before.
+pub fn check_response_cookie(
+    nameserver: IpAddr,
+    sent: Option<&Cookie>,
+    received: Option<&Cookie>,
+) -> Result<(), LookupError> {
+    let Some(sent) = sent else {
+        return Ok(());
+    };
+
+    let mut jar = COOKIE_JAR.lock().unwrap();
+    match received {
+        Some(received) if received.client() != sent.client() => Err(LookupError::CookieMismatch),
+        Some(received) => {
+            if received.server().is_some() {
+                jar.insert(nameserver, received.clone());
+            }
+            Ok(())
+        }
+        None if jar.get(&nameserver).is_some_and(|c| c.server().is_some()) => {
+            Err(LookupError::MissingCookie)
+        }
+        None => Ok(()),
+    }
+}
+
+/// Transfers all records of the zone (AXFR) over TCP, the first and last records are the SOA record of the zone.
+/// If a key is given the request is signed with it and every message of the transfer must be signed as well.
+pub fn transfer_zone(
+    zone: &DomainName,
+    nameserver: IpAddr,
+    tsig_key: Option<&TsigKey>,
+) -> Result<Vec<ResourceRecord>, LookupError> {
+    let mut tsig_session = tsig_key.cloned().map(TsigSession::new);
+    let mut buffer = Message::new_query(zone, RRType::AXFR, false, None).serialize();
+    if let Some(session) = tsig_session.as_mut() {
+        buffer = session.sign(buffer);
+    }
+
+    let mut stream = TcpStream::connect(SocketAddr::from((nameserver, DNS_PORT)))?;
+    stream.set_read_timeout(Some(QUERY_TIMEOUT))?;
+    write_tcp_message(&mut stream, &buffer)?;
+
+    let mut records = vec![];
+    let mut soa_count = 0;
+    // The transfer ends with the SOA record it started with
+    while soa_count < 2 {
+        let read = read_tcp_message(&mut stream)?;
+        if let Some(session) = tsig_session.as_mut() {
+            session.verify(&read)?;
+        }
+
+        let response = Message::parse(&read)?;
+        if response.header.flags.r_code != RCode::NoError {
+            return Err(LookupError::TransferFailed(response.header.flags.r_code));
+        }
+        if response.answer.is_empty() {
+            return Err(LookupError::TransferFailed(RCode::ServerFailure));
+        }
+        for record in response.answer.into_iter() {
+            if *record.record_type() == RRType::SOA {
+                soa_count += 1;
+            }
+            records.push(record);
+        }
+    }
+
+    Ok(records)
+}
+
+/// Messages sent over TCP are prefixed with their length (RFC 1035 section 4.2.2)
+pub fn write_tcp_message(stream: &mut TcpStream, message: &[u8]) -> io::Result<()> {
+    stream.write_all(&(message.len() as u16).to_be_bytes())?;
+    stream.write_all(message)
+}
 
-    Message::parse(&read).expect("Failed to parse response")
+pub fn read_tcp_message(stream: &mut TcpStream) -> io::Result<Vec<u8>> {
+    let mut length = [0u8; 2];
+    stream.read_exact(&mut length)?;
+    let mut message = vec![0u8; u16::from_be_bytes(length) as usize];
+    stream.read_exact(&mut message)?;
+    Ok(message)
 }
@@ -0,0 +1,199 @@
+use std::{
+    fmt::{Display, Formatter},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+use crate::{
+    common::parse_error::{ParseError, ParseResult},
+    messages::{parsing::Reader, serializing::Writer},
+};
+
+const FAMILY_IPV4: u16 = 1;
+const FAMILY_IPV6: u16 = 2;
+
+/// Source prefix lengths recommended for privacy when sending the option (RFC 7871 section 11.1)
+pub const DEFAULT_IPV4_SOURCE_PREFIX: u8 = 24;
+pub const DEFAULT_IPV6_SOURCE_PREFIX: u8 = 56;
+
+/// The EDNS Client Subnet option (RFC 7871)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientSubnet {
+    source_prefix: u8,
+    scope_prefix: u8,
+    address: IpAddr, // Any bits past the source prefix are always zero
+}
+
+impl ClientSubnet {
+    /// Returns None if the prefix is longer than the address
+    pub fn new(address: IpAddr, source_prefix: u8) -> Option<Self> {
+        if source_prefix > max_prefix(&address) {
+            return None;
+        }
+
+        Some(Self {
+            source_prefix,
+            scope_prefix: 0,
+            address: mask(address, source_prefix),
+        })
+    }
+
+    pub fn parse(reader: &mut Reader, length: u16) -> ParseResult<Self> {
+        let family = reader.read_u16()?;
+        let source_prefix = reader.read_u8()?;
+        let scope_prefix = reader.read_u8()?;
+
+        let address_length = (source_prefix as usize).div_ceil(8);
+        if length as usize != 4 + address_length {
+            return Err(ParseError::RRError(format!(
+                "Client subnet address must be {address_length} bytes for a /{source_prefix} prefix, got {}",
+                (length as usize).saturating_sub(4)
+            )));
+        }
+        let bytes = reader.read_exact_vec(address_length)?;
+
+        let address = match family {
+            FAMILY_IPV4 => {
+                let mut octets = [0u8; 4];
+                octets
+                    .get_mut(..address_length)
+                    .ok_or(ParseError::RRError(format!(
+                        "Invalid IPv4 client subnet prefix /{source_prefix}"
+                    )))?
+                    .copy_from_slice(&bytes);
+                IpAddr::V4(Ipv4Addr::from(octets))
+            }
+            FAMILY_IPV6 => {
+                let mut octets = [0u8; 16];
+                octets
+                    .get_mut(..address_length)
+                    .ok_or(ParseError::RRError(format!(
+                        "Invalid IPv6 client subnet prefix /{source_prefix}"
+                    )))?
+                    .copy_from_slice(&bytes);
+                IpAddr::V6(Ipv6Addr::from(octets))
+            }
+            other => {
+                return Err(ParseError::RRError(format!(
+                    "Unsupported client subnet address family {other}"
+                )))
+            }
+        };
+
+        let max = max_prefix(&address);
+        if source_prefix > max || scope_prefix > max {
+            return Err(ParseError::RRError(format!(
+                "Client subnet prefixes /{source_prefix} and /{scope_prefix} must be at most /{max}"
+            )));
+        }
+        if mask(address, source_prefix) != address {
+            return Err(ParseError::RRError(format!(
+                "Client subnet address {address} has bits set past the /{source_prefix} prefix"
+            )));
+        }
+
+        Ok(Self {
+            source_prefix,
+            scope_prefix,
+            address,
+        })
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        let (family, octets) = match self.address {
+            IpAddr::V4(address) => (FAMILY_IPV4, address.octets().to_vec()),
+            IpAddr::V6(address) => (FAMILY_IPV6, address.octets().to_vec()),
+        };
+
+        writer.write_u16(family);
+        writer.write_u8(self.source_prefix);
+        writer.write_u8(self.scope_prefix);
+        // Only the bytes covered by the prefix are sent
+        octets[..(self.source_prefix as usize).div_ceil(8)]
+            .iter()
+            .for_each(|b| writer.write_u8(*b));
+    }
+
+    pub fn address(&self) -> IpAddr {
+        self.address
+    }
+
+    pub fn source_prefix(&self) -> u8 {
+        self.source_prefix
+    }
+
+    pub fn scope_prefix(&self) -> u8 {
+        self.scope_prefix
+    }
+
+    /// The subnet with its source prefix shortened to at most the given length,
+    /// e.g. to not reveal more of a client's address than configured
+    pub fn with_max_source_prefix(&self, max_source_prefix: u8) -> Self {
+        let source_prefix = self.source_prefix.min(max_source_prefix);
+        Self {
+            source_prefix,
+            scope_prefix: self.scope_prefix.min(source_prefix),
+            address: mask(self.address, source_prefix),
+        }
+    }
+
+    /// Used in responses to tell for how large a subnet the answer is valid
+    pub fn with_scope_prefix(&self, scope_prefix: u8) -> Self {
+        Self {
+            scope_prefix: scope_prefix.min(max_prefix(&self.address)),
+            ..self.clone()
+        }
+    }
+}
+
+/// Whether the address is on a private, shared, loopback or link-local network (e.g. RFC 1918 and RFC 4193),
+/// which says nothing about where the client is and so should not be sent as its subnet
+pub fn is_private_address(address: &IpAddr) -> bool {
+    match address.to_canonical() {
+        IpAddr::V4(address) => {
+            let [first, second, _, _] = address.octets();
+            address.is_private()
+                || address.is_loopback()
+                || address.is_link_local()
+                || address.is_unspecified()
+                // Shared address space for carrier-grade NAT (RFC 6598)
+                || (first == 100 && second & 0b1100_0000 == 64)
+        }
+        IpAddr::V6(address) => {
+            address.is_loopback()
+                || address.is_unspecified()
+                || address.is_unique_local()
+                || address.is_unicast_link_local()
+        }
+    }
+}
+
+fn max_prefix(address: &IpAddr) -> u8 {
+    match address {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    }
+}
+
+/// Zeroes all bits of the address past the prefix
+fn mask(address: IpAddr, prefix: u8) -> IpAddr {
+    match address {
+        IpAddr::V4(address) => {
+            let bits = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+            IpAddr::V4(Ipv4Addr::from(u32::from(address) & bits))
+        }
+        IpAddr::V6(address) => {
+            let bits = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+            IpAddr::V6(Ipv6Addr::from(u128::from(address) & bits))
+        }
+    }
+}
+
+impl Display for ClientSubnet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Client Subnet: {}/{} (scope /{})",
+            self.address, self.source_prefix, self.scope_prefix
+        )
+    }
+}
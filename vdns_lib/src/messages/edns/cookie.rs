@@ -0,0 +1,87 @@
+use std::fmt::{Display, Formatter};
+
+use data_encoding::HEXUPPER;
+use rand::Rng;
+
+use crate::{
+    common::parse_error::{ParseError, ParseResult},
+    messages::{parsing::Reader, serializing::Writer},
+};
+
+pub const CLIENT_COOKIE_LENGTH: usize = 8;
+pub const MIN_SERVER_COOKIE_LENGTH: usize = 8;
+pub const MAX_SERVER_COOKIE_LENGTH: usize = 32;
+
+/// The DNS Cookie option (RFC 7873)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cookie {
+    client: [u8; CLIENT_COOKIE_LENGTH],
+    server: Option<Vec<u8>>,
+}
+
+impl Cookie {
+    /// A new random client cookie without a server cookie
+    pub fn new_client() -> Self {
+        Self {
+            client: rand::thread_rng().gen(),
+            server: None,
+        }
+    }
+
+    /// Returns None if the server cookie has an invalid length
+    pub fn new(client: [u8; CLIENT_COOKIE_LENGTH], server: Option<Vec<u8>>) -> Option<Self> {
+        if let Some(server) = server.as_ref() {
+            if !(MIN_SERVER_COOKIE_LENGTH..=MAX_SERVER_COOKIE_LENGTH).contains(&server.len()) {
+                return None;
+            }
+        }
+
+        Some(Self { client, server })
+    }
+
+    pub fn parse(reader: &mut Reader, length: u16) -> ParseResult<Self> {
+        let length = length as usize;
+        let server_length = length.saturating_sub(CLIENT_COOKIE_LENGTH);
+        if length < CLIENT_COOKIE_LENGTH
+            || (server_length != 0
+                && !(MIN_SERVER_COOKIE_LENGTH..=MAX_SERVER_COOKIE_LENGTH).contains(&server_length))
+        {
+            return Err(ParseError::RRError(format!(
+                "Invalid cookie length {length}"
+            )));
+        }
+
+        let client = reader.read_array()?;
+        let server = match server_length {
+            0 => None,
+            len => Some(reader.read_exact_vec(len)?),
+        };
+
+        Ok(Self { client, server })
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        self.client.iter().for_each(|b| writer.write_u8(*b));
+        if let Some(server) = self.server.as_ref() {
+            server.iter().for_each(|b| writer.write_u8(*b));
+        }
+    }
+
+    pub fn client(&self) -> &[u8; CLIENT_COOKIE_LENGTH] {
+        &self.client
+    }
+
+    pub fn server(&self) -> Option<&[u8]> {
+        self.server.as_deref()
+    }
+}
+
+impl Display for Cookie {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cookie: client {}", HEXUPPER.encode(&self.client))?;
+        match self.server.as_ref() {
+            Some(server) => write!(f, ", server {}", HEXUPPER.encode(server)),
+            None => write!(f, ", no server cookie"),
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use std::fmt::{Display, Formatter};
+
+use crate::{
+    common::{
+        class::Class,
+        formatting::indent_string,
+        parse_error::{ParseError, ParseResult},
+        rr_type::RRType,
+    },
+    messages::{
+        parsing::Reader,
+        resource_record::{resource_record::ResourceRecord, rr_data::RRData},
+        serializing::Writer,
+    },
+};
+
+use super::{
+    client_subnet::ClientSubnet, cookie::Cookie, edns_option::EdnsOption,
+    extended_error::ExtendedError,
+};
+
+/// The payload size we advertise, small enough to avoid IP fragmentation in practice
+pub const DEFAULT_UDP_PAYLOAD_SIZE: u16 = 1232;
+/// Requestors advertising less than this should be treated as if they advertised this (RFC 6891 section 6.2.5)
+pub const MIN_UDP_PAYLOAD_SIZE: u16 = 512;
+
+const DNSSEC_OK_FLAG: u16 = 0b1000_0000_0000_0000;
+
+/// The contents of the OPT pseudo-record of EDNS(0) (RFC 6891)
+#[derive(Debug, Clone)]
+pub struct Edns {
+    pub udp_payload_size: u16,
+    pub extended_rcode: u8, // The upper 8 bits of the 12 bit RCODE
+    pub version: u8,
+    pub dnssec_ok: bool,
+    pub z: u16, // Remaining 15 flag bits, reserved and must be 0
+    pub options: Vec<EdnsOption>,
+}
+
+impl Default for Edns {
+    fn default() -> Self {
+        Self {
+            udp_payload_size: DEFAULT_UDP_PAYLOAD_SIZE,
+            extended_rcode: 0,
+            version: 0,
+            dnssec_ok: false,
+            z: 0,
+            options: vec![],
+        }
+    }
+}
+
+impl Edns {
+    /// Extracts the EDNS information from a parsed OPT record
+    pub fn from_record(record: &ResourceRecord) -> ParseResult<Edns> {
+        if *record.record_type() != RRType::OPT {
+            return Err(ParseError::RRError(format!(
+                "Expected an OPT record but got {}",
+                record.record_type()
+            )));
+        }
+        if !record.name().is_root() {
+            return Err(ParseError::RRError(format!(
+                "OPT record must be owned by the root domain, got {}",
+                record.name()
+            )));
+        }
+
+        let data = match record.rdata() {
+            RRData::Unknown { rr_type: _, data } => data,
+            other => {
+                return Err(ParseError::RRError(format!(
+                    "Unexpected data {other} for OPT record"
+                )))
+            }
+        };
+
+        let mut reader = Reader::new(data);
+        let mut options = vec![];
+        while reader.get_index() < data.len() {
+            options.push(EdnsOption::parse(&mut reader)?);
+        }
+
+        let [extended_rcode, version, flags_high, flags_low] =
+            u32::from(record.ttl()).to_be_bytes();
+        let flags = u16::from_be_bytes([flags_high, flags_low]);
+
+        Ok(Edns {
+            udp_payload_size: record.class().into(),
+            extended_rcode,
+            version,
+            dnssec_ok: flags & DNSSEC_OK_FLAG != 0,
+            z: flags & !DNSSEC_OK_FLAG,
+            options,
+        })
+    }
+
+    /// Writes the OPT record
+    pub fn serialize(&self, writer: &mut Writer) {
+        // The owner is always the root domain
+        writer.write_u8(0);
+        RRType::OPT.serialize(writer);
+        Class::from(self.udp_payload_size).serialize(writer);
+
+        let flags = if self.dnssec_ok { DNSSEC_OK_FLAG } else { 0 } | self.z;
+        let [flags_high, flags_low] = flags.to_be_bytes();
+        writer.write_u32(u32::from_be_bytes([
+            self.extended_rcode,
+            self.version,
+            flags_high,
+            flags_low,
+        ]));
+
+        let length_index = writer.len();
+        writer.write_u16(0);
+        for option in self.options.iter() {
+            option.serialize(writer);
+        }
+        let length = writer.len() - length_index - 2;
+        writer.set_u16(length_index, length as u16);
+    }
+
+    pub fn client_subnet(&self) -> Option<&ClientSubnet> {
+        self.options.iter().find_map(|o| match o {
+            EdnsOption::ClientSubnet(subnet) => Some(subnet),
+            _ => None,
+        })
+    }
+
+    /// Replaces any existing client subnet option
+    pub fn set_client_subnet(&mut self, subnet: Option<ClientSubnet>) {
+        self.options
+            .retain(|o| !matches!(o, EdnsOption::ClientSubnet(_)));
+        if let Some(subnet) = subnet {
+            self.options.push(EdnsOption::ClientSubnet(subnet));
+        }
+    }
+
+    pub fn cookie(&self) -> Option<&Cookie> {
+        self.options.iter().find_map(|o| match o {
+            EdnsOption::Cookie(cookie) => Some(cookie),
+            _ => None,
+        })
+    }
+
+    /// Replaces any existing cookie option
+    pub fn set_cookie(&mut self, cookie: Option<Cookie>) {
+        self.options.retain(|o| !matches!(o, EdnsOption::Cookie(_)));
+        if let Some(cookie) = cookie {
+            self.options.push(EdnsOption::Cookie(cookie));
+        }
+    }
+
+    /// A response may carry several extended errors
+    pub fn extended_errors(&self) -> Vec<&ExtendedError> {
+        self.options
+            .iter()
+            .filter_map(|o| match o {
+                EdnsOption::ExtendedError(error) => Some(error),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn add_extended_error(&mut self, error: ExtendedError) {
+        self.options.push(EdnsOption::ExtendedError(error));
+    }
+
+    /// The largest UDP response the sender can handle
+    pub fn max_udp_payload_size(&self) -> usize {
+        self.udp_payload_size.max(MIN_UDP_PAYLOAD_SIZE) as usize
+    }
+}
+
+impl Display for Edns {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{{
+    UDP payload size: {},
+    Extended RCODE: {},
+    Version: {},
+    DNSSEC OK: {},
+    Options: {}
+}}",
+            self.udp_payload_size,
+            self.extended_rcode,
+            self.version,
+            self.dnssec_ok,
+            if self.options.is_empty() {
+                "[]".to_string()
+            } else {
+                format!(
+                    "[
+        {}
+    ]",
+                    indent_string(indent_string(
+                        self.options
+                            .iter()
+                            .map(|o| o.to_string())
+                            .collect::<Vec<String>>()
+                            .join(",\n")
+                    ))
+                )
+            }
+        )
+    }
+}
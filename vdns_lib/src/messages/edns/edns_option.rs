@@ -0,0 +1,83 @@
+use std::fmt::{Display, Formatter};
+
+use data_encoding::HEXUPPER;
+
+use crate::{
+    common::parse_error::ParseResult,
+    messages::{parsing::Reader, serializing::Writer},
+};
+
+use super::{client_subnet::ClientSubnet, cookie::Cookie, extended_error::ExtendedError};
+
+const CLIENT_SUBNET_CODE: u16 = 8;
+const COOKIE_CODE: u16 = 10;
+const EXTENDED_ERROR_CODE: u16 = 15;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EdnsOption {
+    ClientSubnet(ClientSubnet),
+    Cookie(Cookie),
+    ExtendedError(ExtendedError),
+    /// An option we do not model, kept as is
+    Unknown {
+        code: u16,
+        data: Vec<u8>,
+    },
+}
+
+impl EdnsOption {
+    pub fn parse(reader: &mut Reader) -> ParseResult<EdnsOption> {
+        let code = reader.read_u16()?;
+        let length = reader.read_u16()?;
+        let data = reader.read_exact_vec(length as usize)?;
+
+        let mut data_reader = Reader::new(&data);
+        Ok(match code {
+            CLIENT_SUBNET_CODE => {
+                EdnsOption::ClientSubnet(ClientSubnet::parse(&mut data_reader, length)?)
+            }
+            COOKIE_CODE => EdnsOption::Cookie(Cookie::parse(&mut data_reader, length)?),
+            EXTENDED_ERROR_CODE => {
+                EdnsOption::ExtendedError(ExtendedError::parse(&mut data_reader, length)?)
+            }
+            code => EdnsOption::Unknown { code, data },
+        })
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        writer.write_u16(self.code());
+
+        let length_index = writer.len();
+        writer.write_u16(0);
+        match self {
+            EdnsOption::ClientSubnet(subnet) => subnet.serialize(writer),
+            EdnsOption::Cookie(cookie) => cookie.serialize(writer),
+            EdnsOption::ExtendedError(error) => error.serialize(writer),
+            EdnsOption::Unknown { code: _, data } => data.iter().for_each(|b| writer.write_u8(*b)),
+        }
+        let length = writer.len() - length_index - 2;
+        writer.set_u16(length_index, length as u16);
+    }
+
+    pub fn code(&self) -> u16 {
+        match self {
+            EdnsOption::ClientSubnet(_) => CLIENT_SUBNET_CODE,
+            EdnsOption::Cookie(_) => COOKIE_CODE,
+            EdnsOption::ExtendedError(_) => EXTENDED_ERROR_CODE,
+            EdnsOption::Unknown { code, data: _ } => *code,
+        }
+    }
+}
+
+impl Display for EdnsOption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EdnsOption::ClientSubnet(subnet) => write!(f, "{subnet}"),
+            EdnsOption::Cookie(cookie) => write!(f, "{cookie}"),
+            EdnsOption::ExtendedError(error) => write!(f, "{error}"),
+            EdnsOption::Unknown { code, data } => {
+                write!(f, "Option {code}: {}", HEXUPPER.encode(data))
+            }
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use std::fmt::{Display, Formatter};
+
+use crate::{
+    common::parse_error::{ParseError, ParseResult},
+    messages::{parsing::Reader, serializing::Writer},
+};
+
+/// The Extended DNS Error option (RFC 8914)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendedError {
+    code: ExtendedErrorCode,
+    extra_text: Option<String>, // Meant for humans, not to be acted upon
+}
+
+impl ExtendedError {
+    pub fn new(code: ExtendedErrorCode, extra_text: Option<String>) -> Self {
+        Self {
+            code,
+            extra_text: extra_text.filter(|t| !t.is_empty()),
+        }
+    }
+
+    pub fn parse(reader: &mut Reader, length: u16) -> ParseResult<Self> {
+        if length < 2 {
+            return Err(ParseError::RRError(format!(
+                "Invalid extended DNS error length {length}"
+            )));
+        }
+
+        let code = ExtendedErrorCode::from(reader.read_u16()?);
+        let text = reader.read_exact_vec(length as usize - 2)?;
+        // The text should be UTF-8 but may be NUL terminated by some implementations
+        let extra_text = String::from_utf8_lossy(&text)
+            .trim_end_matches('\0')
+            .to_string();
+
+        Ok(Self::new(code, Some(extra_text)))
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        writer.write_u16(u16::from(&self.code));
+        if let Some(text) = self.extra_text.as_ref() {
+            text.bytes().for_each(|b| writer.write_u8(b));
+        }
+    }
+
+    pub fn code(&self) -> &ExtendedErrorCode {
+        &self.code
+    }
+
+    pub fn extra_text(&self) -> Option<&str> {
+        self.extra_text.as_deref()
+    }
+}
+
+impl Display for ExtendedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Extended DNS Error {} ({})",
+            u16::from(&self.code),
+            self.code
+        )?;
+        match self.extra_text.as_ref() {
+            Some(text) => write!(f, ": {text}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The registered INFO-CODEs of extended DNS errors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtendedErrorCode {
+    OtherError,
+    UnsupportedDnskeyAlgorithm,
+    UnsupportedDsDigestType,
+    StaleAnswer,
+    ForgedAnswer,
+    DnssecIndeterminate,
+    DnssecBogus,
+    SignatureExpired,
+    SignatureNotYetValid,
+    DnskeyMissing,
+    RrsigsMissing,
+    NoZoneKeyBitSet,
+    NsecMissing,
+    CachedError,
+    NotReady,
+    Blocked,
+    Censored,
+    Filtered,
+    Prohibited,
+    StaleNxdomainAnswer,
+    NotAuthoritative,
+    NotSupported,
+    NoReachableAuthority,
+    NetworkError,
+    InvalidData,
+    SignatureExpiredBeforeValid,
+    TooEarly,
+    UnsupportedNsec3IterationsValue,
+    UnableToConformToPolicy,
+    Synthesized,
+    InvalidQueryType,
+    Unassigned(u16),
+}
+
+impl From<u16> for ExtendedErrorCode {
+    fn from(value: u16) -> Self {
+        match value {
+            0 => ExtendedErrorCode::OtherError,
+            1 => ExtendedErrorCode::UnsupportedDnskeyAlgorithm,
+            2 => ExtendedErrorCode::UnsupportedDsDigestType,
+            3 => ExtendedErrorCode::StaleAnswer,
+            4 => ExtendedErrorCode::ForgedAnswer,
+            5 => ExtendedErrorCode::DnssecIndeterminate,
+            6 => ExtendedErrorCode::DnssecBogus,
+            7 => ExtendedErrorCode::SignatureExpired,
+            8 => ExtendedErrorCode::SignatureNotYetValid,
+            9 => ExtendedErrorCode::DnskeyMissing,
+            10 => ExtendedErrorCode::RrsigsMissing,
+            11 => ExtendedErrorCode::NoZoneKeyBitSet,
+            12 => ExtendedErrorCode::NsecMissing,
+            13 => ExtendedErrorCode::CachedError,
+            14 => ExtendedErrorCode::NotReady,
+            15 => ExtendedErrorCode::Blocked,
+            16 => ExtendedErrorCode::Censored,
+            17 => ExtendedErrorCode::Filtered,
+            18 => ExtendedErrorCode::Prohibited,
+            19 => ExtendedErrorCode::StaleNxdomainAnswer,
+            20 => ExtendedErrorCode::NotAuthoritative,
+            21 => ExtendedErrorCode::NotSupported,
+            22 => ExtendedErrorCode::NoReachableAuthority,
+            23 => ExtendedErrorCode::NetworkError,
+            24 => ExtendedErrorCode::InvalidData,
+            25 => ExtendedErrorCode::SignatureExpiredBeforeValid,
+            26 => ExtendedErrorCode::TooEarly,
+            27 => ExtendedErrorCode::UnsupportedNsec3IterationsValue,
+            28 => ExtendedErrorCode::UnableToConformToPolicy,
+            29 => ExtendedErrorCode::Synthesized,
+            30 => ExtendedErrorCode::InvalidQueryType,
+            other => ExtendedErrorCode::Unassigned(other),
+        }
+    }
+}
+
+impl From<&ExtendedErrorCode> for u16 {
+    fn from(value: &ExtendedErrorCode) -> Self {
+        match value {
+            ExtendedErrorCode::OtherError => 0,
+            ExtendedErrorCode::UnsupportedDnskeyAlgorithm => 1,
+            ExtendedErrorCode::UnsupportedDsDigestType => 2,
+            ExtendedErrorCode::StaleAnswer => 3,
+            ExtendedErrorCode::ForgedAnswer => 4,
+            ExtendedErrorCode::DnssecIndeterminate => 5,
+            ExtendedErrorCode::DnssecBogus => 6,
+            ExtendedErrorCode::SignatureExpired => 7,
+            ExtendedErrorCode::SignatureNotYetValid => 8,
+            ExtendedErrorCode::DnskeyMissing => 9,
+            ExtendedErrorCode::RrsigsMissing => 10,
+            ExtendedErrorCode::NoZoneKeyBitSet => 11,
+            ExtendedErrorCode::NsecMissing => 12,
+            ExtendedErrorCode::CachedError => 13,
+            ExtendedErrorCode::NotReady => 14,
+            ExtendedErrorCode::Blocked => 15,
+            ExtendedErrorCode::Censored => 16,
+            ExtendedErrorCode::Filtered => 17,
+            ExtendedErrorCode::Prohibited => 18,
+            ExtendedErrorCode::StaleNxdomainAnswer => 19,
+            ExtendedErrorCode::NotAuthoritative => 20,
+            ExtendedErrorCode::NotSupported => 21,
+            ExtendedErrorCode::NoReachableAuthority => 22,
+            ExtendedErrorCode::NetworkError => 23,
+            ExtendedErrorCode::InvalidData => 24,
+            ExtendedErrorCode::SignatureExpiredBeforeValid => 25,
+            ExtendedErrorCode::TooEarly => 26,
+            ExtendedErrorCode::UnsupportedNsec3IterationsValue => 27,
+            ExtendedErrorCode::UnableToConformToPolicy => 28,
+            ExtendedErrorCode::Synthesized => 29,
+            ExtendedErrorCode::InvalidQueryType => 30,
+            ExtendedErrorCode::Unassigned(other) => *other,
+        }
+    }
+}
+
+impl From<ExtendedErrorCode> for u16 {
+    fn from(value: ExtendedErrorCode) -> Self {
+        u16::from(&value)
+    }
+}
+
+impl Display for ExtendedErrorCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExtendedErrorCode::OtherError => write!(f, "Other Error"),
+            ExtendedErrorCode::UnsupportedDnskeyAlgorithm => {
+                write!(f, "Unsupported DNSKEY Algorithm")
+            }
+            ExtendedErrorCode::UnsupportedDsDigestType => write!(f, "Unsupported DS Digest Type"),
+            ExtendedErrorCode::StaleAnswer => write!(f, "Stale Answer"),
+            ExtendedErrorCode::ForgedAnswer => write!(f, "Forged Answer"),
+            ExtendedErrorCode::DnssecIndeterminate => write!(f, "DNSSEC Indeterminate"),
+            ExtendedErrorCode::DnssecBogus => write!(f, "DNSSEC Bogus"),
+            ExtendedErrorCode::SignatureExpired => write!(f, "Signature Expired"),
+            ExtendedErrorCode::SignatureNotYetValid => write!(f, "Signature Not Yet Valid"),
+            ExtendedErrorCode::DnskeyMissing => write!(f, "DNSKEY Missing"),
+            ExtendedErrorCode::RrsigsMissing => write!(f, "RRSIGs Missing"),
+            ExtendedErrorCode::NoZoneKeyBitSet => write!(f, "No Zone Key Bit Set"),
+            ExtendedErrorCode::NsecMissing => write!(f, "NSEC Missing"),
+            ExtendedErrorCode::CachedError => write!(f, "Cached Error"),
+            ExtendedErrorCode::NotReady => write!(f, "Not Ready"),
+            ExtendedErrorCode::Blocked => write!(f, "Blocked"),
+            ExtendedErrorCode::Censored => write!(f, "Censored"),
+            ExtendedErrorCode::Filtered => write!(f, "Filtered"),
+            ExtendedErrorCode::Prohibited => write!(f, "Prohibited"),
+            ExtendedErrorCode::StaleNxdomainAnswer => write!(f, "Stale NXDOMAIN Answer"),
+            ExtendedErrorCode::NotAuthoritative => write!(f, "Not Authoritative"),
+            ExtendedErrorCode::NotSupported => write!(f, "Not Supported"),
+            ExtendedErrorCode::NoReachableAuthority => write!(f, "No Reachable Authority"),
+            ExtendedErrorCode::NetworkError => write!(f, "Network Error"),
+            ExtendedErrorCode::InvalidData => write!(f, "Invalid Data"),
+            ExtendedErrorCode::SignatureExpiredBeforeValid => {
+                write!(f, "Signature Expired before Valid")
+            }
+            ExtendedErrorCode::TooEarly => write!(f, "Too Early"),
+            ExtendedErrorCode::UnsupportedNsec3IterationsValue => {
+                write!(f, "Unsupported NSEC3 Iterations Value")
+            }
+            ExtendedErrorCode::UnableToConformToPolicy => write!(f, "Unable to conform to policy"),
+            ExtendedErrorCode::Synthesized => write!(f, "Synthesized"),
+            ExtendedErrorCode::InvalidQueryType => write!(f, "Invalid Query Type"),
+            ExtendedErrorCode::Unassigned(_) => write!(f, "Unassigned"),
+        }
+    }
+}
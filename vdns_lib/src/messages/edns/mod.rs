@@ -0,0 +1,6 @@
+pub mod client_subnet;
+pub mod cookie;
+#[allow(clippy::module_inception)]
+pub mod edns;
+pub mod edns_option;
+pub mod extended_error;
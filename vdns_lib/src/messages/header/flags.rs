@@ -15,7 +15,7 @@ pub struct Flags {
     pub z: u8,           // Reserved, must be 0, 1 bit.
     pub ad: bool,        // 1 bit
     pub cd: bool,        // 1 bit
-    pub r_code: RCode,   // 4 bits
+    pub r_code: RCode, // 4 bits, the upper 8 bits of extended RCODEs are carried in the OPT record
 }
 
 impl Flags {
@@ -23,7 +23,7 @@ impl Flags {
         let val = reader.read_u16()?;
         Ok(Flags {
             qr: QR::parse(val),
-            op_code: OpCode::parse(val),
+            op_code: OpCode::from(((val >> 11) & 0b1111) as u8),
             aa: (val >> 10) & 1 == 1,
             tc: (val >> 9) & 1 == 1,
             rd: (val >> 8) & 1 == 1,
@@ -31,7 +31,7 @@ impl Flags {
             z: ((val >> 6) & 1) as u8,
             ad: (val >> 5) & 1 == 1,
             cd: (val >> 4) & 1 == 1,
-            r_code: RCode::parse(val),
+            r_code: RCode::from(val & HEADER_RCODE_MASK),
         })
     }
 
@@ -40,37 +40,23 @@ impl Flags {
             QR::Query => 0u8,
             QR::Response => 1u8,
         } << 7u8;
-        first_byte = first_byte
-            | match self.op_code {
-                OpCode::Query => 0,
-                OpCode::IQuery => 1,
-                OpCode::Status => 2,
-                OpCode::Reserved => 3, // 3-15 is reserved, picked one.
-            } << 3u8;
-        first_byte = first_byte
-            | match (self.aa, self.tc, self.rd) {
-                (false, false, false) => 0b000,
-                (false, false, true) => 0b001,
-                (false, true, false) => 0b010,
-                (false, true, true) => 0b011,
-                (true, false, false) => 0b100,
-                (true, false, true) => 0b101,
-                (true, true, false) => 0b110,
-                (true, true, true) => 0b111,
-            };
+        first_byte |= (u8::from(&self.op_code) & 0b1111) << 3u8;
+        first_byte |= match (self.aa, self.tc, self.rd) {
+            (false, false, false) => 0b000,
+            (false, false, true) => 0b001,
+            (false, true, false) => 0b010,
+            (false, true, true) => 0b011,
+            (true, false, false) => 0b100,
+            (true, false, true) => 0b101,
+            (true, true, false) => 0b110,
+            (true, true, true) => 0b111,
+        };
 
         let mut second_byte = if self.ra { 1 } else { 0 } << 7u8;
-        // Three zero bits
-        second_byte = second_byte
-            | match self.r_code {
-                RCode::NoError => 0,
-                RCode::FormatError => 1,
-                RCode::ServerFailure => 2,
-                RCode::NameError => 3,
-                RCode::NotImplemented => 4,
-                RCode::Refused => 5,
-                RCode::Reserved => 6, // 6-15 is reserved, picked one.
-            };
+        second_byte |= (self.z & 1) << 6u8;
+        second_byte |= if self.ad { 1 } else { 0 } << 5u8;
+        second_byte |= if self.cd { 1 } else { 0 } << 4u8;
+        second_byte |= (u16::from(&self.r_code) & HEADER_RCODE_MASK) as u8;
 
         writer.write_u16(((first_byte as u16) << 8) | second_byte as u16);
     }
@@ -100,7 +86,7 @@ impl Flags {
             ra: true, // We support recursion!
             z: 0,
             ad: false,
-            cd: false,
+            cd: query_flags.cd, // Copied from the query (RFC 4035 section 3.1.6)
             r_code: RCode::NoError, // Maybe want to set this differently later on...
         }
     }
@@ -113,7 +99,7 @@ impl Flags {
     }
 
     pub fn recurse(&self) -> bool {
-        return self.rd;
+        self.rd
     }
 }
 
@@ -130,7 +116,7 @@ impl Display for Flags {
     Recursion Available: {},
     Z: reserved ({}),
     Answer authenticated: {},
-    Non-authenticated data: {},
+    Checking Disabled: {},
     Reply Code: {}
 }}",
             self.qr,
@@ -173,37 +159,69 @@ impl Display for QR {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum OpCode {
     Query,
-    IQuery,
+    IQuery, // Obsolete (RFC 3425)
     Status,
-    Reserved, // Not used, reserved for future use.
+    Notify,
+    Update,
+    DSO,
+    Unassigned(u8),
 }
 
-impl OpCode {
-    fn parse(val: u16) -> OpCode {
-        let num = (val >> 11) & 0b1111;
-        match num {
+impl From<u8> for OpCode {
+    fn from(value: u8) -> Self {
+        match value {
             0 => OpCode::Query,
             1 => OpCode::IQuery,
             2 => OpCode::Status,
-            _ => OpCode::Reserved,
+            4 => OpCode::Notify,
+            5 => OpCode::Update,
+            6 => OpCode::DSO,
+            other => OpCode::Unassigned(other),
         }
     }
 }
 
+impl From<&OpCode> for u8 {
+    fn from(value: &OpCode) -> Self {
+        match value {
+            OpCode::Query => 0,
+            OpCode::IQuery => 1,
+            OpCode::Status => 2,
+            OpCode::Notify => 4,
+            OpCode::Update => 5,
+            OpCode::DSO => 6,
+            OpCode::Unassigned(other) => *other,
+        }
+    }
+}
+
+impl From<OpCode> for u8 {
+    fn from(value: OpCode) -> Self {
+        u8::from(&value)
+    }
+}
+
 impl Display for OpCode {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             OpCode::Query => write!(f, "Standard Query"),
             OpCode::IQuery => write!(f, "Inverse Query"),
             OpCode::Status => write!(f, "Status"),
-            OpCode::Reserved => write!(f, "Reserved"),
+            OpCode::Notify => write!(f, "Notify"),
+            OpCode::Update => write!(f, "Update"),
+            OpCode::DSO => write!(f, "DNS Stateful Operations"),
+            OpCode::Unassigned(val) => write!(f, "Unassigned ({val})"),
         }
     }
 }
 
+/// The largest RCODE that fits in the header alone
+pub const MAX_HEADER_RCODE: u16 = HEADER_RCODE_MASK;
+const HEADER_RCODE_MASK: u16 = 0b1111;
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum RCode {
     NoError,
@@ -212,24 +230,96 @@ pub enum RCode {
     NameError,
     NotImplemented,
     Refused,
-    Reserved, // Not used, reserved for future use.
+    YXDomain,
+    YXRRSet,
+    NXRRSet,
+    NotAuth,
+    NotZone,
+    DSOTypeNI,
+    BadVers, // Also BADSIG when used in a TSIG record (RFC 8945)
+    BadKey,
+    BadTime,
+    BadMode,
+    BadName,
+    BadAlg,
+    BadTrunc,
+    BadCookie,
+    PrivateUse(u16),
+    Reserved(u16),
+    Unassigned(u16),
 }
 
 impl RCode {
-    fn parse(val: u16) -> RCode {
-        let num = val & 0b1111;
-        match num {
+    /// Whether the RCODE needs the OPT record to be expressed
+    pub fn is_extended(&self) -> bool {
+        u16::from(self) > MAX_HEADER_RCODE
+    }
+}
+
+impl From<u16> for RCode {
+    fn from(value: u16) -> Self {
+        match value {
             0 => RCode::NoError,
             1 => RCode::FormatError,
             2 => RCode::ServerFailure,
             3 => RCode::NameError,
             4 => RCode::NotImplemented,
             5 => RCode::Refused,
-            _ => RCode::NoError,
+            6 => RCode::YXDomain,
+            7 => RCode::YXRRSet,
+            8 => RCode::NXRRSet,
+            9 => RCode::NotAuth,
+            10 => RCode::NotZone,
+            11 => RCode::DSOTypeNI,
+            16 => RCode::BadVers,
+            17 => RCode::BadKey,
+            18 => RCode::BadTime,
+            19 => RCode::BadMode,
+            20 => RCode::BadName,
+            21 => RCode::BadAlg,
+            22 => RCode::BadTrunc,
+            23 => RCode::BadCookie,
+            3841..=4095 => RCode::PrivateUse(value),
+            65535 => RCode::Reserved(value),
+            other => RCode::Unassigned(other),
+        }
+    }
+}
+
+impl From<&RCode> for u16 {
+    fn from(value: &RCode) -> Self {
+        match value {
+            RCode::NoError => 0,
+            RCode::FormatError => 1,
+            RCode::ServerFailure => 2,
+            RCode::NameError => 3,
+            RCode::NotImplemented => 4,
+            RCode::Refused => 5,
+            RCode::YXDomain => 6,
+            RCode::YXRRSet => 7,
+            RCode::NXRRSet => 8,
+            RCode::NotAuth => 9,
+            RCode::NotZone => 10,
+            RCode::DSOTypeNI => 11,
+            RCode::BadVers => 16,
+            RCode::BadKey => 17,
+            RCode::BadTime => 18,
+            RCode::BadMode => 19,
+            RCode::BadName => 20,
+            RCode::BadAlg => 21,
+            RCode::BadTrunc => 22,
+            RCode::BadCookie => 23,
+            RCode::PrivateUse(val) | RCode::Reserved(val) | RCode::Unassigned(val) => *val,
         }
     }
 }
 
+impl From<RCode> for u16 {
+    fn from(value: RCode) -> Self {
+        u16::from(&value)
+    }
+}
+
 impl Display for RCode {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -239,7 +329,23 @@ impl Display for RCode {
             RCode::NameError => write!(f, "Name Error"),
             RCode::NotImplemented => write!(f, "Not Implemented"),
             RCode::Refused => write!(f, "Refused"),
-            RCode::Reserved => write!(f, "Reserved"),
+            RCode::YXDomain => write!(f, "Name Exists when it should not"),
+            RCode::YXRRSet => write!(f, "RR Set Exists when it should not"),
+            RCode::NXRRSet => write!(f, "RR Set that should exist does not"),
+            RCode::NotAuth => write!(f, "Not Authorized"),
+            RCode::NotZone => write!(f, "Name not contained in zone"),
+            RCode::DSOTypeNI => write!(f, "DSO-TYPE Not Implemented"),
+            RCode::BadVers => write!(f, "Bad OPT Version or TSIG Signature Failure"),
+            RCode::BadKey => write!(f, "Key not recognized"),
+            RCode::BadTime => write!(f, "Signature out of time window"),
+            RCode::BadMode => write!(f, "Bad TKEY Mode"),
+            RCode::BadName => write!(f, "Duplicate key name"),
+            RCode::BadAlg => write!(f, "Algorithm not supported"),
+            RCode::BadTrunc => write!(f, "Bad Truncation"),
+            RCode::BadCookie => write!(f, "Bad/missing Server Cookie"),
+            RCode::PrivateUse(val) => write!(f, "Private Use ({val})"),
+            RCode::Reserved(val) => write!(f, "Reserved ({val})"),
+            RCode::Unassigned(val) => write!(f, "Unassigned ({val})"),
         }
     }
 }
@@ -1,96 +1,135 @@
 use crate::common::domain_name::DomainName;
-use crate::common::parse_error::{ParseError, ParseResult};
+use crate::common::parse_error::{ParseError, ParseResult, Section};
 use crate::common::{formatting::indent_string, rr_type::RRType};
+use crate::messages::edns::edns::Edns;
+use crate::messages::header::flags::{OpCode, RCode};
 use crate::messages::header::message_header::MessageHeader;
 use crate::messages::question::question::Question;
 use std::fmt::{Display, Formatter};
+use tracing::{debug, trace};
 
+use super::message_ref::{skip_question, skip_record};
+use super::parsing::HexDump;
 use super::serializing::Writer;
 use super::{parsing::Reader, resource_record::resource_record::ResourceRecord};
 
+/// The maximum size of a UDP message without EDNS (RFC 1035 section 2.3.4)
+pub const DNS_MAX_UDP_PAYLOAD_SIZE: usize = 512;
+
+#[derive(Clone, Debug)]
 pub struct Message {
-    pub header: MessageHeader,
+    pub header: MessageHeader, // The RCODE includes the extended bits from the OPT record
     pub questions: Vec<Question>,
     pub answer: Vec<ResourceRecord>,
     pub authority: Vec<ResourceRecord>,
     pub additional: Vec<ResourceRecord>,
+    pub edns: Option<Edns>, // Carried as an OPT record in the additional section
 }
 
 impl Message {
     pub fn parse(buf: &[u8]) -> ParseResult<Message> {
-        let mut reader = Reader::new(buf);
-
-        let header = MessageHeader::parse(&mut reader)?;
-
-        let questions = (0..(header.qd_count))
-            .map(|_| Ok(Question::parse(&mut reader)?))
-            .collect::<ParseResult<Vec<Question>>>()
-            .or_else(|err| {
-                println!("Failed to parse questions: {err}");
-                Err(ParseError::Question)
-            })?;
-
-        let answer = (0..header.an_count)
-            .map(|_| Ok(ResourceRecord::parse(&mut reader)?))
-            .collect::<ParseResult<Vec<ResourceRecord>>>()
-            .or_else(|err| {
-                println!("Failed to parse answer: {err}");
-                Err(ParseError::Answer)
-            })?;
-
-        let authority = (0..header.ns_count)
-            .map(|_| Ok(ResourceRecord::parse(&mut reader)?))
-            .collect::<ParseResult<Vec<ResourceRecord>>>()
-            .or_else(|err| {
-                println!("Failed to parse authorities: {err}");
-                Err(ParseError::Authority)
-            })?;
-
-        let additional = (0..header.ar_count)
-            .map(|_| Ok(ResourceRecord::parse(&mut reader)?))
-            .collect::<ParseResult<Vec<ResourceRecord>>>()
-            .or_else(|err| {
-                println!("Failed to parse additionals: {err}");
-                Err(ParseError::Additional)
-            })?;
-
-        Ok(Message {
-            header,
-            questions,
-            answer,
-            authority,
-            additional,
-        })
+        Self::parse_sections(buf, false).map(|partial| partial.message)
+    }
+
+    /// Parses as much of the message as possible, e.g. to make use of a response with a malformed record.
+    /// Questions and records that cannot be parsed are left out and their errors are returned with the message.
+    /// If the end of an entry cannot be found the rest of the message is left out as well.
+    /// Only fails if the header cannot be parsed.
+    pub fn parse_lenient(buf: &[u8]) -> ParseResult<PartialMessage> {
+        Self::parse_sections(buf, true)
+    }
+
+    fn parse_sections(buf: &[u8], lenient: bool) -> ParseResult<PartialMessage> {
+        let message = SectionParser::new(buf, lenient).parse_message();
+        let error = match message.as_ref() {
+            Ok(partial) => partial.errors.first(),
+            Err(err) => Some(err),
+        };
+        if let Some(err) = error {
+            debug!(
+                "Failed to parse message: {err}\n{}",
+                HexDump::new(buf, err.offset())
+            );
+        }
+        message
     }
 
-    pub fn serialize<'a>(self) -> Vec<u8> {
+    pub fn serialize(self) -> Vec<u8> {
+        self.serialize_with_limit(usize::MAX)
+    }
+
+    /// Serializes the message into at most `max_size` bytes, e.g. what the client can receive over UDP.
+    /// Records in the additional section are dropped first, then the TC bit is set and the RRsets of the answer
+    /// and authority sections that don't fit are dropped (RFC 2181 section 9).
+    /// The questions and the OPT record are always kept.
+    /// The counts in the header are those of the questions and records that were written, including the OPT record.
+    pub fn serialize_with_limit(self, max_size: usize) -> Vec<u8> {
         let mut writer = Writer::new();
 
-        self.header.serialize(&mut writer);
+        let mut header = self.header;
+        let mut edns = self.edns;
+        let r_code = u16::from(&header.flags.r_code);
+        match edns.as_mut() {
+            Some(edns) => edns.extended_rcode = (r_code >> 4) as u8,
+            // Extended RCODEs cannot be expressed without EDNS
+            None if header.flags.r_code.is_extended() => {
+                header.flags.r_code = RCode::ServerFailure;
+            }
+            None => {}
+        }
+
+        // The counts are filled in once we know what fits
+        header.clone().serialize(&mut writer);
         for question in self.questions.iter() {
             question.serialize(&mut writer);
         }
-        for answer in self.answer.iter() {
-            answer.serialize(&mut writer);
-        }
-        for authority in self.authority.iter() {
-            authority.serialize(&mut writer);
+
+        let mut edns_writer = Writer::new();
+        if let Some(edns) = edns.as_ref() {
+            edns.serialize(&mut edns_writer);
         }
-        for additional in self.additional.iter() {
-            additional.serialize(&mut writer);
+        let max_size = max_size.saturating_sub(edns_writer.len());
+
+        let (an_count, complete) = write_rrsets(&mut writer, &self.answer, max_size);
+        let (ns_count, complete) = match complete {
+            true => write_rrsets(&mut writer, &self.authority, max_size),
+            false => (0, false),
+        };
+        // Missing additional records doesn't make the response truncated
+        let ar_count = match complete {
+            true => write_rrsets(&mut writer, &self.additional, max_size).0,
+            false => 0,
+        };
+        if let Some(edns) = edns.as_ref() {
+            edns.serialize(&mut writer);
         }
 
+        header.flags.tc |= !complete;
+        header.qd_count = self.questions.len() as u16;
+        header.an_count = an_count;
+        header.ns_count = ns_count;
+        header.ar_count = ar_count + edns.iter().len() as u16;
+        let mut header_writer = Writer::new();
+        header.serialize(&mut header_writer);
+        writer.overwrite(0, &header_writer.get_serialized_message());
+
         writer.get_serialized_message()
     }
 
-    pub fn new_query(name: &str, record_type: RRType, recurse: bool) -> Self {
-        Self {
-            header: MessageHeader::new_query(recurse),
-            questions: vec![Question::new(name, record_type)],
-            answer: vec![],
-            authority: vec![],
-            additional: vec![],
+    pub fn new_query(
+        name: &DomainName,
+        record_type: RRType,
+        recurse: bool,
+        edns: Option<Edns>,
+    ) -> Self {
+        let builder = MessageBuilder::query()
+            .recursion_desired(recurse)
+            .question(Question::new(name.clone(), record_type));
+        match edns {
+            Some(edns) => builder.edns(edns),
+            None => builder,
         }
+        .build()
     }
 
     pub fn is_query(&self) -> bool {
@@ -109,32 +148,354 @@ impl Message {
     }
 
     pub fn new_response(query: &Message, answers: Vec<ResourceRecord>) -> Self {
-        let authority = vec![];
-        let additional = vec![];
-        Self {
-            header: MessageHeader::new_response(
-                &query.header,
-                answers.len() as u16,
-                authority.len() as u16,
-                additional.len() as u16,
-            ),
-            questions: query.questions.clone(),
-            answer: answers,
-            authority: authority,
-            additional: additional,
+        MessageBuilder::response(query).answers(answers).build()
+    }
+
+    /// The largest UDP response that the sender of this message can handle
+    pub fn max_udp_payload_size(&self) -> usize {
+        match self.edns.as_ref() {
+            Some(edns) => edns.max_udp_payload_size(),
+            None => DNS_MAX_UDP_PAYLOAD_SIZE,
         }
     }
 
     pub fn to_short_string(&self) -> String {
         if let Some(question) = self.questions.first() {
-            let (name, rr_type) = question.get_query_name_type();
-            format!("{rr_type} records on domain {name}",)
+            format!(
+                "{} records on domain {}",
+                question.q_type(),
+                question.q_name()
+            )
         } else {
-            format!("No questions received :(")
+            "No questions received :(".to_string()
+        }
+    }
+}
+
+/// A message of which some entries could not be parsed
+#[derive(Clone, Debug)]
+pub struct PartialMessage {
+    /// The header is kept as it was received, so the counts may be larger than the sections
+    pub message: Message,
+    /// The errors of the entries that were left out, in the order they occurred
+    pub errors: Vec<ParseError>,
+}
+
+impl PartialMessage {
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Whether the questions and the answer section were parsed in full,
+    /// so that only records of the authority and additional sections are missing
+    pub fn has_complete_answer(&self) -> bool {
+        self.errors.iter().all(|err| {
+            matches!(
+                err,
+                ParseError::InSection {
+                    section: Section::Authority | Section::Additional,
+                    ..
+                }
+            )
+        })
+    }
+}
+
+/// Reads the sections of a message in order.
+/// In lenient mode entries that cannot be parsed are skipped and their errors are collected.
+struct SectionParser<'a> {
+    reader: Reader<'a>,
+    errors: Option<Vec<ParseError>>,
+    // Set when an entry could not be skipped, nothing after it can be read
+    end_lost: bool,
+}
+
+impl<'a> SectionParser<'a> {
+    fn new(buf: &'a [u8], lenient: bool) -> Self {
+        Self {
+            reader: Reader::new(buf),
+            errors: lenient.then(Vec::new),
+            end_lost: false,
+        }
+    }
+
+    fn parse_message(mut self) -> ParseResult<PartialMessage> {
+        let mut header = MessageHeader::parse(&mut self.reader)
+            .map_err(|err| err.in_section(Section::Header, 0, self.reader.get_index()))?;
+
+        let questions = self.parse_section(
+            Section::Question,
+            header.qd_count,
+            Question::parse,
+            skip_question,
+        )?;
+        let answer = self.parse_section(
+            Section::Answer,
+            header.an_count,
+            ResourceRecord::parse,
+            skip_record,
+        )?;
+        let authority = self.parse_section(
+            Section::Authority,
+            header.ns_count,
+            ResourceRecord::parse,
+            skip_record,
+        )?;
+
+        let mut edns = None;
+        let additional = self.parse_section(
+            Section::Additional,
+            header.ar_count,
+            |reader| take_edns(ResourceRecord::parse(reader)?, &mut edns),
+            skip_record,
+        )?;
+        let additional = additional.into_iter().flatten().collect::<Vec<_>>();
+
+        if let Some(edns) = edns.as_ref() {
+            add_extended_rcode(&mut header, edns);
+        }
+
+        trace!(
+            "Parsed message {} with {} questions, {} answers, {} authority and {} additional records",
+            header.id,
+            questions.len(),
+            answer.len(),
+            authority.len(),
+            additional.len()
+        );
+        Ok(PartialMessage {
+            message: Message {
+                header,
+                questions,
+                answer,
+                authority,
+                additional,
+                edns,
+            },
+            errors: self.errors.unwrap_or_default(),
+        })
+    }
+
+    /// Parses the entries of a section, errors are marked with where in the message they occurred
+    fn parse_section<T>(
+        &mut self,
+        section: Section,
+        count: u16,
+        mut parse: impl FnMut(&mut Reader<'a>) -> ParseResult<T>,
+        skip: fn(&[u8], usize) -> ParseResult<usize>,
+    ) -> ParseResult<Vec<T>> {
+        let mut entries = vec![];
+        for index in 0..count as usize {
+            if self.end_lost {
+                break;
+            }
+
+            let start = self.reader.get_index();
+            let err = match parse(&mut self.reader) {
+                Ok(entry) => {
+                    entries.push(entry);
+                    continue;
+                }
+                Err(err) => err.in_section(section, index, self.reader.get_index()),
+            };
+
+            let Some(errors) = self.errors.as_mut() else {
+                return Err(err);
+            };
+            errors.push(err);
+            match skip(self.reader.buffer(), start) {
+                Ok(end) => self.reader.set_index(end),
+                Err(_) => self.end_lost = true,
+            }
         }
+        Ok(entries)
+    }
+}
+
+/// Keeps the first OPT record of the additional section as the EDNS of the message, returns any other record
+pub(crate) fn take_edns(
+    record: ResourceRecord,
+    edns: &mut Option<Edns>,
+) -> ParseResult<Option<ResourceRecord>> {
+    if *record.record_type() != RRType::OPT {
+        Ok(Some(record))
+    } else if edns.is_none() {
+        *edns = Some(Edns::from_record(&record)?);
+        Ok(None)
+    } else {
+        Err(ParseError::RRError(
+            "Message contains more than one OPT record".to_string(),
+        ))
     }
 }
 
+/// The OPT record holds the upper 8 bits of the 12 bit RCODE
+pub(crate) fn add_extended_rcode(header: &mut MessageHeader, edns: &Edns) {
+    let extended = (edns.extended_rcode as u16) << 4;
+    header.flags.r_code = RCode::from(extended | u16::from(&header.flags.r_code));
+}
+
+/// Builds a message, the counts in the header are derived from the sections when it is built
+pub struct MessageBuilder {
+    message: Message,
+}
+
+impl MessageBuilder {
+    /// A standard query with a random id
+    pub fn query() -> Self {
+        let mut header = MessageHeader::new_query(false);
+        header.qd_count = 0;
+
+        Self {
+            message: Message {
+                header,
+                questions: vec![],
+                answer: vec![],
+                authority: vec![],
+                additional: vec![],
+                edns: None,
+            },
+        }
+    }
+
+    /// A response to the query, with the id, op code and questions of the query.
+    /// EDNS is only used if the query used it.
+    pub fn response(query: &Message) -> Self {
+        let mut builder = Self::response_to_header(&query.header);
+        builder.message.questions = query.questions.clone();
+        // Only speak EDNS to those who do
+        builder.message.edns = query.edns.as_ref().map(|_| Edns::default());
+        builder
+    }
+
+    /// A response to a query of which only the header is known, e.g. because the rest could not be parsed
+    pub fn response_to_header(query: &MessageHeader) -> Self {
+        Self {
+            message: Message {
+                header: MessageHeader::new_response(query, 0, 0, 0),
+                questions: vec![],
+                answer: vec![],
+                authority: vec![],
+                additional: vec![],
+                edns: None,
+            },
+        }
+    }
+
+    pub fn id(mut self, id: u16) -> Self {
+        self.message.header.id = id;
+        self
+    }
+
+    pub fn op_code(mut self, op_code: OpCode) -> Self {
+        self.message.header.flags.op_code = op_code;
+        self
+    }
+
+    pub fn authoritative(mut self, authoritative: bool) -> Self {
+        self.message.header.flags.aa = authoritative;
+        self
+    }
+
+    pub fn truncated(mut self, truncated: bool) -> Self {
+        self.message.header.flags.tc = truncated;
+        self
+    }
+
+    pub fn recursion_desired(mut self, recursion_desired: bool) -> Self {
+        self.message.header.flags.rd = recursion_desired;
+        self
+    }
+
+    pub fn recursion_available(mut self, recursion_available: bool) -> Self {
+        self.message.header.flags.ra = recursion_available;
+        self
+    }
+
+    pub fn authentic_data(mut self, authentic_data: bool) -> Self {
+        self.message.header.flags.ad = authentic_data;
+        self
+    }
+
+    pub fn checking_disabled(mut self, checking_disabled: bool) -> Self {
+        self.message.header.flags.cd = checking_disabled;
+        self
+    }
+
+    /// Extended RCODEs are only kept if the message uses EDNS
+    pub fn r_code(mut self, r_code: RCode) -> Self {
+        self.message.header.flags.r_code = r_code;
+        self
+    }
+
+    pub fn question(mut self, question: Question) -> Self {
+        self.message.questions.push(question);
+        self
+    }
+
+    pub fn answer(mut self, record: ResourceRecord) -> Self {
+        self.message.answer.push(record);
+        self
+    }
+
+    pub fn answers(mut self, records: impl IntoIterator<Item = ResourceRecord>) -> Self {
+        self.message.answer.extend(records);
+        self
+    }
+
+    pub fn authority(mut self, record: ResourceRecord) -> Self {
+        self.message.authority.push(record);
+        self
+    }
+
+    pub fn additional(mut self, record: ResourceRecord) -> Self {
+        self.message.additional.push(record);
+        self
+    }
+
+    pub fn edns(mut self, edns: Edns) -> Self {
+        self.message.edns = Some(edns);
+        self
+    }
+
+    pub fn build(self) -> Message {
+        let mut message = self.message;
+        message.header.qd_count = message.questions.len() as u16;
+        message.header.an_count = message.answer.len() as u16;
+        message.header.ns_count = message.authority.len() as u16;
+        message.header.ar_count = (message.additional.len() + message.edns.iter().len()) as u16;
+        message
+    }
+}
+
+/// Continues building an existing message
+impl From<Message> for MessageBuilder {
+    fn from(message: Message) -> Self {
+        Self { message }
+    }
+}
+
+/// Writes whole RRsets as long as the writer stays within the max size,
+/// returns how many records were written and whether all of them were
+fn write_rrsets(writer: &mut Writer, records: &[ResourceRecord], max_size: usize) -> (u16, bool) {
+    let mut written = 0;
+    for rrset in records.chunk_by(|a, b| {
+        a.name() == b.name() && a.record_type() == b.record_type() && a.class() == b.class()
+    }) {
+        let start = writer.len();
+        for record in rrset.iter() {
+            record.serialize(writer);
+        }
+
+        if writer.len() > max_size {
+            writer.truncate(start);
+            return (written, false);
+        }
+        written += rrset.len() as u16;
+    }
+    (written, true)
+}
+
 impl Display for Message {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -144,7 +505,8 @@ impl Display for Message {
     Questions: {},
     Answers: {},
     Authority: {},
-    Additional: {}
+    Additional: {},
+    EDNS: {}
 }}",
             indent_string(self.header.to_string()),
             if self.questions.is_empty() {
@@ -211,6 +573,10 @@ impl Display for Message {
                     ))
                 )
             },
+            match self.edns.as_ref() {
+                Some(edns) => indent_string(edns.to_string()),
+                None => "None".to_string(),
+            },
         )
     }
 }
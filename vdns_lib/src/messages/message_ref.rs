@@ -0,0 +1,369 @@
+use std::fmt::{Display, Formatter};
+
+use tracing::debug;
+
+use crate::common::{
+    class::Class,
+    domain_name::{DomainName, LabelIter},
+    parse_error::{ParseResult, Section},
+    q_class::QClass,
+    rr_type::RRType,
+};
+
+use super::{
+    edns::edns::Edns,
+    header::message_header::MessageHeader,
+    message::{add_extended_rcode, take_edns, Message},
+    parsing::{HexDump, Reader, ReaderError},
+    question::question::Question,
+    resource_record::resource_record::ResourceRecord,
+};
+
+// The type, class, TTL and data length that follow the name of a record
+const RECORD_FIXED_LENGTH: usize = 10;
+// The type and class that follow the name of a question
+const QUESTION_FIXED_LENGTH: usize = 4;
+
+/// A view of a message in the buffer it was received in. Parsing only checks that the sections and names are
+/// well formed, the questions and records are then read as they are iterated without copying anything.
+#[derive(Clone)]
+pub struct MessageRef<'a> {
+    buf: &'a [u8],
+    header: MessageHeader,
+    question_start: usize,
+    answer_start: usize,
+    authority_start: usize,
+    additional_start: usize,
+}
+
+impl<'a> MessageRef<'a> {
+    pub fn parse(buf: &'a [u8]) -> ParseResult<MessageRef<'a>> {
+        let message = Self::parse_sections(buf);
+        if let Err(err) = message.as_ref() {
+            debug!(
+                "Failed to parse message: {err}\n{}",
+                HexDump::new(buf, err.offset())
+            );
+        }
+        message
+    }
+
+    fn parse_sections(buf: &'a [u8]) -> ParseResult<MessageRef<'a>> {
+        let mut reader = Reader::new(buf);
+        let header = MessageHeader::parse(&mut reader)
+            .map_err(|err| err.in_section(Section::Header, 0, reader.get_index()))?;
+
+        let question_start = reader.get_index();
+        let mut index = question_start;
+        for i in 0..header.qd_count as usize {
+            index = skip_question(buf, index)
+                .map_err(|err| err.in_section(Section::Question, i, index))?;
+        }
+
+        let answer_start = index;
+        index = skip_records(buf, index, Section::Answer, header.an_count)?;
+        let authority_start = index;
+        index = skip_records(buf, index, Section::Authority, header.ns_count)?;
+        let additional_start = index;
+        skip_records(buf, index, Section::Additional, header.ar_count)?;
+
+        Ok(MessageRef {
+            buf,
+            header,
+            question_start,
+            answer_start,
+            authority_start,
+            additional_start,
+        })
+    }
+
+    pub fn header(&self) -> &MessageHeader {
+        &self.header
+    }
+
+    /// The buffer that the message was parsed from
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.buf
+    }
+
+    pub fn questions(&self) -> impl Iterator<Item = QuestionRef<'a>> {
+        let buf = self.buf;
+        let mut index = self.question_start;
+        (0..self.header.qd_count).map(move |_| {
+            let name = NameRef { buf, offset: index };
+            let name_end = skip_name(buf, index).expect("Names are checked when parsing");
+            index = name_end + QUESTION_FIXED_LENGTH;
+            QuestionRef {
+                name,
+                q_type: read_u16(buf, name_end),
+                q_class: read_u16(buf, name_end + 2),
+            }
+        })
+    }
+
+    pub fn answer(&self) -> impl Iterator<Item = RecordRef<'a>> {
+        records(self.buf, self.answer_start, self.header.an_count)
+    }
+
+    pub fn authority(&self) -> impl Iterator<Item = RecordRef<'a>> {
+        records(self.buf, self.authority_start, self.header.ns_count)
+    }
+
+    /// The records of the additional section, including any OPT record
+    pub fn additional(&self) -> impl Iterator<Item = RecordRef<'a>> {
+        records(self.buf, self.additional_start, self.header.ar_count)
+    }
+
+    /// Decodes only the OPT record, if there is one
+    pub fn edns(&self) -> ParseResult<Option<Edns>> {
+        self.additional()
+            .find(|r| r.rr_type() == RRType::OPT)
+            .map(|r| r.to_record().and_then(|r| Edns::from_record(&r)))
+            .transpose()
+    }
+
+    /// Decodes the whole message, reading the entries from where they were found when parsing
+    pub fn to_message(&self) -> ParseResult<Message> {
+        let mut header = self.header.clone();
+        let questions = self.questions().map(|q| q.to_question()).collect();
+        let answer = to_records(self.answer(), Section::Answer)?;
+        let authority = to_records(self.authority(), Section::Authority)?;
+
+        let mut edns = None;
+        let mut additional = vec![];
+        for (i, record) in self.additional().enumerate() {
+            let parsed = record
+                .to_record()
+                .and_then(|r| take_edns(r, &mut edns))
+                .map_err(|err| err.in_section(Section::Additional, i, record.offset()))?;
+            additional.extend(parsed);
+        }
+
+        if let Some(edns) = edns.as_ref() {
+            add_extended_rcode(&mut header, edns);
+        }
+
+        Ok(Message {
+            header,
+            questions,
+            answer,
+            authority,
+            additional,
+            edns,
+        })
+    }
+
+    pub fn to_short_string(&self) -> String {
+        match self.questions().next() {
+            Some(question) => format!(
+                "{} records on domain {}",
+                question.q_type(),
+                question.name()
+            ),
+            None => "No questions received :(".to_string(),
+        }
+    }
+}
+
+/// A name in the buffer of a message, its labels are read when needed
+#[derive(Clone, Copy)]
+pub struct NameRef<'a> {
+    buf: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> NameRef<'a> {
+    /// The labels of the name, most specific first
+    pub fn labels(&self) -> impl Iterator<Item = &'a [u8]> {
+        // The name was checked when the message was parsed
+        LabelIter::new(self.buf, self.offset).map_while(Result::ok)
+    }
+
+    pub fn num_labels(&self) -> usize {
+        self.labels().count()
+    }
+
+    /// Whether the name is the same as the other, compared case-insensitively
+    pub fn eq_name(&self, other: &DomainName) -> bool {
+        let mut labels = self.labels();
+        other
+            .labels()
+            .iter()
+            .all(|l| labels.next().is_some_and(|o| o.eq_ignore_ascii_case(l)))
+            && labels.next().is_none()
+    }
+
+    /// Whether the name is the other name or below it, compared case-insensitively
+    pub fn is_subdomain_of(&self, other: &DomainName) -> bool {
+        let count = self.num_labels();
+        count >= other.num_labels()
+            && self
+                .labels()
+                .skip(count - other.num_labels())
+                .zip(other.labels().iter())
+                .all(|(a, b)| a.eq_ignore_ascii_case(b))
+    }
+
+    pub fn to_domain_name(&self) -> DomainName {
+        DomainName::from_labels(self.labels().map(|l| l.to_vec()).collect())
+            .expect("Names are checked when parsing")
+    }
+}
+
+impl Display for NameRef<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_domain_name())
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct QuestionRef<'a> {
+    name: NameRef<'a>,
+    q_type: u16,
+    q_class: u16,
+}
+
+impl<'a> QuestionRef<'a> {
+    pub fn name(&self) -> NameRef<'a> {
+        self.name
+    }
+
+    pub fn q_type(&self) -> RRType {
+        RRType::from(self.q_type)
+    }
+
+    pub fn q_class(&self) -> QClass {
+        QClass::from(self.q_class)
+    }
+
+    pub fn to_question(&self) -> Question {
+        Question::from_parts(self.name.to_domain_name(), self.q_type(), self.q_class())
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct RecordRef<'a> {
+    buf: &'a [u8],
+    offset: usize,
+    name: NameRef<'a>,
+    rr_type: u16,
+    class: u16,
+    ttl: u32,
+    rdata: &'a [u8],
+}
+
+impl<'a> RecordRef<'a> {
+    pub fn name(&self) -> NameRef<'a> {
+        self.name
+    }
+
+    pub fn rr_type(&self) -> RRType {
+        RRType::from(self.rr_type)
+    }
+
+    /// Where the record starts in the message
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The class of the record, for OPT records this is the UDP payload size
+    pub fn class(&self) -> Class {
+        Class::from(self.class)
+    }
+
+    pub fn ttl(&self) -> u32 {
+        self.ttl
+    }
+
+    /// The record data as it is in the message, names within it may point to other parts of the message
+    pub fn rdata(&self) -> &'a [u8] {
+        self.rdata
+    }
+
+    pub fn to_record(&self) -> ParseResult<ResourceRecord> {
+        let mut reader = Reader::new(self.buf);
+        reader.set_index(self.offset);
+        ResourceRecord::parse(&mut reader)
+    }
+}
+
+fn records(buf: &[u8], start: usize, count: u16) -> impl Iterator<Item = RecordRef<'_>> {
+    let mut index = start;
+    (0..count).map(move |_| {
+        let offset = index;
+        let name_end = skip_name(buf, offset).expect("Names are checked when parsing");
+        let rd_length = read_u16(buf, name_end + 8) as usize;
+        let rdata_start = name_end + RECORD_FIXED_LENGTH;
+        index = rdata_start + rd_length;
+
+        RecordRef {
+            buf,
+            offset,
+            name: NameRef { buf, offset },
+            rr_type: read_u16(buf, name_end),
+            class: read_u16(buf, name_end + 2),
+            ttl: ((read_u16(buf, name_end + 4) as u32) << 16) | read_u16(buf, name_end + 6) as u32,
+            rdata: &buf[rdata_start..index],
+        }
+    })
+}
+
+fn to_records<'a>(
+    records: impl Iterator<Item = RecordRef<'a>>,
+    section: Section,
+) -> ParseResult<Vec<ResourceRecord>> {
+    records
+        .enumerate()
+        .map(|(i, record)| {
+            record
+                .to_record()
+                .map_err(|err| err.in_section(section, i, record.offset()))
+        })
+        .collect()
+}
+
+/// Checks the name and returns the index right after it
+fn skip_name(buf: &[u8], index: usize) -> ParseResult<usize> {
+    let mut labels = LabelIter::new(buf, index);
+    for label in labels.by_ref() {
+        label?;
+    }
+    Ok(labels.end_index())
+}
+
+/// Checks the name of the question and returns the index right after it
+pub(crate) fn skip_question(buf: &[u8], index: usize) -> ParseResult<usize> {
+    let end = skip_name(buf, index)? + QUESTION_FIXED_LENGTH;
+    if end > buf.len() {
+        return Err(ReaderError::U16.into());
+    }
+    Ok(end)
+}
+
+/// Checks the records of a section and returns the index right after them
+fn skip_records(buf: &[u8], start: usize, section: Section, count: u16) -> ParseResult<usize> {
+    let mut index = start;
+    for i in 0..count as usize {
+        index = skip_record(buf, index).map_err(|err| err.in_section(section, i, index))?;
+    }
+    Ok(index)
+}
+
+/// Checks the name and length of the record and returns the index right after it
+pub(crate) fn skip_record(buf: &[u8], index: usize) -> ParseResult<usize> {
+    let name_end = skip_name(buf, index)?;
+    let rdata_start = name_end + RECORD_FIXED_LENGTH;
+    if rdata_start > buf.len() {
+        return Err(ReaderError::U16.into());
+    }
+
+    let end = rdata_start + read_u16(buf, name_end + 8) as usize;
+    if end > buf.len() {
+        return Err(ReaderError::Vec.into());
+    }
+    Ok(end)
+}
+
+fn read_u16(buf: &[u8], index: usize) -> u16 {
+    u16::from_be_bytes([buf[index], buf[index + 1]])
+}
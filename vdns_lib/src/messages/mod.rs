@@ -1,6 +1,10 @@
+pub mod edns;
 pub mod header;
 pub mod message;
+pub mod message_ref;
 pub mod parsing;
 pub mod question;
 pub mod resource_record;
 pub mod serializing;
+pub mod tsig;
+pub mod update;
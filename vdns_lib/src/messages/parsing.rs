@@ -1,4 +1,9 @@
-use std::string::FromUtf8Error;
+use std::{
+    fmt::{Display, Formatter},
+    string::FromUtf8Error,
+};
+
+const HEX_DUMP_LINE_LENGTH: usize = 16;
 
 pub struct Reader<'a> {
     buffer: &'a [u8],
@@ -37,13 +42,18 @@ impl<'a> Reader<'a> {
         }
     }
 
+    /// The whole message being read
+    pub fn buffer(&self) -> &'a [u8] {
+        self.buffer
+    }
+
     pub fn get_index(&self) -> usize {
         self.index
     }
 
     pub fn read_u8(&mut self) -> ReaderResult<u8> {
         let b = *self.buffer.get(self.index).ok_or(ReaderError::U8)?;
-        self.index = self.index + 1;
+        self.index += 1;
         Ok(b)
     }
 
@@ -97,6 +107,14 @@ impl<'a> Reader<'a> {
         Ok(v)
     }
 
+    /// Like `read_vec` but fails if there are fewer than `len` bytes left
+    pub fn read_exact_vec(&mut self, len: usize) -> ReaderResult<Vec<u8>> {
+        let end = self.index + len;
+        let v = Vec::from(self.buffer.get(self.index..end).ok_or(ReaderError::Vec)?);
+        self.index = end;
+        Ok(v)
+    }
+
     pub fn peek_remaining_bytes(&self) -> &[u8] {
         &self.buffer[self.index..]
     }
@@ -105,9 +123,58 @@ impl<'a> Reader<'a> {
         self.index = new_index;
     }
 
-    pub fn print_current_message(&self) {
-        println!("Buffer as of now: \n{:0x?}\n", &self.buffer[0..self.index]);
-        let message = String::from_utf8_lossy(self.buffer);
-        println!("Message as of now: \n'{message}'");
+    /// A hex dump of the message that highlights where the reader is
+    pub fn hex_dump(&self) -> HexDump<'a> {
+        HexDump::new(self.buffer, Some(self.index))
+    }
+}
+
+/// Formats a message as a hex dump with 16 bytes per line, e.g. for showing where parsing failed.
+/// The highlighted byte is surrounded by brackets.
+pub struct HexDump<'a> {
+    buffer: &'a [u8],
+    highlight: Option<usize>,
+}
+
+impl<'a> HexDump<'a> {
+    pub fn new(buffer: &'a [u8], highlight: Option<usize>) -> Self {
+        Self { buffer, highlight }
+    }
+}
+
+impl Display for HexDump<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (line, bytes) in self.buffer.chunks(HEX_DUMP_LINE_LENGTH).enumerate() {
+            let start = line * HEX_DUMP_LINE_LENGTH;
+            write!(f, "{start:04x}:")?;
+            for (offset, byte) in (start..).zip(bytes) {
+                match self.highlight == Some(offset) {
+                    true => write!(f, "[{byte:02x}]")?,
+                    false => write!(f, " {byte:02x} ")?,
+                }
+            }
+            // Keep the text of a short last line aligned with the rest
+            for _ in bytes.len()..HEX_DUMP_LINE_LENGTH {
+                write!(f, "    ")?;
+            }
+
+            let text = bytes
+                .iter()
+                .map(|&b| match b.is_ascii_graphic() || b == b' ' {
+                    true => b as char,
+                    false => '.',
+                })
+                .collect::<String>();
+            writeln!(f, " |{text}|")?;
+        }
+
+        if let Some(highlight) = self.highlight.filter(|&h| h >= self.buffer.len()) {
+            writeln!(
+                f,
+                "[offset {highlight:04x} is past the end of the {} byte message]",
+                self.buffer.len()
+            )?;
+        }
+        Ok(())
     }
 }
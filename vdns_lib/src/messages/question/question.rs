@@ -1,4 +1,4 @@
-use crate::common::parse_error::{ParseError, ParseResult};
+use crate::common::parse_error::ParseResult;
 use crate::common::rr_type::RRType;
 use crate::common::{domain_name::DomainName, q_class::QClass};
 use crate::messages::parsing::Reader;
@@ -14,18 +14,10 @@ pub struct Question {
 
 impl Question {
     pub fn parse(reader: &mut Reader) -> ParseResult<Question> {
-        let name = DomainName::parse(reader)?;
-
         Ok(Question {
-            q_name: name,
-            q_type: RRType::parse(reader).or_else(|err| {
-                println!("Failed to parse QType {err}");
-                Err(ParseError::Question)
-            })?,
-            q_class: QClass::parse(reader).or_else(|err| {
-                println!("Failed to parse QClass {err}");
-                Err(ParseError::Question)
-            })?,
+            q_name: DomainName::parse(reader)?,
+            q_type: RRType::parse(reader)?,
+            q_class: QClass::parse(reader)?,
         })
     }
 
@@ -35,14 +27,34 @@ impl Question {
         self.q_class.serialize(writer);
     }
 
-    pub fn new(name: &str, requested_type: RRType) -> Self {
+    pub fn new(name: DomainName, requested_type: RRType) -> Self {
         Self {
-            q_name: DomainName::from_string(name),
+            q_name: name,
             q_type: requested_type,
             q_class: QClass::IN,
         }
     }
 
+    pub fn from_parts(q_name: DomainName, q_type: RRType, q_class: QClass) -> Self {
+        Self {
+            q_name,
+            q_type,
+            q_class,
+        }
+    }
+
+    pub fn q_name(&self) -> &DomainName {
+        &self.q_name
+    }
+
+    pub fn q_type(&self) -> &RRType {
+        &self.q_type
+    }
+
+    pub fn q_class(&self) -> &QClass {
+        &self.q_class
+    }
+
     pub fn get_query_name_type(&self) -> (DomainName, RRType) {
         (self.q_name.clone(), self.q_type.clone())
     }
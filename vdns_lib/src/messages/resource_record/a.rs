@@ -1,18 +1,25 @@
-use std::{fmt::Display, net::Ipv4Addr};
+use std::{fmt::Display, net::Ipv4Addr, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    common::parse_error::ParseResult,
+    common::{
+        parse_error::{ParseError, ParseResult},
+        presentation::parse_field,
+    },
     messages::{parsing::Reader, serializing::Writer},
 };
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct A {
     address: Ipv4Addr,
 }
 
 impl A {
+    pub fn new(address: Ipv4Addr) -> Self {
+        Self { address }
+    }
+
     pub fn parse(reader: &mut Reader) -> ParseResult<Self> {
         Ok(A {
             address: Ipv4Addr::from(reader.read_u32()?),
@@ -24,6 +31,10 @@ impl A {
             writer.write_u8(*b);
         }
     }
+
+    pub fn address(&self) -> &Ipv4Addr {
+        &self.address
+    }
 }
 
 impl Display for A {
@@ -31,3 +42,12 @@ impl Display for A {
         write!(f, "{}", self.address)
     }
 }
+
+/// Parses the presentation format, e.g. `192.0.2.1`
+impl FromStr for A {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(A::new(parse_field(s.trim(), "IPv4 address")?))
+    }
+}
@@ -1,18 +1,25 @@
-use std::{fmt::Display, net::Ipv6Addr};
+use std::{fmt::Display, net::Ipv6Addr, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    common::parse_error::ParseResult,
+    common::{
+        parse_error::{ParseError, ParseResult},
+        presentation::parse_field,
+    },
     messages::{parsing::Reader, serializing::Writer},
 };
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AAAA {
     address: Ipv6Addr,
 }
 
 impl AAAA {
+    pub fn new(address: Ipv6Addr) -> Self {
+        Self { address }
+    }
+
     pub fn parse(reader: &mut Reader) -> ParseResult<Self> {
         Ok(AAAA {
             address: Ipv6Addr::from(reader.read_u128()?),
@@ -24,6 +31,10 @@ impl AAAA {
             writer.write_u8(*b);
         }
     }
+
+    pub fn address(&self) -> &Ipv6Addr {
+        &self.address
+    }
 }
 
 impl Display for AAAA {
@@ -31,3 +42,12 @@ impl Display for AAAA {
         write!(f, "{}", self.address)
     }
 }
+
+/// Parses the presentation format, e.g. `2001:db8::1`
+impl FromStr for AAAA {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(AAAA::new(parse_field(s.trim(), "IPv6 address")?))
+    }
+}
@@ -0,0 +1,121 @@
+use std::{fmt::Display, str::FromStr};
+
+use data_encoding::BASE64;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{
+        parse_error::{ParseError, ParseResult},
+        presentation::{decode_field, parse_field, split_tokens},
+    },
+    messages::{parsing::Reader, serializing::Writer},
+};
+
+const ZONE_KEY_FLAG: u16 = 0b0000_0001_0000_0000;
+const SECURE_ENTRY_POINT_FLAG: u16 = 0b0000_0000_0000_0001;
+
+/// DNS public key (RFC 4034 section 2), also used for CDNSKEY records
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DNSKEY {
+    flags: u16,
+    protocol: u8, // Always 3
+    algorithm: u8,
+    public_key: Vec<u8>,
+}
+
+impl DNSKEY {
+    pub fn parse(reader: &mut Reader, length: u16) -> ParseResult<Self> {
+        let end = reader.get_index() + length as usize;
+        let flags = reader.read_u16()?;
+        let protocol = reader.read_u8()?;
+        let algorithm = reader.read_u8()?;
+        let public_key = reader.read_exact_vec(end.saturating_sub(reader.get_index()))?;
+
+        Ok(DNSKEY {
+            flags,
+            protocol,
+            algorithm,
+            public_key,
+        })
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        writer.write_u16(self.flags);
+        writer.write_u8(self.protocol);
+        writer.write_u8(self.algorithm);
+        self.public_key.iter().for_each(|b| writer.write_u8(*b));
+    }
+
+    pub fn is_zone_key(&self) -> bool {
+        self.flags & ZONE_KEY_FLAG != 0
+    }
+
+    pub fn is_secure_entry_point(&self) -> bool {
+        self.flags & SECURE_ENTRY_POINT_FLAG != 0
+    }
+
+    pub fn algorithm(&self) -> u8 {
+        self.algorithm
+    }
+
+    /// The key tag used to refer to this key from RRSIG and DS records (RFC 4034 appendix B)
+    pub fn key_tag(&self) -> u16 {
+        if self.algorithm == 1 {
+            // RSA/MD5 uses the most significant 16 bits of the least significant 24 bits of the modulus
+            let len = self.public_key.len();
+            if len < 3 {
+                return 0;
+            }
+            return u16::from_be_bytes([self.public_key[len - 3], self.public_key[len - 2]]);
+        }
+
+        let mut writer = Writer::new();
+        self.serialize(&mut writer);
+        let mut acc: u32 = writer
+            .get_serialized_message()
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                if i & 1 == 1 {
+                    *b as u32
+                } else {
+                    (*b as u32) << 8
+                }
+            })
+            .sum();
+        acc += (acc >> 16) & 0xFFFF;
+        (acc & 0xFFFF) as u16
+    }
+}
+
+impl Display for DNSKEY {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.flags,
+            self.protocol,
+            self.algorithm,
+            BASE64.encode(&self.public_key)
+        )
+    }
+}
+
+/// Parses the presentation format, the flags, protocol and algorithm followed by the base64 encoded public key
+impl FromStr for DNSKEY {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match split_tokens(s)?.as_slice() {
+            [flags, protocol, algorithm, public_key @ ..] if !public_key.is_empty() => Ok(DNSKEY {
+                flags: parse_field(flags, "DNSKEY flags")?,
+                protocol: parse_field(protocol, "DNSKEY protocol")?,
+                algorithm: parse_field(algorithm, "DNSKEY algorithm")?,
+                public_key: decode_field(&BASE64, public_key, "DNSKEY public key")?,
+            }),
+            _ => Err(ParseError::RRError(format!(
+                "DNSKEY data '{s}' should be the flags, protocol, algorithm and public key"
+            ))),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::{fmt::Display, str::FromStr};
+
+use data_encoding::{HEXUPPER, HEXUPPER_PERMISSIVE};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{
+        parse_error::{ParseError, ParseResult},
+        presentation::{decode_field, parse_field, split_tokens},
+    },
+    messages::{parsing::Reader, serializing::Writer},
+};
+
+/// Delegation signer (RFC 4034 section 5), also used for CDS records
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DS {
+    key_tag: u16,
+    algorithm: u8,
+    digest_type: u8,
+    digest: Vec<u8>,
+}
+
+impl DS {
+    pub fn parse(reader: &mut Reader, length: u16) -> ParseResult<Self> {
+        let end = reader.get_index() + length as usize;
+        let key_tag = reader.read_u16()?;
+        let algorithm = reader.read_u8()?;
+        let digest_type = reader.read_u8()?;
+        let digest = reader.read_exact_vec(end.saturating_sub(reader.get_index()))?;
+
+        Ok(DS {
+            key_tag,
+            algorithm,
+            digest_type,
+            digest,
+        })
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        writer.write_u16(self.key_tag);
+        writer.write_u8(self.algorithm);
+        writer.write_u8(self.digest_type);
+        self.digest.iter().for_each(|b| writer.write_u8(*b));
+    }
+
+    pub fn key_tag(&self) -> u16 {
+        self.key_tag
+    }
+}
+
+impl Display for DS {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.key_tag,
+            self.algorithm,
+            self.digest_type,
+            HEXUPPER.encode(&self.digest)
+        )
+    }
+}
+
+/// Parses the presentation format, the key tag, algorithm and digest type followed by the hex encoded digest
+impl FromStr for DS {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match split_tokens(s)?.as_slice() {
+            [key_tag, algorithm, digest_type, digest @ ..] if !digest.is_empty() => Ok(DS {
+                key_tag: parse_field(key_tag, "DS key tag")?,
+                algorithm: parse_field(algorithm, "DS algorithm")?,
+                digest_type: parse_field(digest_type, "DS digest type")?,
+                digest: decode_field(&HEXUPPER_PERMISSIVE, digest, "DS digest")?,
+            }),
+            _ => Err(ParseError::RRError(format!(
+                "DS data '{s}' should be the key tag, algorithm, digest type and digest"
+            ))),
+        }
+    }
+}
@@ -1,5 +1,18 @@
 pub mod a;
 pub mod aaaa;
+pub mod dnskey;
+pub mod ds;
+pub mod mx;
+pub mod naptr;
+pub mod nsec;
+pub mod nsec3;
+#[allow(clippy::module_inception)]
 pub mod resource_record;
 pub mod rr_data;
+pub mod rrsig;
 pub mod soa;
+pub mod srv;
+pub mod svcb;
+pub mod tsig;
+pub mod txt;
+pub mod type_bitmap;
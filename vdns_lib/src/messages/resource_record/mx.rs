@@ -0,0 +1,59 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{
+        domain_name::DomainName,
+        parse_error::{ParseError, ParseResult},
+        presentation::{parse_field, split_tokens},
+    },
+    messages::{parsing::Reader, serializing::Writer},
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MX {
+    preference: u16, // Lower values are preferred
+    exchange: DomainName,
+}
+
+impl MX {
+    pub fn parse(reader: &mut Reader) -> ParseResult<Self> {
+        Ok(MX {
+            preference: reader.read_u16()?,
+            exchange: DomainName::parse(reader)?,
+        })
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        writer.write_u16(self.preference);
+        self.exchange.serialize(writer);
+    }
+}
+
+impl Display for MX {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "MX {{ preference: {}, exchange: {} }}",
+            self.preference, self.exchange
+        )
+    }
+}
+
+/// Parses the presentation format, e.g. `10 mail.example.com.`
+impl FromStr for MX {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match split_tokens(s)?.as_slice() {
+            [preference, exchange] => Ok(MX {
+                preference: parse_field(preference, "MX preference")?,
+                exchange: exchange.parse()?,
+            }),
+            _ => Err(ParseError::RRError(format!(
+                "MX data '{s}' should be a preference and an exchange"
+            ))),
+        }
+    }
+}
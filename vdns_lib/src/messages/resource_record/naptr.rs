@@ -0,0 +1,96 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{
+        character_string::CharacterString,
+        domain_name::DomainName,
+        parse_error::{ParseError, ParseResult},
+        presentation::{parse_field, split_tokens},
+    },
+    messages::{parsing::Reader, serializing::Writer},
+};
+
+/// Naming authority pointer (RFC 3403)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NAPTR {
+    order: u16,
+    preference: u16,
+    flags: CharacterString,
+    services: CharacterString,
+    regexp: CharacterString,
+    replacement: DomainName,
+}
+
+impl NAPTR {
+    pub fn new(
+        order: u16,
+        preference: u16,
+        flags: CharacterString,
+        services: CharacterString,
+        regexp: CharacterString,
+        replacement: DomainName,
+    ) -> Self {
+        Self {
+            order,
+            preference,
+            flags,
+            services,
+            regexp,
+            replacement,
+        }
+    }
+
+    pub fn parse(reader: &mut Reader) -> ParseResult<Self> {
+        Ok(NAPTR {
+            order: reader.read_u16()?,
+            preference: reader.read_u16()?,
+            flags: CharacterString::parse(reader)?,
+            services: CharacterString::parse(reader)?,
+            regexp: CharacterString::parse(reader)?,
+            replacement: DomainName::parse(reader)?,
+        })
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        writer.write_u16(self.order);
+        writer.write_u16(self.preference);
+        self.flags.serialize(writer);
+        self.services.serialize(writer);
+        self.regexp.serialize(writer);
+        // The replacement must not be compressed
+        self.replacement.serialize_uncompressed(writer);
+    }
+}
+
+impl Display for NAPTR {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {} {}",
+            self.order, self.preference, self.flags, self.services, self.regexp, self.replacement
+        )
+    }
+}
+
+/// Parses the presentation format, e.g. `100 10 "S" "SIP+D2U" "" _sip._udp.example.com.`
+impl FromStr for NAPTR {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match split_tokens(s)?.as_slice() {
+            [order, preference, flags, services, regexp, replacement] => Ok(NAPTR::new(
+                parse_field(order, "NAPTR order")?,
+                parse_field(preference, "NAPTR preference")?,
+                flags.parse()?,
+                services.parse()?,
+                regexp.parse()?,
+                replacement.parse()?,
+            )),
+            _ => Err(ParseError::RRError(format!(
+                "NAPTR data '{s}' should be an order, preference, flags, services, regexp and replacement"
+            ))),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{
+        domain_name::DomainName,
+        parse_error::{ParseError, ParseResult},
+        presentation::split_tokens,
+    },
+    messages::{parsing::Reader, serializing::Writer},
+};
+
+use super::type_bitmap::TypeBitmap;
+
+/// Next secure record (RFC 4034 section 4)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NSEC {
+    next_domain_name: DomainName,
+    types: TypeBitmap,
+}
+
+impl NSEC {
+    pub fn parse(reader: &mut Reader, length: u16) -> ParseResult<Self> {
+        let end = reader.get_index() + length as usize;
+        let next_domain_name = DomainName::parse(reader)?;
+        let types = TypeBitmap::parse(reader, end.saturating_sub(reader.get_index()))?;
+
+        Ok(NSEC {
+            next_domain_name,
+            types,
+        })
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        // The next domain name must not be compressed
+        self.next_domain_name.serialize_uncompressed(writer);
+        self.types.serialize(writer);
+    }
+
+    pub fn next_domain_name(&self) -> &DomainName {
+        &self.next_domain_name
+    }
+
+    pub fn types(&self) -> &TypeBitmap {
+        &self.types
+    }
+}
+
+impl Display for NSEC {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.next_domain_name, self.types)
+    }
+}
+
+/// Parses the presentation format, the next domain name followed by the types, e.g. `b.example.com. A AAAA RRSIG`
+impl FromStr for NSEC {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match split_tokens(s)?.as_slice() {
+            [next_domain_name, types @ ..] => Ok(NSEC {
+                next_domain_name: next_domain_name.parse()?,
+                types: types.join(" ").parse()?,
+            }),
+            [] => Err(ParseError::RRError(
+                "NSEC data is missing the next domain name".to_string(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use std::{fmt::Display, str::FromStr};
+
+use data_encoding::{BASE32HEX_NOPAD, HEXUPPER, HEXUPPER_PERMISSIVE};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{
+        parse_error::{ParseError, ParseResult},
+        presentation::{decode_field, parse_field, split_tokens},
+    },
+    messages::{parsing::Reader, serializing::Writer},
+};
+
+use super::type_bitmap::TypeBitmap;
+
+const OPT_OUT_FLAG: u8 = 0b0000_0001;
+
+/// Hashed next secure record (RFC 5155 section 3)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NSEC3 {
+    hash_algorithm: u8,
+    flags: u8,
+    iterations: u16,
+    salt: Vec<u8>,
+    next_hashed_owner_name: Vec<u8>,
+    types: TypeBitmap,
+}
+
+impl NSEC3 {
+    pub fn parse(reader: &mut Reader, length: u16) -> ParseResult<Self> {
+        let end = reader.get_index() + length as usize;
+        let hash_algorithm = reader.read_u8()?;
+        let flags = reader.read_u8()?;
+        let iterations = reader.read_u16()?;
+        let salt_length = reader.read_u8()?;
+        let salt = reader.read_exact_vec(salt_length as usize)?;
+        let hash_length = reader.read_u8()?;
+        let next_hashed_owner_name = reader.read_exact_vec(hash_length as usize)?;
+        let types = TypeBitmap::parse(reader, end.saturating_sub(reader.get_index()))?;
+
+        Ok(NSEC3 {
+            hash_algorithm,
+            flags,
+            iterations,
+            salt,
+            next_hashed_owner_name,
+            types,
+        })
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        writer.write_u8(self.hash_algorithm);
+        writer.write_u8(self.flags);
+        writer.write_u16(self.iterations);
+        writer.write_u8(self.salt.len() as u8);
+        self.salt.iter().for_each(|b| writer.write_u8(*b));
+        writer.write_u8(self.next_hashed_owner_name.len() as u8);
+        self.next_hashed_owner_name
+            .iter()
+            .for_each(|b| writer.write_u8(*b));
+        self.types.serialize(writer);
+    }
+
+    pub fn is_opt_out(&self) -> bool {
+        self.flags & OPT_OUT_FLAG != 0
+    }
+
+    pub fn types(&self) -> &TypeBitmap {
+        &self.types
+    }
+}
+
+impl Display for NSEC3 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {} {}",
+            self.hash_algorithm,
+            self.flags,
+            self.iterations,
+            format_salt(&self.salt),
+            BASE32HEX_NOPAD
+                .encode(&self.next_hashed_owner_name)
+                .to_lowercase(),
+            self.types
+        )
+    }
+}
+
+/// Hashed authenticated denial of existence parameters (RFC 5155 section 4)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NSEC3PARAM {
+    hash_algorithm: u8,
+    flags: u8,
+    iterations: u16,
+    salt: Vec<u8>,
+}
+
+impl NSEC3PARAM {
+    pub fn parse(reader: &mut Reader) -> ParseResult<Self> {
+        let hash_algorithm = reader.read_u8()?;
+        let flags = reader.read_u8()?;
+        let iterations = reader.read_u16()?;
+        let salt_length = reader.read_u8()?;
+        let salt = reader.read_exact_vec(salt_length as usize)?;
+
+        Ok(NSEC3PARAM {
+            hash_algorithm,
+            flags,
+            iterations,
+            salt,
+        })
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        writer.write_u8(self.hash_algorithm);
+        writer.write_u8(self.flags);
+        writer.write_u16(self.iterations);
+        writer.write_u8(self.salt.len() as u8);
+        self.salt.iter().for_each(|b| writer.write_u8(*b));
+    }
+}
+
+impl Display for NSEC3PARAM {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.hash_algorithm,
+            self.flags,
+            self.iterations,
+            format_salt(&self.salt)
+        )
+    }
+}
+
+/// An empty salt is presented as `-`
+fn format_salt(salt: &[u8]) -> String {
+    if salt.is_empty() {
+        "-".to_string()
+    } else {
+        HEXUPPER.encode(salt)
+    }
+}
+
+/// Parses the presentation format, e.g. `1 0 10 AABBCCDD 2vptu5timamqttgl4luu9kg21e0aor3s A RRSIG`
+impl FromStr for NSEC3 {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match split_tokens(s)?.as_slice() {
+            [hash_algorithm, flags, iterations, salt, next_hashed_owner_name, types @ ..] => {
+                Ok(NSEC3 {
+                    hash_algorithm: parse_field(hash_algorithm, "NSEC3 hash algorithm")?,
+                    flags: parse_field(flags, "NSEC3 flags")?,
+                    iterations: parse_field(iterations, "NSEC3 iterations")?,
+                    salt: parse_salt(salt)?,
+                    next_hashed_owner_name: check_length(
+                        decode_field(
+                            &BASE32HEX_NOPAD,
+                            &[next_hashed_owner_name.to_ascii_uppercase()],
+                            "NSEC3 next hashed owner name",
+                        )?,
+                        "NSEC3 next hashed owner name",
+                    )?,
+                    types: types.join(" ").parse()?,
+                })
+            }
+            _ => Err(ParseError::RRError(format!(
+                "NSEC3 data '{s}' is missing fields"
+            ))),
+        }
+    }
+}
+
+/// Parses the presentation format, e.g. `1 0 10 AABBCCDD`
+impl FromStr for NSEC3PARAM {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match split_tokens(s)?.as_slice() {
+            [hash_algorithm, flags, iterations, salt] => Ok(NSEC3PARAM {
+                hash_algorithm: parse_field(hash_algorithm, "NSEC3PARAM hash algorithm")?,
+                flags: parse_field(flags, "NSEC3PARAM flags")?,
+                iterations: parse_field(iterations, "NSEC3PARAM iterations")?,
+                salt: parse_salt(salt)?,
+            }),
+            _ => Err(ParseError::RRError(format!(
+                "NSEC3PARAM data '{s}' should be the hash algorithm, flags, iterations and salt"
+            ))),
+        }
+    }
+}
+
+fn parse_salt(salt: &str) -> ParseResult<Vec<u8>> {
+    if salt == "-" {
+        return Ok(vec![]);
+    }
+    check_length(
+        decode_field(&HEXUPPER_PERMISSIVE, &[salt.to_string()], "salt")?,
+        "Salt",
+    )
+}
+
+/// The salt and hash are written with a single length octet
+fn check_length(data: Vec<u8>, field: &str) -> ParseResult<Vec<u8>> {
+    if data.len() > u8::MAX as usize {
+        return Err(ParseError::RRError(format!(
+            "{field} of {} bytes is longer than {} bytes",
+            data.len(),
+            u8::MAX
+        )));
+    }
+    Ok(data)
+}
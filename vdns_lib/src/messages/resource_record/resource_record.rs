@@ -11,7 +11,7 @@ use crate::{
 
 use super::rr_data::RRData;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResourceRecord {
     name: DomainName,
     record_type: RRType,
@@ -22,13 +22,39 @@ pub struct ResourceRecord {
 }
 
 impl ResourceRecord {
+    pub fn new(name: DomainName, class: Class, ttl: TTL, rdata: RRData) -> Self {
+        let mut writer = Writer::new();
+        rdata.serialize(&mut writer);
+
+        Self {
+            name,
+            record_type: rdata.rr_type(),
+            class,
+            ttl,
+            rd_length: writer.len() as u16,
+            rdata,
+        }
+    }
+
     pub fn parse(reader: &mut Reader) -> ParseResult<ResourceRecord> {
         let name = DomainName::parse(reader)?;
         let record_type = RRType::parse(reader)?;
-        let class = Class::parse(reader)?;
+        let class = match record_type {
+            // The class of an OPT record holds the UDP payload size
+            RRType::OPT => Class::from(reader.read_u16()?),
+            _ => Class::parse(reader)?,
+        };
         let ttl = TTL::parse(reader)?;
         let rd_length = reader.read_u16()?;
-        let rdata = RRData::parse(reader, &record_type, rd_length)?;
+        let rdata = match class {
+            // Dynamic updates use empty data with these classes to match any record of the type
+            // (RFC 2136 section 2.4), no other record may leave out the data of its type
+            Class::Any | Class::None if rd_length == 0 => RRData::Unknown {
+                rr_type: (&record_type).into(),
+                data: vec![],
+            },
+            _ => RRData::parse(reader, &record_type, rd_length)?,
+        };
 
         Ok(Self {
             name,
@@ -46,11 +72,33 @@ impl ResourceRecord {
         self.class.serialize(writer);
         self.ttl.serialize(writer);
 
-        let mut inner_writer = Writer::new();
-        self.rdata.serialize(&mut inner_writer);
+        // The data is written straight into the message so that names within it can be compressed,
+        // the length is filled in once we know it.
+        let length_index = writer.len();
+        writer.write_u16(0);
+        self.rdata.serialize(writer);
+        let rd_length = writer.len() - length_index - 2;
+        writer.set_u16(length_index, rd_length as u16);
+    }
+
+    pub fn name(&self) -> &DomainName {
+        &self.name
+    }
+
+    pub fn record_type(&self) -> &RRType {
+        &self.record_type
+    }
+
+    pub fn class(&self) -> &Class {
+        &self.class
+    }
+
+    pub fn ttl(&self) -> &TTL {
+        &self.ttl
+    }
 
-        writer.write_u16(inner_writer.len() as u16);
-        writer.merge(&mut inner_writer);
+    pub fn rdata(&self) -> &RRData {
+        &self.rdata
     }
 
     pub fn get_query_name_type(&self) -> (DomainName, RRType) {
@@ -1,33 +1,216 @@
 use std::fmt::Display;
 
+use data_encoding::{HEXUPPER, HEXUPPER_PERMISSIVE};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    common::{domain_name::DomainName, parse_error::ParseResult, rr_type::RRType},
+    common::{
+        domain_name::DomainName,
+        parse_error::{ParseError, ParseResult},
+        presentation::{decode_field, parse_field, split_tokens},
+        rr_type::RRType,
+    },
     messages::{parsing::Reader, serializing::Writer},
 };
 
-use super::{a::A, aaaa::AAAA, soa::SOA};
+use super::{
+    a::A,
+    aaaa::AAAA,
+    dnskey::DNSKEY,
+    ds::DS,
+    mx::MX,
+    naptr::NAPTR,
+    nsec::NSEC,
+    nsec3::{NSEC3, NSEC3PARAM},
+    rrsig::RRSIG,
+    soa::SOA,
+    srv::SRV,
+    svcb::SVCB,
+    tsig::TSIG,
+    txt::TXT,
+};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum RRData {
     CNAME(DomainName),
     A(A),
     AAAA(AAAA),
     SOA(SOA),
-    TXT(String),
+    TXT(TXT),
+    MX(MX),
+    NS(DomainName),
+    PTR(DomainName),
+    SRV(SRV),
+    NAPTR(NAPTR),
+    SVCB(SVCB),
+    HTTPS(SVCB),
+    DNSKEY(DNSKEY),
+    CDNSKEY(DNSKEY),
+    DS(DS),
+    CDS(DS),
+    RRSIG(RRSIG),
+    NSEC(NSEC),
+    NSEC3(NSEC3),
+    NSEC3PARAM(NSEC3PARAM),
+    TSIG(TSIG),
+    /// Data for a record type that we do not (yet) model, kept as the raw rdata (RFC 3597).
+    Unknown {
+        rr_type: u16,
+        data: Vec<u8>,
+    },
 }
 
 impl RRData {
     pub fn parse(reader: &mut Reader, rr_type: &RRType, length: u16) -> ParseResult<RRData> {
-        Ok(match rr_type {
+        let start = reader.get_index();
+        let data = match rr_type {
             RRType::CNAME => RRData::CNAME(DomainName::parse(reader)?),
             RRType::A => RRData::A(A::parse(reader)?),
             RRType::AAAA => RRData::AAAA(AAAA::parse(reader)?),
             RRType::SOA => RRData::SOA(SOA::parse(reader)?),
-            RRType::TXT => RRData::TXT(reader.read_string(length as usize)?),
-            t => todo!("Data for RRType {t} is not yet implemented!"),
-        })
+            RRType::TXT => RRData::TXT(TXT::parse(reader, length)?),
+            RRType::MX => RRData::MX(MX::parse(reader)?),
+            RRType::NS => RRData::NS(DomainName::parse(reader)?),
+            RRType::PTR => RRData::PTR(DomainName::parse(reader)?),
+            RRType::SRV => RRData::SRV(SRV::parse(reader)?),
+            RRType::NAPTR => RRData::NAPTR(NAPTR::parse(reader)?),
+            RRType::SVCB => RRData::SVCB(SVCB::parse(reader, length)?),
+            RRType::HTTPS => RRData::HTTPS(SVCB::parse(reader, length)?),
+            RRType::DNSKEY => RRData::DNSKEY(DNSKEY::parse(reader, length)?),
+            RRType::CDNSKEY => RRData::CDNSKEY(DNSKEY::parse(reader, length)?),
+            RRType::DS => RRData::DS(DS::parse(reader, length)?),
+            RRType::CDS => RRData::CDS(DS::parse(reader, length)?),
+            RRType::RRSIG => RRData::RRSIG(RRSIG::parse(reader, length)?),
+            RRType::NSEC => RRData::NSEC(NSEC::parse(reader, length)?),
+            RRType::NSEC3 => RRData::NSEC3(NSEC3::parse(reader, length)?),
+            RRType::NSEC3PARAM => RRData::NSEC3PARAM(NSEC3PARAM::parse(reader)?),
+            RRType::TSIG => RRData::TSIG(TSIG::parse(reader)?),
+            t => RRData::Unknown {
+                rr_type: t.into(),
+                data: reader.read_exact_vec(length as usize)?,
+            },
+        };
+
+        let read = reader.get_index() - start;
+        if read != length as usize {
+            return Err(ParseError::RRError(format!(
+                "Data for RRType {rr_type} was {read} bytes but rd_length was {length}"
+            )));
+        }
+
+        Ok(data)
+    }
+
+    /// Parses the data of a record of the given type from the presentation format, either the format of
+    /// the type or the generic `\# <length> <hex data>` form that works for any type (RFC 3597 section 5).
+    /// Names in the data must be absolute.
+    pub fn from_presentation(rr_type: &RRType, text: &str) -> ParseResult<RRData> {
+        let tokens = split_tokens(text)?;
+        if tokens.first().map(|t| t.as_str()) == Some("\\#") {
+            return Self::from_generic(rr_type, &tokens[1..]);
+        }
+
+        let data = match rr_type {
+            RRType::CNAME => RRData::CNAME(text.trim().parse()?),
+            RRType::A => RRData::A(text.parse()?),
+            RRType::AAAA => RRData::AAAA(text.parse()?),
+            RRType::SOA => RRData::SOA(text.parse()?),
+            RRType::TXT => RRData::TXT(text.parse()?),
+            RRType::MX => RRData::MX(text.parse()?),
+            RRType::NS => RRData::NS(text.trim().parse()?),
+            RRType::PTR => RRData::PTR(text.trim().parse()?),
+            RRType::SRV => RRData::SRV(text.parse()?),
+            RRType::NAPTR => RRData::NAPTR(text.parse()?),
+            RRType::SVCB => RRData::SVCB(text.parse()?),
+            RRType::HTTPS => RRData::HTTPS(text.parse()?),
+            RRType::DNSKEY => RRData::DNSKEY(text.parse()?),
+            RRType::CDNSKEY => RRData::CDNSKEY(text.parse()?),
+            RRType::DS => RRData::DS(text.parse()?),
+            RRType::CDS => RRData::CDS(text.parse()?),
+            RRType::RRSIG => RRData::RRSIG(text.parse()?),
+            RRType::NSEC => RRData::NSEC(text.parse()?),
+            RRType::NSEC3 => RRData::NSEC3(text.parse()?),
+            RRType::NSEC3PARAM => RRData::NSEC3PARAM(text.parse()?),
+            t if t.is_meta() => {
+                return Err(ParseError::RRError(format!(
+                    "RRType {t} can't be given in the presentation format"
+                )))
+            }
+            t => {
+                return Err(ParseError::RRError(format!(
+                    "RRType {t} is only supported in the generic '\\# <length> <data>' form"
+                )))
+            }
+        };
+
+        if let RRData::CNAME(name) | RRData::NS(name) | RRData::PTR(name) = &data {
+            if text.split_whitespace().count() != 1 {
+                return Err(ParseError::RRError(format!(
+                    "{rr_type} data '{text}' should be a single name"
+                )));
+            }
+            if !name.is_absolute() {
+                return Err(ParseError::RRError(format!(
+                    "Name '{name}' in {rr_type} data must be absolute"
+                )));
+            }
+        }
+
+        Ok(data)
+    }
+
+    fn from_generic(rr_type: &RRType, tokens: &[String]) -> ParseResult<RRData> {
+        if rr_type.is_meta() {
+            return Err(ParseError::RRError(format!(
+                "RRType {rr_type} can't be given in the presentation format"
+            )));
+        }
+
+        let (length, data) = match tokens {
+            [length, data @ ..] => (parse_field::<u16>(length, "generic data length")?, data),
+            [] => {
+                return Err(ParseError::RRError(
+                    "Generic data is missing its length".to_string(),
+                ))
+            }
+        };
+        let data = decode_field(&HEXUPPER_PERMISSIVE, data, "generic data")?;
+        if data.len() != length as usize {
+            return Err(ParseError::RRError(format!(
+                "Generic data was {} bytes but its length was given as {length}",
+                data.len()
+            )));
+        }
+
+        let mut reader = Reader::new(&data);
+        RRData::parse(&mut reader, rr_type, length)
+    }
+
+    pub fn rr_type(&self) -> RRType {
+        match self {
+            RRData::CNAME(_) => RRType::CNAME,
+            RRData::A(_) => RRType::A,
+            RRData::AAAA(_) => RRType::AAAA,
+            RRData::SOA(_) => RRType::SOA,
+            RRData::TXT(_) => RRType::TXT,
+            RRData::MX(_) => RRType::MX,
+            RRData::NS(_) => RRType::NS,
+            RRData::PTR(_) => RRType::PTR,
+            RRData::SRV(_) => RRType::SRV,
+            RRData::NAPTR(_) => RRType::NAPTR,
+            RRData::SVCB(_) => RRType::SVCB,
+            RRData::HTTPS(_) => RRType::HTTPS,
+            RRData::DNSKEY(_) => RRType::DNSKEY,
+            RRData::CDNSKEY(_) => RRType::CDNSKEY,
+            RRData::DS(_) => RRType::DS,
+            RRData::CDS(_) => RRType::CDS,
+            RRData::RRSIG(_) => RRType::RRSIG,
+            RRData::NSEC(_) => RRType::NSEC,
+            RRData::NSEC3(_) => RRType::NSEC3,
+            RRData::NSEC3PARAM(_) => RRType::NSEC3PARAM,
+            RRData::TSIG(_) => RRType::TSIG,
+            RRData::Unknown { rr_type, data: _ } => RRType::from(*rr_type),
+        }
     }
 
     pub fn serialize(&self, writer: &mut Writer) {
@@ -36,7 +219,21 @@ impl RRData {
             RRData::A(a) => a.serialize(writer),
             RRData::AAAA(aaaa) => aaaa.serialize(writer),
             RRData::SOA(soa) => soa.serialize(writer),
-            RRData::TXT(txt) => txt.as_bytes().iter().for_each(|b| writer.write_u8(*b)),
+            RRData::TXT(txt) => txt.serialize(writer),
+            RRData::MX(mx) => mx.serialize(writer),
+            RRData::NS(name) => name.serialize(writer),
+            RRData::PTR(name) => name.serialize(writer),
+            RRData::SRV(srv) => srv.serialize(writer),
+            RRData::NAPTR(naptr) => naptr.serialize(writer),
+            RRData::SVCB(svcb) | RRData::HTTPS(svcb) => svcb.serialize(writer),
+            RRData::DNSKEY(key) | RRData::CDNSKEY(key) => key.serialize(writer),
+            RRData::DS(ds) | RRData::CDS(ds) => ds.serialize(writer),
+            RRData::RRSIG(sig) => sig.serialize(writer),
+            RRData::NSEC(nsec) => nsec.serialize(writer),
+            RRData::NSEC3(nsec3) => nsec3.serialize(writer),
+            RRData::NSEC3PARAM(param) => param.serialize(writer),
+            RRData::TSIG(tsig) => tsig.serialize(writer),
+            RRData::Unknown { rr_type: _, data } => data.iter().for_each(|b| writer.write_u8(*b)),
         }
     }
 }
@@ -51,7 +248,26 @@ impl Display for RRData {
                 RRData::A(val) => format!("A(Address = {val})"),
                 RRData::AAAA(val) => format!("AAAA(Address = {val})"),
                 RRData::SOA(val) => format!("SOA({val})"),
-                RRData::TXT(val) => format!("TXT('{val}')"),
+                RRData::TXT(val) => format!("TXT({val})"),
+                RRData::MX(val) => format!("MX({val})"),
+                RRData::NS(name) => format!("NS( {name} )"),
+                RRData::PTR(name) => format!("PTR( {name} )"),
+                RRData::SRV(val) => format!("SRV({val})"),
+                RRData::NAPTR(val) => format!("NAPTR({val})"),
+                RRData::SVCB(val) => format!("SVCB({val})"),
+                RRData::HTTPS(val) => format!("HTTPS({val})"),
+                RRData::DNSKEY(val) => format!("DNSKEY({val}) ; key tag {}", val.key_tag()),
+                RRData::CDNSKEY(val) => format!("CDNSKEY({val}) ; key tag {}", val.key_tag()),
+                RRData::DS(val) => format!("DS({val})"),
+                RRData::CDS(val) => format!("CDS({val})"),
+                RRData::RRSIG(val) => format!("RRSIG({val})"),
+                RRData::NSEC(val) => format!("NSEC({val})"),
+                RRData::NSEC3(val) => format!("NSEC3({val})"),
+                RRData::NSEC3PARAM(val) => format!("NSEC3PARAM({val})"),
+                RRData::TSIG(val) => format!("TSIG({val})"),
+                RRData::Unknown { rr_type: _, data } if data.is_empty() => "\\# 0".to_string(),
+                RRData::Unknown { rr_type: _, data } =>
+                    format!("\\# {} {}", data.len(), HEXUPPER.encode(data)),
             }
         )
     }
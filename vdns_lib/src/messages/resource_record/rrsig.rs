@@ -0,0 +1,179 @@
+use std::{fmt::Display, str::FromStr};
+
+use data_encoding::BASE64;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{
+        domain_name::DomainName,
+        parse_error::{ParseError, ParseResult},
+        presentation::{decode_field, parse_field, split_tokens},
+        rr_type::RRType,
+    },
+    messages::{parsing::Reader, serializing::Writer},
+};
+
+/// Resource record signature (RFC 4034 section 3)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RRSIG {
+    type_covered: RRType,
+    algorithm: u8,
+    labels: u8,
+    original_ttl: u32,
+    signature_expiration: u32, // Seconds since the epoch, modulo 2^32
+    signature_inception: u32,  // Seconds since the epoch, modulo 2^32
+    key_tag: u16,
+    signer_name: DomainName,
+    signature: Vec<u8>,
+}
+
+impl RRSIG {
+    pub fn parse(reader: &mut Reader, length: u16) -> ParseResult<Self> {
+        let end = reader.get_index() + length as usize;
+        Ok(RRSIG {
+            type_covered: RRType::parse(reader)?,
+            algorithm: reader.read_u8()?,
+            labels: reader.read_u8()?,
+            original_ttl: reader.read_u32()?,
+            signature_expiration: reader.read_u32()?,
+            signature_inception: reader.read_u32()?,
+            key_tag: reader.read_u16()?,
+            signer_name: DomainName::parse(reader)?,
+            signature: reader.read_exact_vec(end.saturating_sub(reader.get_index()))?,
+        })
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        self.type_covered.serialize(writer);
+        writer.write_u8(self.algorithm);
+        writer.write_u8(self.labels);
+        writer.write_u32(self.original_ttl);
+        writer.write_u32(self.signature_expiration);
+        writer.write_u32(self.signature_inception);
+        writer.write_u16(self.key_tag);
+        // The signer name must not be compressed
+        self.signer_name.serialize_uncompressed(writer);
+        self.signature.iter().for_each(|b| writer.write_u8(*b));
+    }
+
+    pub fn type_covered(&self) -> &RRType {
+        &self.type_covered
+    }
+
+    pub fn key_tag(&self) -> u16 {
+        self.key_tag
+    }
+
+    pub fn signer_name(&self) -> &DomainName {
+        &self.signer_name
+    }
+}
+
+impl Display for RRSIG {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {} {} {} {} {}",
+            self.type_covered,
+            self.algorithm,
+            self.labels,
+            self.original_ttl,
+            format_timestamp(self.signature_expiration),
+            format_timestamp(self.signature_inception),
+            self.key_tag,
+            self.signer_name,
+            BASE64.encode(&self.signature)
+        )
+    }
+}
+
+/// Parses the presentation format, e.g.
+/// `A 13 3 300 20240101000000 20231201000000 12345 example.com. <base64 signature>`
+impl FromStr for RRSIG {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match split_tokens(s)?.as_slice() {
+            [type_covered, algorithm, labels, original_ttl, expiration, inception, key_tag, signer_name, signature @ ..]
+                if !signature.is_empty() =>
+            {
+                Ok(RRSIG {
+                    type_covered: type_covered.parse()?,
+                    algorithm: parse_field(algorithm, "RRSIG algorithm")?,
+                    labels: parse_field(labels, "RRSIG labels")?,
+                    original_ttl: parse_field(original_ttl, "RRSIG original TTL")?,
+                    signature_expiration: parse_timestamp(expiration)?,
+                    signature_inception: parse_timestamp(inception)?,
+                    key_tag: parse_field(key_tag, "RRSIG key tag")?,
+                    signer_name: signer_name.parse()?,
+                    signature: decode_field(&BASE64, signature, "RRSIG signature")?,
+                })
+            }
+            _ => Err(ParseError::RRError(format!(
+                "RRSIG data '{s}' is missing fields"
+            ))),
+        }
+    }
+}
+
+/// Formats seconds since the epoch as YYYYMMDDHHmmSS (UTC), as used in the presentation format (RFC 4034 section 3.2)
+pub fn format_timestamp(timestamp: u32) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds_of_day = timestamp % 86400;
+
+    // Converts days since the epoch to a date in the proleptic Gregorian calendar
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{year:04}{month:02}{day:02}{:02}{:02}{:02}",
+        seconds_of_day / 3600,
+        (seconds_of_day / 60) % 60,
+        seconds_of_day % 60
+    )
+}
+
+/// Parses a timestamp as YYYYMMDDHHmmSS (UTC) or as seconds since the epoch (RFC 4034 section 3.2)
+pub fn parse_timestamp(text: &str) -> ParseResult<u32> {
+    let invalid = || ParseError::RRError(format!("Invalid timestamp '{text}'"));
+    if text.len() != 14 {
+        return text.parse().map_err(|_| invalid());
+    }
+
+    let field = |range: std::ops::Range<usize>| -> ParseResult<i64> {
+        text.get(range)
+            .filter(|f| f.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|f| f.parse().ok())
+            .ok_or_else(invalid)
+    };
+    let (year, month, day) = (field(0..4)?, field(4..6)?, field(6..8)?);
+    let (hour, minute, second) = (field(8..10)?, field(10..12)?, field(12..14)?);
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return Err(invalid());
+    }
+
+    // Converts the date in the proleptic Gregorian calendar to days since the epoch
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    // Timestamps wrap around every 2^32 seconds
+    let seconds = days * 86400 + hour * 3600 + minute * 60 + second;
+    Ok(seconds.rem_euclid(1 << 32) as u32)
+}
@@ -1,13 +1,18 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    common::{domain_name::DomainName, parse_error::ParseResult},
+    common::{
+        domain_name::DomainName,
+        parse_error::{ParseError, ParseResult},
+        presentation::{parse_field, split_tokens},
+        ttl::TTL,
+    },
     messages::{parsing::Reader, serializing::Writer},
 };
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SOA {
     m_name: DomainName,
     r_name: DomainName,
@@ -19,6 +24,26 @@ pub struct SOA {
 }
 
 impl SOA {
+    pub fn new(
+        m_name: DomainName,
+        r_name: DomainName,
+        serial: u32,
+        refresh: u32,
+        retry: u32,
+        expire: u32,
+        minimum: u32,
+    ) -> Self {
+        Self {
+            m_name,
+            r_name,
+            serial,
+            refresh,
+            retry,
+            expire,
+            minimum,
+        }
+    }
+
     pub fn parse(reader: &mut Reader) -> ParseResult<Self> {
         Ok(SOA {
             m_name: DomainName::parse(reader)?,
@@ -40,6 +65,19 @@ impl SOA {
         writer.write_u32(self.expire);
         writer.write_u32(self.minimum);
     }
+
+    pub fn serial(&self) -> u32 {
+        self.serial
+    }
+
+    pub fn set_serial(&mut self, serial: u32) {
+        self.serial = serial;
+    }
+
+    /// The TTL to use for negative answers (RFC 2308)
+    pub fn minimum(&self) -> u32 {
+        self.minimum
+    }
 }
 
 impl Display for SOA {
@@ -65,3 +103,37 @@ impl Display for SOA {
         )
     }
 }
+
+/// Parses the presentation format, e.g. `ns.example.com. hostmaster.example.com. 1 3600 600 86400 300`.
+/// The timers may use units as in `1h`.
+impl FromStr for SOA {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = split_tokens(s)?;
+        let [m_name, r_name, serial, timers @ ..] = tokens.as_slice() else {
+            return Err(ParseError::RRError(format!(
+                "SOA data '{s}' is missing the names or serial"
+            )));
+        };
+        let [refresh, retry, expire, minimum] = timers
+            .iter()
+            .map(|t| t.parse::<TTL>().map(|ttl| u32::from(&ttl)))
+            .collect::<ParseResult<Vec<u32>>>()?[..]
+        else {
+            return Err(ParseError::RRError(format!(
+                "SOA data '{s}' should have four timers after the serial"
+            )));
+        };
+
+        Ok(SOA::new(
+            m_name.parse()?,
+            r_name.parse()?,
+            parse_field(serial, "SOA serial")?,
+            refresh,
+            retry,
+            expire,
+            minimum,
+        ))
+    }
+}
@@ -0,0 +1,146 @@
+use std::{fmt::Display, str::FromStr};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{
+        domain_name::DomainName,
+        parse_error::{ParseError, ParseResult},
+        presentation::{parse_field, split_tokens},
+    },
+    messages::{parsing::Reader, serializing::Writer},
+};
+
+/// Service location record (RFC 2782)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SRV {
+    priority: u16,
+    weight: u16,
+    port: u16,
+    target: DomainName,
+}
+
+impl SRV {
+    pub fn new(priority: u16, weight: u16, port: u16, target: DomainName) -> Self {
+        Self {
+            priority,
+            weight,
+            port,
+            target,
+        }
+    }
+
+    pub fn parse(reader: &mut Reader) -> ParseResult<Self> {
+        Ok(SRV {
+            priority: reader.read_u16()?,
+            weight: reader.read_u16()?,
+            port: reader.read_u16()?,
+            target: DomainName::parse(reader)?,
+        })
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        writer.write_u16(self.priority);
+        writer.write_u16(self.weight);
+        writer.write_u16(self.port);
+        // The target must not be compressed
+        self.target.serialize_uncompressed(writer);
+    }
+
+    pub fn priority(&self) -> u16 {
+        self.priority
+    }
+
+    pub fn weight(&self) -> u16 {
+        self.weight
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn target(&self) -> &DomainName {
+        &self.target
+    }
+}
+
+impl Display for SRV {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.priority, self.weight, self.port, self.target
+        )
+    }
+}
+
+/// Parses the presentation format, e.g. `10 60 5060 sip.example.com.`
+impl FromStr for SRV {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match split_tokens(s)?.as_slice() {
+            [priority, weight, port, target] => Ok(SRV::new(
+                parse_field(priority, "SRV priority")?,
+                parse_field(weight, "SRV weight")?,
+                parse_field(port, "SRV port")?,
+                target.parse()?,
+            )),
+            _ => Err(ParseError::RRError(format!(
+                "SRV data '{s}' should be a priority, weight, port and target"
+            ))),
+        }
+    }
+}
+
+/// Orders the records of an SRV RRset in the order that the targets should be contacted.
+/// See `order_targets_with`.
+pub fn order_targets(records: &[SRV]) -> Vec<SRV> {
+    order_targets_with(records, &mut rand::thread_rng())
+}
+
+/// Orders the records of an SRV RRset in the order that the targets should be contacted,
+/// lowest priority first and within each priority by the weighted random selection in RFC 2782.
+/// A lone record with the target `.` means that the service is not available, in which case nothing is returned.
+pub fn order_targets_with<R: Rng + ?Sized>(records: &[SRV], rng: &mut R) -> Vec<SRV> {
+    if let [record] = records {
+        if record.target.is_root() {
+            return vec![];
+        }
+    }
+
+    let mut remaining = records.to_vec();
+    remaining.sort_by_key(|r| r.priority);
+
+    let mut ordered = Vec::with_capacity(remaining.len());
+    while let Some(first) = remaining.first() {
+        let priority = first.priority;
+        let split = remaining
+            .iter()
+            .position(|r| r.priority != priority)
+            .unwrap_or(remaining.len());
+        let mut group: Vec<SRV> = remaining.drain(..split).collect();
+
+        // Records with weight 0 go first so that they have a very small chance of being selected
+        group.sort_by_key(|r| r.weight != 0);
+
+        while !group.is_empty() {
+            let total: u32 = group.iter().map(|r| r.weight as u32).sum();
+            let selected = rng.gen_range(0..=total);
+
+            let mut running_sum = 0;
+            let index = group
+                .iter()
+                .position(|r| {
+                    running_sum += r.weight as u32;
+                    running_sum >= selected
+                })
+                .unwrap_or(group.len() - 1);
+
+            ordered.push(group.remove(index));
+        }
+    }
+
+    ordered
+}
@@ -0,0 +1,505 @@
+use std::{
+    fmt::Display,
+    net::{Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+use data_encoding::BASE64;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{
+        domain_name::DomainName,
+        parse_error::{ParseError, ParseResult},
+        presentation::{escape, split_tokens, unescape},
+    },
+    messages::{parsing::Reader, serializing::Writer},
+};
+
+/// Service binding record (RFC 9460), also used for HTTPS records
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SVCB {
+    priority: u16, // 0 means AliasMode, anything else ServiceMode
+    target: DomainName,
+    params: Vec<SvcParam>,
+}
+
+impl SVCB {
+    /// The params are sorted by key as required on the wire.
+    /// Fails if a key is repeated or if a key listed as mandatory is missing (RFC 9460 section 8).
+    pub fn new(priority: u16, target: DomainName, mut params: Vec<SvcParam>) -> ParseResult<Self> {
+        params.sort_by_key(|p| u16::from(p.key()));
+        if let Some(pair) = params
+            .windows(2)
+            .find(|pair| u16::from(pair[0].key()) == u16::from(pair[1].key()))
+        {
+            return Err(ParseError::RRError(format!(
+                "SvcParamKey {} appears more than once",
+                pair[0].key()
+            )));
+        }
+        for param in params.iter_mut() {
+            if let SvcParam::Mandatory(keys) = param {
+                keys.sort_by_key(|k| u16::from(*k));
+            }
+        }
+        check_mandatory(&params)?;
+
+        Ok(Self {
+            priority,
+            target,
+            params,
+        })
+    }
+
+    pub fn parse(reader: &mut Reader, length: u16) -> ParseResult<Self> {
+        let end = reader.get_index() + length as usize;
+        let priority = reader.read_u16()?;
+        let target = DomainName::parse(reader)?;
+
+        let mut params: Vec<SvcParam> = vec![];
+        while reader.get_index() < end {
+            let param = SvcParam::parse(reader)?;
+            if let Some(last) = params.last() {
+                if u16::from(last.key()) >= u16::from(param.key()) {
+                    return Err(ParseError::RRError(format!(
+                        "SvcParamKeys must be in strictly increasing order, got {} after {}",
+                        param.key(),
+                        last.key()
+                    )));
+                }
+            }
+            params.push(param);
+        }
+        check_mandatory(&params)?;
+
+        Ok(SVCB {
+            priority,
+            target,
+            params,
+        })
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        writer.write_u16(self.priority);
+        // The target must not be compressed
+        self.target.serialize_uncompressed(writer);
+        for param in self.params.iter() {
+            param.serialize(writer);
+        }
+    }
+
+    pub fn is_alias(&self) -> bool {
+        self.priority == 0
+    }
+
+    pub fn priority(&self) -> u16 {
+        self.priority
+    }
+
+    pub fn target(&self) -> &DomainName {
+        &self.target
+    }
+
+    pub fn params(&self) -> &[SvcParam] {
+        &self.params
+    }
+}
+
+/// Presentation format, e.g. `1 . alpn=h2,h3 port=443`
+impl Display for SVCB {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.priority, self.target)?;
+        for param in self.params.iter() {
+            write!(f, " {param}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the presentation format of the record data, e.g. `1 . alpn=h2,h3 port=443`
+impl FromStr for SVCB {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = split_tokens(s)?;
+        let (priority, target, params) = match tokens.as_slice() {
+            [priority, target, params @ ..] => (priority, target, params),
+            _ => {
+                return Err(ParseError::RRError(format!(
+                    "SVCB data '{s}' is missing the priority or target"
+                )))
+            }
+        };
+
+        let priority = priority
+            .parse::<u16>()
+            .map_err(|_| ParseError::RRError(format!("Invalid SvcPriority '{priority}'")))?;
+        let target = target.parse::<DomainName>()?;
+        let params = params
+            .iter()
+            .map(|p| p.parse())
+            .collect::<ParseResult<Vec<SvcParam>>>()?;
+
+        SVCB::new(priority, target, params)
+    }
+}
+
+/// The mandatory keys must not include `mandatory` itself or repeat a key, and must all be present.
+/// The params are expected to be sorted by key.
+fn check_mandatory(params: &[SvcParam]) -> ParseResult<()> {
+    let Some(SvcParam::Mandatory(keys)) = params.first() else {
+        return Ok(());
+    };
+
+    let numbers = keys.iter().map(|k| u16::from(*k)).collect::<Vec<u16>>();
+    for (index, (key, number)) in keys.iter().zip(numbers.iter()).enumerate() {
+        if *number == u16::from(SvcParamKey::Mandatory) {
+            return Err(ParseError::RRError(
+                "The mandatory SvcParam must not list itself".to_string(),
+            ));
+        }
+        if numbers[..index].contains(number) {
+            return Err(ParseError::RRError(format!(
+                "SvcParamKey {key} is listed as mandatory more than once"
+            )));
+        }
+        if !params.iter().any(|p| u16::from(p.key()) == *number) {
+            return Err(ParseError::RRError(format!(
+                "SvcParamKey {key} is mandatory but missing"
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SvcParamKey {
+    Mandatory,
+    Alpn,
+    NoDefaultAlpn,
+    Port,
+    Ipv4Hint,
+    Ech,
+    Ipv6Hint,
+    Key(u16), // Any other key, presented as `keyNNNNN`
+}
+
+impl From<u16> for SvcParamKey {
+    fn from(value: u16) -> Self {
+        match value {
+            0 => SvcParamKey::Mandatory,
+            1 => SvcParamKey::Alpn,
+            2 => SvcParamKey::NoDefaultAlpn,
+            3 => SvcParamKey::Port,
+            4 => SvcParamKey::Ipv4Hint,
+            5 => SvcParamKey::Ech,
+            6 => SvcParamKey::Ipv6Hint,
+            val => SvcParamKey::Key(val),
+        }
+    }
+}
+
+impl From<SvcParamKey> for u16 {
+    fn from(value: SvcParamKey) -> Self {
+        match value {
+            SvcParamKey::Mandatory => 0,
+            SvcParamKey::Alpn => 1,
+            SvcParamKey::NoDefaultAlpn => 2,
+            SvcParamKey::Port => 3,
+            SvcParamKey::Ipv4Hint => 4,
+            SvcParamKey::Ech => 5,
+            SvcParamKey::Ipv6Hint => 6,
+            SvcParamKey::Key(val) => val,
+        }
+    }
+}
+
+impl Display for SvcParamKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SvcParamKey::Mandatory => write!(f, "mandatory"),
+            SvcParamKey::Alpn => write!(f, "alpn"),
+            SvcParamKey::NoDefaultAlpn => write!(f, "no-default-alpn"),
+            SvcParamKey::Port => write!(f, "port"),
+            SvcParamKey::Ipv4Hint => write!(f, "ipv4hint"),
+            SvcParamKey::Ech => write!(f, "ech"),
+            SvcParamKey::Ipv6Hint => write!(f, "ipv6hint"),
+            SvcParamKey::Key(val) => write!(f, "key{val}"),
+        }
+    }
+}
+
+impl FromStr for SvcParamKey {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "mandatory" => SvcParamKey::Mandatory,
+            "alpn" => SvcParamKey::Alpn,
+            "no-default-alpn" => SvcParamKey::NoDefaultAlpn,
+            "port" => SvcParamKey::Port,
+            "ipv4hint" => SvcParamKey::Ipv4Hint,
+            "ech" => SvcParamKey::Ech,
+            "ipv6hint" => SvcParamKey::Ipv6Hint,
+            other => {
+                let val = other
+                    .strip_prefix("key")
+                    .filter(|n| !n.is_empty() && (n.len() == 1 || !n.starts_with('0')))
+                    .and_then(|n| n.parse::<u16>().ok())
+                    .ok_or(ParseError::RRError(format!(
+                        "Unknown SvcParamKey '{other}'"
+                    )))?;
+                SvcParamKey::from(val)
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SvcParam {
+    Mandatory(Vec<SvcParamKey>),
+    Alpn(Vec<Vec<u8>>),
+    NoDefaultAlpn,
+    Port(u16),
+    Ipv4Hint(Vec<Ipv4Addr>),
+    Ech(Vec<u8>), // An ECHConfigList
+    Ipv6Hint(Vec<Ipv6Addr>),
+    Unknown { key: u16, value: Vec<u8> },
+}
+
+impl SvcParam {
+    pub fn parse(reader: &mut Reader) -> ParseResult<Self> {
+        let key = SvcParamKey::from(reader.read_u16()?);
+        let length = reader.read_u16()? as usize;
+        let value = reader.read_exact_vec(length)?;
+
+        let value_reader = &mut Reader::new(&value);
+        let param = match key {
+            SvcParamKey::Mandatory => SvcParam::Mandatory(read_list(value_reader, length, |r| {
+                Ok(SvcParamKey::from(r.read_u16()?))
+            })?),
+            SvcParamKey::Alpn => SvcParam::Alpn(read_list(value_reader, length, |r| {
+                let len = r.read_u8()?;
+                Ok(r.read_exact_vec(len as usize)?)
+            })?),
+            SvcParamKey::NoDefaultAlpn if length == 0 => SvcParam::NoDefaultAlpn,
+            SvcParamKey::Port if length == 2 => SvcParam::Port(value_reader.read_u16()?),
+            SvcParamKey::Ipv4Hint => SvcParam::Ipv4Hint(read_list(value_reader, length, |r| {
+                Ok(Ipv4Addr::from(r.read_u32()?))
+            })?),
+            SvcParamKey::Ech => SvcParam::Ech(value.clone()),
+            SvcParamKey::Ipv6Hint => SvcParam::Ipv6Hint(read_list(value_reader, length, |r| {
+                Ok(Ipv6Addr::from(r.read_u128()?))
+            })?),
+            SvcParamKey::Key(key) => SvcParam::Unknown {
+                key,
+                value: value.clone(),
+            },
+            key => {
+                return Err(ParseError::RRError(format!(
+                    "Invalid length {length} for SvcParam {key}"
+                )))
+            }
+        };
+
+        Ok(param)
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        writer.write_u16(self.key().into());
+
+        let length_index = writer.len();
+        writer.write_u16(0);
+        match self {
+            SvcParam::Mandatory(keys) => keys.iter().for_each(|k| writer.write_u16((*k).into())),
+            SvcParam::Alpn(ids) => ids.iter().for_each(|id| {
+                writer.write_u8(id.len() as u8);
+                id.iter().for_each(|b| writer.write_u8(*b));
+            }),
+            SvcParam::NoDefaultAlpn => {}
+            SvcParam::Port(port) => writer.write_u16(*port),
+            SvcParam::Ipv4Hint(addresses) => addresses
+                .iter()
+                .for_each(|a| a.octets().iter().for_each(|b| writer.write_u8(*b))),
+            SvcParam::Ech(value) | SvcParam::Unknown { key: _, value } => {
+                value.iter().for_each(|b| writer.write_u8(*b))
+            }
+            SvcParam::Ipv6Hint(addresses) => addresses
+                .iter()
+                .for_each(|a| a.octets().iter().for_each(|b| writer.write_u8(*b))),
+        }
+        let length = writer.len() - length_index - 2;
+        writer.set_u16(length_index, length as u16);
+    }
+
+    pub fn key(&self) -> SvcParamKey {
+        match self {
+            SvcParam::Mandatory(_) => SvcParamKey::Mandatory,
+            SvcParam::Alpn(_) => SvcParamKey::Alpn,
+            SvcParam::NoDefaultAlpn => SvcParamKey::NoDefaultAlpn,
+            SvcParam::Port(_) => SvcParamKey::Port,
+            SvcParam::Ipv4Hint(_) => SvcParamKey::Ipv4Hint,
+            SvcParam::Ech(_) => SvcParamKey::Ech,
+            SvcParam::Ipv6Hint(_) => SvcParamKey::Ipv6Hint,
+            SvcParam::Unknown { key, value: _ } => SvcParamKey::from(*key),
+        }
+    }
+}
+
+/// Reads values until the param value is exhausted, failing on any trailing bytes
+fn read_list<T>(
+    reader: &mut Reader,
+    length: usize,
+    read: impl Fn(&mut Reader) -> ParseResult<T>,
+) -> ParseResult<Vec<T>> {
+    let mut values = vec![];
+    while reader.get_index() < length {
+        values.push(read(reader)?);
+    }
+    if values.is_empty() {
+        return Err(ParseError::RRError("Empty SvcParam value list".to_string()));
+    }
+    Ok(values)
+}
+
+/// Presentation format, e.g. `alpn=h2,h3`
+impl Display for SvcParam {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let key = self.key();
+        match self {
+            SvcParam::Mandatory(keys) => write!(
+                f,
+                "{key}={}",
+                keys.iter()
+                    .map(|k| k.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+            SvcParam::Alpn(ids) => {
+                // Commas and backslashes within an id are escaped on the list level first (RFC 9460 appendix A.1)
+                let list = ids
+                    .iter()
+                    .map(|id| {
+                        id.iter().fold(vec![], |mut acc, &b| {
+                            if b == b',' || b == b'\\' {
+                                acc.push(b'\\');
+                            }
+                            acc.push(b);
+                            acc
+                        })
+                    })
+                    .collect::<Vec<Vec<u8>>>()
+                    .join(&b',');
+                write!(f, "{key}={}", escape(&list))
+            }
+            SvcParam::NoDefaultAlpn => write!(f, "{key}"),
+            SvcParam::Port(port) => write!(f, "{key}={port}"),
+            SvcParam::Ipv4Hint(addresses) => write!(
+                f,
+                "{key}={}",
+                addresses
+                    .iter()
+                    .map(|a| a.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+            SvcParam::Ech(value) => write!(f, "{key}={}", BASE64.encode(value)),
+            SvcParam::Ipv6Hint(addresses) => write!(
+                f,
+                "{key}={}",
+                addresses
+                    .iter()
+                    .map(|a| a.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+            SvcParam::Unknown { key: _, value } if value.is_empty() => write!(f, "{key}"),
+            SvcParam::Unknown { key: _, value } => write!(f, "{key}={}", escape(value)),
+        }
+    }
+}
+
+/// Parses a single `key=value` token, without surrounding quotes
+impl FromStr for SvcParam {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = match s.split_once('=') {
+            Some((key, value)) => (key.parse::<SvcParamKey>()?, Some(unescape(value)?)),
+            None => (s.parse::<SvcParamKey>()?, None),
+        };
+
+        let invalid =
+            |reason: &str| ParseError::RRError(format!("Invalid SvcParam '{s}', {reason}"));
+        let text_list = |value: &[u8]| {
+            String::from_utf8(value.to_vec())
+                .map(|v| v.split(',').map(|s| s.to_string()).collect::<Vec<String>>())
+                .map_err(|_| invalid("value is not valid text"))
+        };
+
+        Ok(match (key, value) {
+            (SvcParamKey::NoDefaultAlpn, None) => SvcParam::NoDefaultAlpn,
+            (SvcParamKey::Key(key), None) => SvcParam::Unknown { key, value: vec![] },
+            (_, None) => return Err(invalid("missing value")),
+            (SvcParamKey::Mandatory, Some(value)) => SvcParam::Mandatory(
+                text_list(&value)?
+                    .iter()
+                    .map(|k| k.parse())
+                    .collect::<ParseResult<Vec<SvcParamKey>>>()?,
+            ),
+            (SvcParamKey::Alpn, Some(value)) => {
+                let ids = split_value_list(&value);
+                if ids
+                    .iter()
+                    .any(|id| id.is_empty() || id.len() > u8::MAX as usize)
+                {
+                    return Err(invalid("alpn ids must be between 1 and 255 bytes"));
+                }
+                SvcParam::Alpn(ids)
+            }
+            (SvcParamKey::NoDefaultAlpn, Some(_)) => return Err(invalid("expected no value")),
+            (SvcParamKey::Port, Some(value)) => SvcParam::Port(
+                String::from_utf8_lossy(&value)
+                    .parse()
+                    .map_err(|_| invalid("invalid port"))?,
+            ),
+            (SvcParamKey::Ipv4Hint, Some(value)) => SvcParam::Ipv4Hint(
+                text_list(&value)?
+                    .iter()
+                    .map(|a| a.parse().map_err(|_| invalid("invalid IPv4 address")))
+                    .collect::<ParseResult<Vec<Ipv4Addr>>>()?,
+            ),
+            (SvcParamKey::Ech, Some(value)) => SvcParam::Ech(
+                BASE64
+                    .decode(&value)
+                    .map_err(|_| invalid("invalid base64"))?,
+            ),
+            (SvcParamKey::Ipv6Hint, Some(value)) => SvcParam::Ipv6Hint(
+                text_list(&value)?
+                    .iter()
+                    .map(|a| a.parse().map_err(|_| invalid("invalid IPv6 address")))
+                    .collect::<ParseResult<Vec<Ipv6Addr>>>()?,
+            ),
+            (SvcParamKey::Key(key), Some(value)) => SvcParam::Unknown { key, value },
+        })
+    }
+}
+
+/// Splits an unescaped value on commas, where `\,` and `\\` are a literal comma and backslash
+fn split_value_list(value: &[u8]) -> Vec<Vec<u8>> {
+    let mut items = vec![vec![]];
+    let mut bytes = value.iter();
+    while let Some(&b) = bytes.next() {
+        match b {
+            b'\\' => {
+                if let Some(&escaped) = bytes.next() {
+                    items.last_mut().unwrap().push(escaped);
+                }
+            }
+            b',' => items.push(vec![]),
+            b => items.last_mut().unwrap().push(b),
+        }
+    }
+    items
+}
@@ -0,0 +1,133 @@
+use std::fmt::Display;
+
+use data_encoding::BASE64;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{domain_name::DomainName, parse_error::ParseResult},
+    messages::{header::flags::RCode, parsing::Reader, serializing::Writer},
+};
+
+/// Transaction signature (RFC 8945 section 4.2)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TSIG {
+    algorithm: DomainName,
+    time_signed: u64, // 48 bits, seconds since the epoch
+    fudge: u16,
+    mac: Vec<u8>,
+    original_id: u16,
+    error: u16,
+    other_data: Vec<u8>,
+}
+
+impl TSIG {
+    pub fn new(
+        algorithm: DomainName,
+        time_signed: u64,
+        fudge: u16,
+        mac: Vec<u8>,
+        original_id: u16,
+        error: RCode,
+        other_data: Vec<u8>,
+    ) -> Self {
+        Self {
+            algorithm,
+            time_signed,
+            fudge,
+            mac,
+            original_id,
+            error: error.into(),
+            other_data,
+        }
+    }
+
+    pub fn parse(reader: &mut Reader) -> ParseResult<Self> {
+        let algorithm = DomainName::parse(reader)?;
+        let time_signed = ((reader.read_u16()? as u64) << 32) | reader.read_u32()? as u64;
+        let fudge = reader.read_u16()?;
+        let mac_size = reader.read_u16()?;
+        let mac = reader.read_exact_vec(mac_size as usize)?;
+        let original_id = reader.read_u16()?;
+        let error = reader.read_u16()?;
+        let other_len = reader.read_u16()?;
+        let other_data = reader.read_exact_vec(other_len as usize)?;
+
+        Ok(TSIG {
+            algorithm,
+            time_signed,
+            fudge,
+            mac,
+            original_id,
+            error,
+            other_data,
+        })
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        // The algorithm name must not be compressed
+        self.algorithm.serialize_uncompressed(writer);
+        self.serialize_time(writer);
+        writer.write_u16(self.mac.len() as u16);
+        self.mac.iter().for_each(|b| writer.write_u8(*b));
+        writer.write_u16(self.original_id);
+        self.serialize_error_and_other(writer);
+    }
+
+    /// The time signed and fudge, the 'timer' variables of the MAC
+    pub fn serialize_time(&self, writer: &mut Writer) {
+        writer.write_u16((self.time_signed >> 32) as u16);
+        writer.write_u32(self.time_signed as u32);
+        writer.write_u16(self.fudge);
+    }
+
+    /// The error and other data, the last of the variables of the MAC
+    pub fn serialize_error_and_other(&self, writer: &mut Writer) {
+        writer.write_u16(self.error);
+        writer.write_u16(self.other_data.len() as u16);
+        self.other_data.iter().for_each(|b| writer.write_u8(*b));
+    }
+
+    pub fn algorithm(&self) -> &DomainName {
+        &self.algorithm
+    }
+
+    pub fn time_signed(&self) -> u64 {
+        self.time_signed
+    }
+
+    pub fn fudge(&self) -> u16 {
+        self.fudge
+    }
+
+    pub fn mac(&self) -> &[u8] {
+        &self.mac
+    }
+
+    pub fn original_id(&self) -> u16 {
+        self.original_id
+    }
+
+    pub fn error(&self) -> RCode {
+        RCode::from(self.error)
+    }
+
+    pub fn other_data(&self) -> &[u8] {
+        &self.other_data
+    }
+}
+
+impl Display for TSIG {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {} {} {}",
+            self.algorithm,
+            self.time_signed,
+            self.fudge,
+            BASE64.encode(&self.mac),
+            self.original_id,
+            self.error(),
+            BASE64.encode(&self.other_data)
+        )
+    }
+}
@@ -0,0 +1,106 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{
+        character_string::{CharacterString, MAX_CHARACTER_STRING_LENGTH},
+        parse_error::{ParseError, ParseResult},
+        presentation::split_tokens,
+    },
+    messages::{parsing::Reader, serializing::Writer},
+};
+
+/// Text record (RFC 1035 section 3.3.14), one or more character-strings of arbitrary bytes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TXT {
+    strings: Vec<CharacterString>,
+}
+
+impl TXT {
+    pub fn new(strings: Vec<CharacterString>) -> Self {
+        Self { strings }
+    }
+
+    /// Splits the data into as many character-strings as needed
+    pub fn from_bytes(data: &[u8]) -> Self {
+        if data.is_empty() {
+            return Self {
+                strings: vec![CharacterString::default()],
+            };
+        }
+
+        Self {
+            strings: data
+                .chunks(MAX_CHARACTER_STRING_LENGTH)
+                .filter_map(|chunk| CharacterString::new(chunk.to_vec()))
+                .collect(),
+        }
+    }
+
+    pub fn parse(reader: &mut Reader, length: u16) -> ParseResult<Self> {
+        let end = reader.get_index() + length as usize;
+        let mut strings = vec![];
+        while reader.get_index() < end {
+            strings.push(CharacterString::parse(reader)?);
+        }
+
+        Ok(Self { strings })
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        for string in self.strings.iter() {
+            string.serialize(writer);
+        }
+    }
+
+    pub fn strings(&self) -> &[CharacterString] {
+        &self.strings
+    }
+
+    /// The data of all strings joined together, as e.g. SPF and DKIM records are interpreted
+    pub fn data(&self) -> Vec<u8> {
+        self.strings
+            .iter()
+            .flat_map(|s| s.data().to_vec())
+            .collect()
+    }
+}
+
+impl From<&str> for TXT {
+    fn from(value: &str) -> Self {
+        Self::from_bytes(value.as_bytes())
+    }
+}
+
+impl Display for TXT {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.strings
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<String>>()
+                .join(" ")
+        )
+    }
+}
+
+/// Parses the presentation format, one or more character-strings that are quoted if they contain spaces
+impl FromStr for TXT {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let strings = split_tokens(s)?
+            .iter()
+            .map(|t| t.parse())
+            .collect::<ParseResult<Vec<CharacterString>>>()?;
+        if strings.is_empty() {
+            return Err(ParseError::RRError(
+                "TXT data needs at least one character-string".to_string(),
+            ));
+        }
+        Ok(TXT::new(strings))
+    }
+}
@@ -0,0 +1,118 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{
+        parse_error::{ParseError, ParseResult},
+        rr_type::RRType,
+    },
+    messages::{parsing::Reader, serializing::Writer},
+};
+
+/// The type bit maps field of NSEC and NSEC3 records (RFC 4034 section 4.1.2)
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct TypeBitmap {
+    types: Vec<RRType>,
+}
+
+impl TypeBitmap {
+    pub fn new(mut types: Vec<RRType>) -> Self {
+        types.sort_by_key(|t| u16::from(t));
+        types.dedup();
+        Self { types }
+    }
+
+    pub fn parse(reader: &mut Reader, length: usize) -> ParseResult<Self> {
+        let end = reader.get_index() + length;
+        let mut types = vec![];
+        let mut last_window = None;
+
+        while reader.get_index() < end {
+            let window = reader.read_u8()?;
+            if last_window.is_some_and(|last| last >= window) {
+                return Err(ParseError::RRError(format!(
+                    "Type bitmap windows must be in increasing order, got {window} after {}",
+                    last_window.unwrap_or_default()
+                )));
+            }
+            last_window = Some(window);
+
+            let bitmap_length = reader.read_u8()?;
+            if !(1..=32).contains(&bitmap_length) {
+                return Err(ParseError::RRError(format!(
+                    "Invalid type bitmap length {bitmap_length} for window {window}"
+                )));
+            }
+
+            let bitmap = reader.read_exact_vec(bitmap_length as usize)?;
+            for (index, byte) in bitmap.iter().enumerate() {
+                for bit in 0..8 {
+                    if byte & (0b1000_0000 >> bit) != 0 {
+                        let val = ((window as u16) << 8) | (index * 8 + bit) as u16;
+                        types.push(RRType::from(val));
+                    }
+                }
+            }
+        }
+
+        Ok(Self { types })
+    }
+
+    pub fn serialize(&self, writer: &mut Writer) {
+        let mut windows: Vec<(u8, [u8; 32])> = vec![];
+        for rr_type in self.types.iter() {
+            let val = u16::from(rr_type);
+            let [window, low] = val.to_be_bytes();
+            if windows.last().map(|(w, _)| *w) != Some(window) {
+                windows.push((window, [0; 32]));
+            }
+            if let Some((_, bitmap)) = windows.last_mut() {
+                bitmap[(low / 8) as usize] |= 0b1000_0000 >> (low % 8);
+            }
+        }
+
+        for (window, bitmap) in windows.iter() {
+            // Trailing zero octets are left out
+            let length = bitmap.iter().rposition(|b| *b != 0).unwrap_or(0) + 1;
+            writer.write_u8(*window);
+            writer.write_u8(length as u8);
+            bitmap[..length].iter().for_each(|b| writer.write_u8(*b));
+        }
+    }
+
+    pub fn types(&self) -> &[RRType] {
+        &self.types
+    }
+
+    pub fn contains(&self, rr_type: &RRType) -> bool {
+        self.types.contains(rr_type)
+    }
+}
+
+impl Display for TypeBitmap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.types
+                .iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<String>>()
+                .join(" ")
+        )
+    }
+}
+
+/// Parses the types separated by whitespace, e.g. `A AAAA RRSIG`
+impl FromStr for TypeBitmap {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let types = s
+            .split_whitespace()
+            .map(|t| t.parse())
+            .collect::<ParseResult<Vec<RRType>>>()?;
+        Ok(TypeBitmap::new(types))
+    }
+}
@@ -1,8 +1,18 @@
 use std::collections::HashMap;
 
+use crate::common::domain_name::DomainName;
+
+const MAX_POINTER_OFFSET: usize = 0b0011_1111_1111_1111;
+
 pub struct Writer {
     buffer: Vec<u8>,
-    labels: HashMap<String, usize>,
+    labels: HashMap<DomainName, usize>,
+}
+
+impl Default for Writer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Writer {
@@ -29,6 +39,24 @@ impl Writer {
             .for_each(|b| self.buffer.push(b));
     }
 
+    /// Overwrites the two bytes at the given index, e.g. to fill in a length after the fact
+    pub fn set_u16(&mut self, index: usize, val: u16) {
+        let [b0, b1] = val.to_be_bytes();
+        self.buffer[index] = b0;
+        self.buffer[index + 1] = b1;
+    }
+
+    /// Overwrites the bytes starting at the given index, e.g. to fill in a header after the fact
+    pub fn overwrite(&mut self, index: usize, bytes: &[u8]) {
+        self.buffer[index..index + bytes.len()].copy_from_slice(bytes);
+    }
+
+    /// Drops everything written from the given index, along with the names there that could be pointed to
+    pub fn truncate(&mut self, len: usize) {
+        self.buffer.truncate(len);
+        self.labels.retain(|_, index| *index < len);
+    }
+
     pub fn get_serialized_message(&self) -> Vec<u8> {
         self.buffer.clone()
     }
@@ -37,6 +65,10 @@ impl Writer {
         self.buffer.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
     /// Writes the entire buffer of the other writer to this one
     pub fn merge(&mut self, other: &mut Writer) {
         for &b in other.buffer.iter() {
@@ -44,12 +76,17 @@ impl Writer {
         }
     }
 
-    pub fn track_label(&mut self, label: String) {
-        self.labels.insert(label, self.buffer.len());
+    /// Remembers that the name starts at the current position
+    pub fn track_label(&mut self, name: DomainName) {
+        // Pointers only have 14 bits for the offset
+        if self.buffer.len() <= MAX_POINTER_OFFSET {
+            self.labels.entry(name).or_insert(self.buffer.len());
+        }
     }
 
-    pub fn lookup_label(&self, label: &String) -> Option<usize> {
-        let index = self.labels.get(label)?;
+    /// Where a name that is equal to this one, ignoring case, was written
+    pub fn lookup_label(&self, name: &DomainName) -> Option<usize> {
+        let index = self.labels.get(name)?;
         Some(*index)
     }
 }
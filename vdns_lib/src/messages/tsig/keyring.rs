@@ -0,0 +1,174 @@
+use std::{
+    fmt::{Debug, Display, Formatter},
+    str::FromStr,
+};
+
+use data_encoding::BASE64;
+use hmac::{Hmac, Mac};
+use sha2::{Sha256, Sha512};
+
+use crate::common::domain_name::DomainName;
+
+/// The MAC algorithms we support for TSIG (RFC 8945 section 6)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TsigAlgorithm {
+    HmacSha256,
+    HmacSha512,
+}
+
+impl TsigAlgorithm {
+    pub fn name(&self) -> DomainName {
+        match self {
+            TsigAlgorithm::HmacSha256 => "hmac-sha256.",
+            TsigAlgorithm::HmacSha512 => "hmac-sha512.",
+        }
+        .parse()
+        .expect("Algorithm names are valid domain names")
+    }
+
+    pub fn from_name(name: &DomainName) -> Option<Self> {
+        name.to_string().parse().ok()
+    }
+
+    /// The length of an untruncated MAC
+    pub fn mac_length(&self) -> usize {
+        match self {
+            TsigAlgorithm::HmacSha256 => 32,
+            TsigAlgorithm::HmacSha512 => 64,
+        }
+    }
+
+    pub fn mac(&self, secret: &[u8], data: &[u8]) -> Vec<u8> {
+        match self {
+            TsigAlgorithm::HmacSha256 => {
+                let mut mac = Hmac::<Sha256>::new_from_slice(secret)
+                    .expect("HMAC accepts keys of any length");
+                mac.update(data);
+                mac.finalize().into_bytes().to_vec()
+            }
+            TsigAlgorithm::HmacSha512 => {
+                let mut mac = Hmac::<Sha512>::new_from_slice(secret)
+                    .expect("HMAC accepts keys of any length");
+                mac.update(data);
+                mac.finalize().into_bytes().to_vec()
+            }
+        }
+    }
+
+    /// Compares the possibly truncated MAC in constant time
+    pub fn verify(&self, secret: &[u8], data: &[u8], mac: &[u8]) -> bool {
+        match self {
+            TsigAlgorithm::HmacSha256 => {
+                let mut hmac = Hmac::<Sha256>::new_from_slice(secret)
+                    .expect("HMAC accepts keys of any length");
+                hmac.update(data);
+                hmac.verify_truncated_left(mac).is_ok()
+            }
+            TsigAlgorithm::HmacSha512 => {
+                let mut hmac = Hmac::<Sha512>::new_from_slice(secret)
+                    .expect("HMAC accepts keys of any length");
+                hmac.update(data);
+                hmac.verify_truncated_left(mac).is_ok()
+            }
+        }
+    }
+}
+
+impl FromStr for TsigAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim_end_matches('.').to_lowercase().as_str() {
+            "hmac-sha256" => Ok(TsigAlgorithm::HmacSha256),
+            "hmac-sha512" => Ok(TsigAlgorithm::HmacSha512),
+            other => Err(format!("Unsupported TSIG algorithm {other}")),
+        }
+    }
+}
+
+impl Display for TsigAlgorithm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A shared secret used to sign messages
+#[derive(Clone)]
+pub struct TsigKey {
+    name: DomainName,
+    algorithm: TsigAlgorithm,
+    secret: Vec<u8>,
+}
+
+impl TsigKey {
+    pub fn new(name: DomainName, algorithm: TsigAlgorithm, secret: Vec<u8>) -> Self {
+        Self {
+            name,
+            algorithm,
+            secret,
+        }
+    }
+
+    pub fn name(&self) -> &DomainName {
+        &self.name
+    }
+
+    pub fn algorithm(&self) -> &TsigAlgorithm {
+        &self.algorithm
+    }
+
+    pub fn secret(&self) -> &[u8] {
+        &self.secret
+    }
+}
+
+/// Parses keys on the form `[algorithm:]name:base64-secret`, the algorithm defaults to hmac-sha256
+impl FromStr for TsigKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s.split(':').collect::<Vec<&str>>();
+        let (algorithm, name, secret) = match parts.as_slice() {
+            [name, secret] => (TsigAlgorithm::HmacSha256, name, secret),
+            [algorithm, name, secret] => (algorithm.parse()?, name, secret),
+            _ => return Err("Expected a key on the form [algorithm:]name:secret".to_string()),
+        };
+
+        let secret = BASE64
+            .decode(secret.as_bytes())
+            .map_err(|err| format!("Invalid base64 secret: {err}"))?;
+
+        let name = name
+            .parse()
+            .map_err(|err| format!("Invalid key name: {err}"))?;
+
+        Ok(TsigKey::new(name, algorithm, secret))
+    }
+}
+
+// Keep the secret out of logs
+impl Debug for TsigKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TsigKey({} {})", self.name, self.algorithm)
+    }
+}
+
+/// The keys that messages may be signed with, looked up by name
+#[derive(Debug, Clone, Default)]
+pub struct Keyring {
+    keys: Vec<TsigKey>,
+}
+
+impl Keyring {
+    pub fn new(keys: Vec<TsigKey>) -> Self {
+        Self { keys }
+    }
+
+    pub fn get(&self, name: &DomainName) -> Option<&TsigKey> {
+        self.keys.iter().find(|k| k.name == *name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
@@ -0,0 +1,2 @@
+pub mod keyring;
+pub mod signing;
@@ -0,0 +1,296 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    common::{
+        class::Class, domain_name::DomainName, parse_error::ParseError, rr_type::RRType, ttl::TTL,
+    },
+    messages::{
+        header::flags::RCode,
+        message_ref::MessageRef,
+        resource_record::{resource_record::ResourceRecord, rr_data::RRData, tsig::TSIG},
+        serializing::Writer,
+    },
+};
+
+use super::keyring::{Keyring, TsigAlgorithm, TsigKey};
+
+/// How far the clocks of the signer and verifier may differ (RFC 8945 section 10)
+pub const DEFAULT_FUDGE: u16 = 300;
+
+const ID_INDEX: usize = 0;
+const AR_COUNT_INDEX: usize = 10;
+// A truncated MAC must keep at least this many bytes (RFC 8945 section 5.2.2.1)
+const MIN_MAC_LENGTH: usize = 10;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TsigError {
+    #[error("Message is not signed")]
+    Unsigned,
+    #[error("Failed to parse signed message")]
+    ParseError(#[from] ParseError),
+    #[error("Unknown key {0}")]
+    BadKey(DomainName),
+    #[error("Signature does not match")]
+    BadSig,
+    #[error("Signature time is outside of the allowed window")]
+    BadTime,
+    #[error("Signature is truncated too much")]
+    BadTrunc,
+    #[error("The other side rejected our signature with {0}")]
+    Rejected(RCode),
+}
+
+impl TsigError {
+    /// The error to put in the TSIG record of the response
+    pub fn r_code(&self) -> RCode {
+        match self {
+            TsigError::Unsigned | TsigError::ParseError(_) => RCode::FormatError,
+            TsigError::BadKey(_) => RCode::BadKey,
+            TsigError::BadSig => RCode::BadVers, // BADSIG shares its value with BADVERS
+            TsigError::BadTime => RCode::BadTime,
+            TsigError::BadTrunc => RCode::BadTrunc,
+            TsigError::Rejected(r_code) => r_code.clone(),
+        }
+    }
+}
+
+/// Signs and verifies the messages of a single transaction, e.g. a query and its response or
+/// a zone transfer request and all the messages of the transfer (RFC 8945 section 5).
+/// Each MAC covers the MAC of the message before it.
+pub struct TsigSession {
+    key: TsigKey,
+    prior_mac: Option<Vec<u8>>,
+    responses: usize,
+    error: RCode,
+}
+
+impl TsigSession {
+    /// A session for signing a request
+    pub fn new(key: TsigKey) -> Self {
+        Self {
+            key,
+            prior_mac: None,
+            responses: 0,
+            error: RCode::NoError,
+        }
+    }
+
+    /// Verifies a signed request with a key from the keyring, the session is then used to sign the response.
+    /// A request that is only signed at the wrong time is accepted, but the response will carry the BADTIME error.
+    pub fn accept_request(buf: &[u8], keyring: &Keyring) -> Result<TsigSession, TsigError> {
+        let (_, key_name, tsig) = find_tsig(buf)?.ok_or(TsigError::Unsigned)?;
+        let key = keyring
+            .get(&key_name)
+            .filter(|k| TsigAlgorithm::from_name(tsig.algorithm()).as_ref() == Some(k.algorithm()))
+            .ok_or(TsigError::BadKey(key_name))?;
+
+        let mut session = TsigSession::new(key.clone());
+        match session.verify(buf) {
+            Ok(()) => Ok(session),
+            Err(TsigError::BadTime) => {
+                session.error = RCode::BadTime;
+                Ok(session)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// The TSIG error that the next signed message will carry
+    pub fn error(&self) -> &RCode {
+        &self.error
+    }
+
+    pub fn key(&self) -> &TsigKey {
+        &self.key
+    }
+
+    /// How many bytes signing a message adds at most, to leave room for when limiting the size of the message
+    pub fn signature_length(&self) -> usize {
+        let other_data = match self.error {
+            RCode::BadTime => 6,
+            _ => 0,
+        };
+        // The type, class, TTL and data length of the record, then the fixed size fields of the TSIG data
+        self.key.name().wire_length()
+            + 10
+            + self.key.algorithm().name().wire_length()
+            + 16
+            + self.key.algorithm().mac_length()
+            + other_data
+    }
+
+    /// Appends a TSIG record to the serialized message
+    pub fn sign(&mut self, message: Vec<u8>) -> Vec<u8> {
+        self.sign_at(message, now())
+    }
+
+    /// Appends a TSIG record to the serialized message as if it was signed at the time, in seconds since the epoch
+    pub fn sign_at(&mut self, mut message: Vec<u8>, time_signed: u64) -> Vec<u8> {
+        let original_id = u16::from_be_bytes([message[ID_INDEX], message[ID_INDEX + 1]]);
+        let other_data = match self.error {
+            // Tell the other side what time we think it is
+            RCode::BadTime => time_signed.to_be_bytes()[2..].to_vec(),
+            _ => vec![],
+        };
+
+        let unsigned = TSIG::new(
+            self.key.algorithm().name(),
+            time_signed,
+            DEFAULT_FUDGE,
+            vec![],
+            original_id,
+            self.error.clone(),
+            other_data.clone(),
+        );
+        let data = self.digest_data(&message, &unsigned);
+        let mac = self.key.algorithm().mac(self.key.secret(), &data);
+
+        let tsig = TSIG::new(
+            self.key.algorithm().name(),
+            time_signed,
+            DEFAULT_FUDGE,
+            mac.clone(),
+            original_id,
+            self.error.clone(),
+            other_data,
+        );
+        append_tsig(&mut message, self.key.name().clone(), tsig);
+
+        self.advance(mac);
+        message
+    }
+
+    /// Verifies the TSIG record at the end of the serialized message
+    pub fn verify(&mut self, buf: &[u8]) -> Result<(), TsigError> {
+        let (start, key_name, tsig) = find_tsig(buf)?.ok_or(TsigError::Unsigned)?;
+        let algorithm = TsigAlgorithm::from_name(tsig.algorithm());
+        if key_name != *self.key.name() || algorithm.as_ref() != Some(self.key.algorithm()) {
+            return Err(TsigError::BadKey(key_name));
+        }
+        if tsig.error() != RCode::NoError && tsig.mac().is_empty() {
+            return Err(TsigError::Rejected(tsig.error()));
+        }
+
+        let mac_length = self.key.algorithm().mac_length();
+        if tsig.mac().len() > mac_length || tsig.mac().len() < MIN_MAC_LENGTH.max(mac_length / 2) {
+            return Err(TsigError::BadTrunc);
+        }
+
+        // The MAC is calculated over the message as it was before the TSIG record was added
+        let mut message = buf[..start].to_vec();
+        message[ID_INDEX..ID_INDEX + 2].copy_from_slice(&tsig.original_id().to_be_bytes());
+        let ar_count = u16::from_be_bytes([message[AR_COUNT_INDEX], message[AR_COUNT_INDEX + 1]]);
+        message[AR_COUNT_INDEX..AR_COUNT_INDEX + 2]
+            .copy_from_slice(&ar_count.saturating_sub(1).to_be_bytes());
+
+        let data = self.digest_data(&message, &tsig);
+        if !self
+            .key
+            .algorithm()
+            .verify(self.key.secret(), &data, tsig.mac())
+        {
+            return Err(TsigError::BadSig);
+        }
+        self.advance(tsig.mac().to_vec());
+
+        if now().abs_diff(tsig.time_signed()) > tsig.fudge() as u64 {
+            return Err(TsigError::BadTime);
+        }
+        if tsig.error() != RCode::NoError {
+            return Err(TsigError::Rejected(tsig.error()));
+        }
+
+        Ok(())
+    }
+
+    fn advance(&mut self, mac: Vec<u8>) {
+        if self.prior_mac.is_some() {
+            self.responses += 1;
+        }
+        self.prior_mac = Some(mac);
+    }
+
+    /// The data that the MAC is calculated over (RFC 8945 sections 4.3 and 5.3.1)
+    fn digest_data(&self, message: &[u8], tsig: &TSIG) -> Vec<u8> {
+        let mut writer = Writer::new();
+        if let Some(prior_mac) = self.prior_mac.as_ref() {
+            writer.write_u16(prior_mac.len() as u16);
+            prior_mac.iter().for_each(|b| writer.write_u8(*b));
+        }
+        message.iter().for_each(|b| writer.write_u8(*b));
+
+        if self.responses > 0 {
+            // Later messages of a multi-message response only include the timers
+            tsig.serialize_time(&mut writer);
+        } else {
+            self.key
+                .name()
+                .to_lowercase()
+                .serialize_uncompressed(&mut writer);
+            Class::Any.serialize(&mut writer);
+            TTL::NoCache.serialize(&mut writer);
+            tsig.algorithm()
+                .to_lowercase()
+                .serialize_uncompressed(&mut writer);
+            tsig.serialize_time(&mut writer);
+            tsig.serialize_error_and_other(&mut writer);
+        }
+
+        writer.get_serialized_message()
+    }
+}
+
+/// Appends an unsigned TSIG record carrying the error to a response for a request that could not be verified
+pub fn error_response(mut response: Vec<u8>, request: &[u8], error: &TsigError) -> Vec<u8> {
+    if let Ok(Some((_, key_name, tsig))) = find_tsig(request) {
+        let error_tsig = TSIG::new(
+            tsig.algorithm().clone(),
+            now(),
+            DEFAULT_FUDGE,
+            vec![],
+            tsig.original_id(),
+            error.r_code(),
+            vec![],
+        );
+        append_tsig(&mut response, key_name, error_tsig);
+    }
+    response
+}
+
+/// Whether the last record of the serialized message is a TSIG record
+pub fn is_signed(buf: &[u8]) -> bool {
+    matches!(find_tsig(buf), Ok(Some(_)))
+}
+
+/// Finds the TSIG record which must be the last record of the message,
+/// along with the index where it starts and its owner which is the name of the key.
+fn find_tsig(buf: &[u8]) -> Result<Option<(usize, DomainName, TSIG)>, ParseError> {
+    let record = match MessageRef::parse(buf)?.additional().last() {
+        Some(record) if record.rr_type() == RRType::TSIG => record,
+        _ => return Ok(None),
+    };
+
+    let start = record.offset();
+    let record = record.to_record()?;
+    Ok(match record.rdata() {
+        RRData::TSIG(tsig) => Some((start, record.name().clone(), tsig.clone())),
+        _ => None,
+    })
+}
+
+fn append_tsig(message: &mut Vec<u8>, key_name: DomainName, tsig: TSIG) {
+    let record = ResourceRecord::new(key_name, Class::Any, TTL::NoCache, RRData::TSIG(tsig));
+    let mut writer = Writer::new();
+    record.serialize(&mut writer);
+
+    let ar_count = u16::from_be_bytes([message[AR_COUNT_INDEX], message[AR_COUNT_INDEX + 1]]);
+    message[AR_COUNT_INDEX..AR_COUNT_INDEX + 2].copy_from_slice(&(ar_count + 1).to_be_bytes());
+    message.extend(writer.get_serialized_message());
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
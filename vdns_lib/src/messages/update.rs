@@ -0,0 +1,436 @@
+use std::fmt::{Display, Formatter};
+
+use crate::{
+    common::{
+        class::Class,
+        domain_name::DomainName,
+        formatting::indent_string,
+        parse_error::{ParseError, ParseResult},
+        q_class::QClass,
+        rr_type::RRType,
+        ttl::TTL,
+    },
+    messages::{
+        edns::edns::Edns,
+        header::{flags::OpCode, message_header::MessageHeader},
+        message::{Message, MessageBuilder},
+        question::question::Question,
+        resource_record::{resource_record::ResourceRecord, rr_data::RRData},
+    },
+};
+
+/// A dynamic update message (RFC 2136).
+/// Uses the same format as other messages, but the sections are used for the zone, prerequisites and updates.
+pub struct UpdateMessage {
+    pub header: MessageHeader,
+    pub zone: DomainName,
+    pub zone_class: Class,
+    pub prerequisites: Vec<Prerequisite>,
+    pub updates: Vec<Update>,
+    pub additional: Vec<ResourceRecord>,
+    pub edns: Option<Edns>,
+}
+
+impl UpdateMessage {
+    pub fn parse(buf: &[u8]) -> ParseResult<UpdateMessage> {
+        UpdateMessage::from_message(Message::parse(buf)?)
+    }
+
+    pub fn serialize(self) -> Vec<u8> {
+        self.into_message().serialize()
+    }
+
+    /// Interprets the sections of a parsed message with the UPDATE opcode
+    pub fn from_message(message: Message) -> ParseResult<UpdateMessage> {
+        if message.header.flags.op_code != OpCode::Update {
+            return Err(ParseError::RRError(format!(
+                "Expected an update message but got op code {}",
+                message.header.flags.op_code
+            )));
+        }
+
+        // The zone section must contain exactly one SOA 'question' (RFC 2136 section 3.1.1)
+        let zone = match message.questions.as_slice() {
+            [zone] => zone,
+            zones => {
+                return Err(ParseError::RRError(format!(
+                    "Update messages must have exactly one zone, got {}",
+                    zones.len()
+                )))
+            }
+        };
+        let (zone_name, zone_type) = zone.get_query_name_type();
+        if zone_type != RRType::SOA {
+            return Err(ParseError::RRError(format!(
+                "The zone of an update must have type SOA, got {zone_type}"
+            )));
+        }
+        let zone_class = Class::from(u16::from(zone.q_class()));
+
+        let prerequisites = message
+            .answer
+            .iter()
+            .map(|r| Prerequisite::from_record(r, &zone_class))
+            .collect::<ParseResult<Vec<Prerequisite>>>()?;
+        let updates = message
+            .authority
+            .iter()
+            .map(|r| Update::from_record(r, &zone_class))
+            .collect::<ParseResult<Vec<Update>>>()?;
+
+        Ok(UpdateMessage {
+            header: message.header,
+            zone: zone_name,
+            zone_class,
+            prerequisites,
+            updates,
+            additional: message.additional,
+            edns: message.edns,
+        })
+    }
+
+    pub fn into_message(self) -> Message {
+        let zone_class = self.zone_class;
+        MessageBuilder::from(Message {
+            header: self.header,
+            questions: vec![Question::from_parts(
+                self.zone,
+                RRType::SOA,
+                QClass::from(u16::from(&zone_class)),
+            )],
+            answer: self
+                .prerequisites
+                .into_iter()
+                .map(|p| p.into_record(&zone_class))
+                .collect(),
+            authority: self
+                .updates
+                .into_iter()
+                .map(|u| u.into_record(&zone_class))
+                .collect(),
+            additional: self.additional,
+            edns: self.edns,
+        })
+        .build()
+    }
+
+    pub fn builder(zone: DomainName) -> UpdateBuilder {
+        UpdateBuilder::new(zone)
+    }
+}
+
+impl Display for UpdateMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{{
+    Header: {},
+    Zone: {} {},
+    Prerequisites: {},
+    Updates: {}
+}}",
+            indent_string(self.header.to_string()),
+            self.zone,
+            self.zone_class,
+            if self.prerequisites.is_empty() {
+                "[]".to_string()
+            } else {
+                format!(
+                    "[
+        {}
+    ]",
+                    self.prerequisites
+                        .iter()
+                        .map(|p| p.to_string())
+                        .collect::<Vec<String>>()
+                        .join(",\n        ")
+                )
+            },
+            if self.updates.is_empty() {
+                "[]".to_string()
+            } else {
+                format!(
+                    "[
+        {}
+    ]",
+                    self.updates
+                        .iter()
+                        .map(|u| u.to_string())
+                        .collect::<Vec<String>>()
+                        .join(",\n        ")
+                )
+            },
+        )
+    }
+}
+
+/// A condition that must hold for the updates to be applied (RFC 2136 section 2.4)
+#[derive(Debug, Clone)]
+pub enum Prerequisite {
+    NameInUse(DomainName),
+    NameNotInUse(DomainName),
+    RRSetExists(DomainName, RRType),
+    RRSetDoesNotExist(DomainName, RRType),
+    /// All records with the same name and type together make up the RRset that must exist exactly as given
+    RecordExists(ResourceRecord),
+}
+
+impl Prerequisite {
+    pub fn from_record(record: &ResourceRecord, zone_class: &Class) -> ParseResult<Prerequisite> {
+        if record.ttl().seconds_until_expiration() != 0 {
+            return Err(ParseError::RRError(format!(
+                "Prerequisite for {} must have a TTL of zero",
+                record.name()
+            )));
+        }
+
+        let name = record.name().clone();
+        let record_type = record.record_type().clone();
+        let empty = has_empty_data(record);
+        Ok(match (record.class(), record_type) {
+            (Class::Any, RRType::All) if empty => Prerequisite::NameInUse(name),
+            (Class::Any, rr_type) if empty => Prerequisite::RRSetExists(name, rr_type),
+            (Class::None, RRType::All) if empty => Prerequisite::NameNotInUse(name),
+            (Class::None, rr_type) if empty => Prerequisite::RRSetDoesNotExist(name, rr_type),
+            (class, _) if class == zone_class => Prerequisite::RecordExists(record.clone()),
+            (class, rr_type) => {
+                return Err(ParseError::RRError(format!(
+                    "Invalid prerequisite {name} {class} {rr_type}"
+                )))
+            }
+        })
+    }
+
+    pub fn into_record(self, zone_class: &Class) -> ResourceRecord {
+        match self {
+            Prerequisite::NameInUse(name) => empty_record(name, Class::Any, RRType::All),
+            Prerequisite::NameNotInUse(name) => empty_record(name, Class::None, RRType::All),
+            Prerequisite::RRSetExists(name, rr_type) => empty_record(name, Class::Any, rr_type),
+            Prerequisite::RRSetDoesNotExist(name, rr_type) => {
+                empty_record(name, Class::None, rr_type)
+            }
+            Prerequisite::RecordExists(record) => ResourceRecord::new(
+                record.name().clone(),
+                zone_class.clone(),
+                TTL::NoCache,
+                record.rdata().clone(),
+            ),
+        }
+    }
+
+    pub fn name(&self) -> &DomainName {
+        match self {
+            Prerequisite::NameInUse(name)
+            | Prerequisite::NameNotInUse(name)
+            | Prerequisite::RRSetExists(name, _)
+            | Prerequisite::RRSetDoesNotExist(name, _) => name,
+            Prerequisite::RecordExists(record) => record.name(),
+        }
+    }
+}
+
+impl Display for Prerequisite {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Prerequisite::NameInUse(name) => write!(f, "{name} is in use"),
+            Prerequisite::NameNotInUse(name) => write!(f, "{name} is not in use"),
+            Prerequisite::RRSetExists(name, rr_type) => write!(f, "{name} {rr_type} exists"),
+            Prerequisite::RRSetDoesNotExist(name, rr_type) => {
+                write!(f, "{name} {rr_type} does not exist")
+            }
+            Prerequisite::RecordExists(record) => {
+                write!(f, "{} {} exists", record.name(), record.rdata())
+            }
+        }
+    }
+}
+
+/// A change to make to the zone (RFC 2136 section 2.5)
+#[derive(Debug, Clone)]
+pub enum Update {
+    Add(ResourceRecord),
+    DeleteRRSet(DomainName, RRType),
+    DeleteName(DomainName),
+    DeleteRecord(DomainName, RRData),
+}
+
+impl Update {
+    pub fn from_record(record: &ResourceRecord, zone_class: &Class) -> ParseResult<Update> {
+        let name = record.name().clone();
+        let record_type = record.record_type().clone();
+        let empty = has_empty_data(record);
+        let zero_ttl = record.ttl().seconds_until_expiration() == 0;
+        Ok(match (record.class(), record_type) {
+            (Class::Any, RRType::All) if empty && zero_ttl => Update::DeleteName(name),
+            (Class::Any, rr_type) if empty && zero_ttl => Update::DeleteRRSet(name, rr_type),
+            (Class::None, rr_type) if zero_ttl && !rr_type.is_meta() => {
+                Update::DeleteRecord(name, record.rdata().clone())
+            }
+            (class, rr_type) if class == zone_class && !rr_type.is_meta() => {
+                Update::Add(record.clone())
+            }
+            (class, rr_type) => {
+                return Err(ParseError::RRError(format!(
+                    "Invalid update {name} {class} {rr_type}"
+                )))
+            }
+        })
+    }
+
+    pub fn into_record(self, zone_class: &Class) -> ResourceRecord {
+        match self {
+            Update::Add(record) => ResourceRecord::new(
+                record.name().clone(),
+                zone_class.clone(),
+                record.ttl().clone(),
+                record.rdata().clone(),
+            ),
+            Update::DeleteRRSet(name, rr_type) => empty_record(name, Class::Any, rr_type),
+            Update::DeleteName(name) => empty_record(name, Class::Any, RRType::All),
+            Update::DeleteRecord(name, rdata) => {
+                ResourceRecord::new(name, Class::None, TTL::NoCache, rdata)
+            }
+        }
+    }
+
+    pub fn name(&self) -> &DomainName {
+        match self {
+            Update::Add(record) => record.name(),
+            Update::DeleteRRSet(name, _)
+            | Update::DeleteName(name)
+            | Update::DeleteRecord(name, _) => name,
+        }
+    }
+}
+
+impl Display for Update {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Update::Add(record) => write!(
+                f,
+                "Add {} {} {}",
+                record.name(),
+                record.ttl(),
+                record.rdata()
+            ),
+            Update::DeleteRRSet(name, rr_type) => write!(f, "Delete {name} {rr_type}"),
+            Update::DeleteName(name) => write!(f, "Delete {name}"),
+            Update::DeleteRecord(name, rdata) => write!(f, "Delete {name} {rdata}"),
+        }
+    }
+}
+
+/// Builds an update for a zone, the operations are added in order
+pub struct UpdateBuilder {
+    message: UpdateMessage,
+}
+
+impl UpdateBuilder {
+    pub fn new(zone: DomainName) -> Self {
+        let mut header = MessageHeader::new_query(false);
+        header.flags.op_code = OpCode::Update;
+
+        Self {
+            message: UpdateMessage {
+                header,
+                zone,
+                zone_class: Class::IN,
+                prerequisites: vec![],
+                updates: vec![],
+                additional: vec![],
+                edns: None,
+            },
+        }
+    }
+
+    pub fn require_name_in_use(mut self, name: DomainName) -> Self {
+        self.message
+            .prerequisites
+            .push(Prerequisite::NameInUse(name));
+        self
+    }
+
+    pub fn require_name_not_in_use(mut self, name: DomainName) -> Self {
+        self.message
+            .prerequisites
+            .push(Prerequisite::NameNotInUse(name));
+        self
+    }
+
+    pub fn require_rrset_exists(mut self, name: DomainName, rr_type: RRType) -> Self {
+        self.message
+            .prerequisites
+            .push(Prerequisite::RRSetExists(name, rr_type));
+        self
+    }
+
+    pub fn require_rrset_does_not_exist(mut self, name: DomainName, rr_type: RRType) -> Self {
+        self.message
+            .prerequisites
+            .push(Prerequisite::RRSetDoesNotExist(name, rr_type));
+        self
+    }
+
+    /// Requires the RRset of the record to be exactly the records required this way
+    pub fn require_record(mut self, name: DomainName, rdata: RRData) -> Self {
+        let record =
+            ResourceRecord::new(name, self.message.zone_class.clone(), TTL::NoCache, rdata);
+        self.message
+            .prerequisites
+            .push(Prerequisite::RecordExists(record));
+        self
+    }
+
+    pub fn add(mut self, name: DomainName, ttl: TTL, rdata: RRData) -> Self {
+        let record = ResourceRecord::new(name, self.message.zone_class.clone(), ttl, rdata);
+        self.message.updates.push(Update::Add(record));
+        self
+    }
+
+    pub fn delete_record(mut self, name: DomainName, rdata: RRData) -> Self {
+        self.message.updates.push(Update::DeleteRecord(name, rdata));
+        self
+    }
+
+    pub fn delete_rrset(mut self, name: DomainName, rr_type: RRType) -> Self {
+        self.message
+            .updates
+            .push(Update::DeleteRRSet(name, rr_type));
+        self
+    }
+
+    pub fn delete_name(mut self, name: DomainName) -> Self {
+        self.message.updates.push(Update::DeleteName(name));
+        self
+    }
+
+    pub fn additional(mut self, record: ResourceRecord) -> Self {
+        self.message.additional.push(record);
+        self
+    }
+
+    pub fn edns(mut self, edns: Edns) -> Self {
+        self.message.edns = Some(edns);
+        self
+    }
+
+    pub fn build(self) -> UpdateMessage {
+        self.message
+    }
+}
+
+fn has_empty_data(record: &ResourceRecord) -> bool {
+    matches!(record.rdata(), RRData::Unknown { rr_type: _, data } if data.is_empty())
+}
+
+fn empty_record(name: DomainName, class: Class, rr_type: RRType) -> ResourceRecord {
+    ResourceRecord::new(
+        name,
+        class,
+        TTL::NoCache,
+        RRData::Unknown {
+            rr_type: rr_type.into(),
+            data: vec![],
+        },
+    )
+}
@@ -0,0 +1,51 @@
+use std::{fmt::Display, io, path::PathBuf};
+
+use crate::common::parse_error::ParseError;
+
+/// Where in a zone file something was found, lines and columns start at 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Position {
+    /// None when parsing text that was not read from a file
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}:{}:{}", file.display(), self.line, self.column),
+            None => write!(f, "line {}, column {}", self.line, self.column),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ZoneFileError {
+    #[error("Failed to read zone file {}", .path.display())]
+    Read {
+        path: PathBuf,
+        #[source]
+        error: io::Error,
+    },
+    #[error("{position}: {message}")]
+    Syntax { position: Position, message: String },
+    #[error("{position}: {error}")]
+    Invalid {
+        position: Position,
+        #[source]
+        error: ParseError,
+    },
+}
+
+impl ZoneFileError {
+    /// Where the error was found, none if the file could not be read at all
+    pub fn position(&self) -> Option<&Position> {
+        match self {
+            ZoneFileError::Read { .. } => None,
+            ZoneFileError::Syntax { position, .. } | ZoneFileError::Invalid { position, .. } => {
+                Some(position)
+            }
+        }
+    }
+}
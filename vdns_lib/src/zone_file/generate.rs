@@ -0,0 +1,125 @@
+use std::str::FromStr;
+
+use crate::common::{
+    parse_error::{ParseError, ParseResult},
+    presentation::parse_field,
+};
+
+/// The range of a `$GENERATE` directive, `start-stop[/step]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenerateRange {
+    start: u32,
+    stop: u32,
+    step: u32,
+}
+
+impl GenerateRange {
+    pub fn values(&self) -> impl Iterator<Item = u32> {
+        (self.start..=self.stop).step_by(self.step as usize)
+    }
+
+    /// How many values the range has
+    pub fn count(&self) -> u64 {
+        (self.stop - self.start) as u64 / self.step as u64 + 1
+    }
+}
+
+impl FromStr for GenerateRange {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (range, step) = match s.split_once('/') {
+            Some((range, step)) => (range, parse_field(step, "$GENERATE step")?),
+            None => (s, 1),
+        };
+        let (start, stop) = range.split_once('-').ok_or(ParseError::RRError(format!(
+            "$GENERATE range '{s}' should be start-stop[/step]"
+        )))?;
+        let start = parse_field(start, "$GENERATE range start")?;
+        let stop = parse_field(stop, "$GENERATE range stop")?;
+
+        if start > stop || step == 0 {
+            return Err(ParseError::RRError(format!(
+                "Invalid $GENERATE range '{s}'"
+            )));
+        }
+        Ok(GenerateRange { start, stop, step })
+    }
+}
+
+/// Replaces `$` in the template with the value and `${offset,width,base}` with the value plus the offset,
+/// padded with zeroes to the width and written in base `d`, `o`, `x` or `X`. `\$` is a literal `$`.
+pub fn substitute(template: &str, value: u32) -> ParseResult<String> {
+    let mut text = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('$') => text.push('$'),
+                Some(escaped) => {
+                    text.push('\\');
+                    text.push(escaped);
+                }
+                None => text.push('\\'),
+            },
+            '$' if chars.peek() == Some(&'{') => {
+                chars.next();
+                let mut modifier = String::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        closed = true;
+                        break;
+                    }
+                    modifier.push(c);
+                }
+                if !closed {
+                    return Err(ParseError::RRError(format!(
+                        "Unclosed $GENERATE modifier in '{template}'"
+                    )));
+                }
+                text.push_str(&format_value(&modifier, value)?);
+            }
+            '$' => text.push_str(&value.to_string()),
+            c => text.push(c),
+        }
+    }
+
+    Ok(text)
+}
+
+fn format_value(modifier: &str, value: u32) -> ParseResult<String> {
+    let mut parts = modifier.split(',');
+    let offset = match parts.next() {
+        Some(offset) if !offset.is_empty() => parse_field(offset, "$GENERATE offset")?,
+        _ => 0,
+    };
+    let width = match parts.next() {
+        Some(width) => parse_field(width, "$GENERATE width")?,
+        None => 0,
+    };
+    let base = parts.next().unwrap_or("d");
+    if parts.next().is_some() {
+        return Err(ParseError::RRError(format!(
+            "Invalid $GENERATE modifier '{{{modifier}}}'"
+        )));
+    }
+
+    let value = u32::try_from(value as i64 + offset).map_err(|_| {
+        ParseError::RRError(format!(
+            "$GENERATE offset {offset} makes {value} out of range"
+        ))
+    })?;
+    Ok(match base {
+        "d" => format!("{value:0width$}"),
+        "o" => format!("{value:0width$o}"),
+        "x" => format!("{value:0width$x}"),
+        "X" => format!("{value:0width$X}"),
+        other => {
+            return Err(ParseError::RRError(format!(
+                "Unsupported $GENERATE base '{other}'"
+            )))
+        }
+    })
+}
@@ -0,0 +1,139 @@
+use std::path::Path;
+
+use super::error::{Position, ZoneFileError};
+
+/// A word of a zone file entry, quoted strings are kept with their quotes and escapes are kept as is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub text: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// The tokens of a single directive or record, which may span several lines within parentheses
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub tokens: Vec<Token>,
+    /// An entry starting with whitespace has no owner and uses the owner of the previous record
+    pub starts_with_blank: bool,
+}
+
+/// Splits the text of a zone file into entries (RFC 1035 section 5.1).
+/// Comments start with `;`, and line breaks within parentheses do not end the entry.
+pub fn tokenize(text: &str, file: Option<&Path>) -> Result<Vec<Entry>, ZoneFileError> {
+    let error = |line: usize, column: usize, message: &str| ZoneFileError::Syntax {
+        position: Position {
+            file: file.map(Path::to_path_buf),
+            line,
+            column,
+        },
+        message: message.to_string(),
+    };
+
+    let mut entries = vec![];
+    let mut tokens: Vec<Token> = vec![];
+    let mut starts_with_blank = false;
+    // Where the outermost open parenthesis is
+    let mut open: Option<(usize, usize)> = None;
+    let mut depth = 0;
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let chars: Vec<char> = line.chars().collect();
+        if depth == 0 && tokens.is_empty() {
+            starts_with_blank = matches!(chars.first(), Some(' ' | '\t'));
+        }
+
+        let mut current: Option<Token> = None;
+        let mut i = 0;
+        while i < chars.len() {
+            let column = i + 1;
+            match chars[i] {
+                ' ' | '\t' | '\r' => tokens.extend(current.take()),
+                ';' => break,
+                '(' => {
+                    tokens.extend(current.take());
+                    if depth == 0 {
+                        open = Some((line_number, column));
+                    }
+                    depth += 1;
+                }
+                ')' => {
+                    tokens.extend(current.take());
+                    if depth == 0 {
+                        return Err(error(line_number, column, "Unbalanced ')'"));
+                    }
+                    depth -= 1;
+                }
+                '"' => {
+                    tokens.extend(current.take());
+                    let mut text = String::from('"');
+                    loop {
+                        i += 1;
+                        match chars.get(i) {
+                            None => return Err(error(line_number, column, "Unterminated quote")),
+                            Some('"') => break,
+                            Some('\\') => {
+                                text.push('\\');
+                                i += 1;
+                                match chars.get(i) {
+                                    Some(c) => text.push(*c),
+                                    None => {
+                                        return Err(error(
+                                            line_number,
+                                            column,
+                                            "Unterminated quote",
+                                        ))
+                                    }
+                                }
+                            }
+                            Some(c) => text.push(*c),
+                        }
+                    }
+                    text.push('"');
+                    tokens.push(Token {
+                        text,
+                        line: line_number,
+                        column,
+                    });
+                }
+                c => {
+                    let token = current.get_or_insert_with(|| Token {
+                        text: String::new(),
+                        line: line_number,
+                        column,
+                    });
+                    token.text.push(c);
+                    if c == '\\' {
+                        i += 1;
+                        match chars.get(i) {
+                            Some(escaped) => token.text.push(*escaped),
+                            None => {
+                                return Err(error(
+                                    line_number,
+                                    column,
+                                    "Line ends with an unfinished escape",
+                                ))
+                            }
+                        }
+                    }
+                }
+            }
+            i += 1;
+        }
+        tokens.extend(current.take());
+
+        if depth == 0 && !tokens.is_empty() {
+            entries.push(Entry {
+                tokens: std::mem::take(&mut tokens),
+                starts_with_blank,
+            });
+        }
+    }
+
+    if let Some((line, column)) = open.filter(|_| depth > 0) {
+        return Err(error(line, column, "Unclosed '('"));
+    }
+
+    Ok(entries)
+}
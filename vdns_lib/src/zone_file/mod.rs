@@ -0,0 +1,4 @@
+pub mod error;
+pub mod generate;
+pub mod lexer;
+pub mod parser;
@@ -0,0 +1,323 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    common::{
+        class::Class, domain_name::DomainName, parse_error::ParseError, rr_type::RRType, ttl::TTL,
+    },
+    messages::resource_record::{resource_record::ResourceRecord, rr_data::RRData},
+};
+
+use super::{
+    error::{Position, ZoneFileError},
+    generate::{substitute, GenerateRange},
+    lexer::{tokenize, Entry, Token},
+};
+
+/// How deeply `$INCLUDE` directives may be nested, which stops files from including themselves forever
+pub const MAX_INCLUDE_DEPTH: usize = 16;
+/// How many records the `$GENERATE` directives of a zone file and its included files may create in total,
+/// so that a typo in a range can't exhaust memory
+pub const MAX_GENERATE_RECORDS: u64 = 65536;
+
+/// Parses zone files in the master file format (RFC 1035 section 5) into records.
+/// Besides `$ORIGIN` and `$INCLUDE` it supports `$TTL` (RFC 2308 section 4) and BIND's `$GENERATE`.
+pub struct ZoneFileParser {
+    origin: DomainName,
+    // Set by $TTL, used for records without a TTL
+    default_ttl: Option<TTL>,
+    last_owner: Option<DomainName>,
+    last_ttl: Option<TTL>,
+    last_class: Option<Class>,
+    include_depth: usize,
+    // Counted across every $GENERATE, including those in included files
+    generated_records: u64,
+    records: Vec<ResourceRecord>,
+}
+
+impl ZoneFileParser {
+    /// A parser where relative names are relative to the origin, until changed by `$ORIGIN`.
+    /// The origin should be absolute, usually the name of the zone.
+    pub fn new(origin: DomainName) -> Self {
+        Self {
+            origin,
+            default_ttl: None,
+            last_owner: None,
+            last_ttl: None,
+            last_class: None,
+            include_depth: 0,
+            generated_records: 0,
+            records: vec![],
+        }
+    }
+
+    pub fn parse_file(
+        mut self,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<ResourceRecord>, ZoneFileError> {
+        self.read_file(path.as_ref())?;
+        Ok(self.records)
+    }
+
+    /// Parses the text of a zone file. `$INCLUDE` paths in the text are relative to the working directory,
+    /// those in included files are relative to the directory of the file that includes them.
+    pub fn parse_str(mut self, text: &str) -> Result<Vec<ResourceRecord>, ZoneFileError> {
+        self.parse_text(text, None)?;
+        Ok(self.records)
+    }
+
+    fn read_file(&mut self, path: &Path) -> Result<(), ZoneFileError> {
+        let text = fs::read_to_string(path).map_err(|error| ZoneFileError::Read {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        self.parse_text(&text, Some(path))
+    }
+
+    fn parse_text(&mut self, text: &str, file: Option<&Path>) -> Result<(), ZoneFileError> {
+        for Entry {
+            tokens,
+            starts_with_blank,
+        } in tokenize(text, file)?
+        {
+            if !starts_with_blank && tokens[0].text.starts_with('$') {
+                self.parse_directive(&tokens, file)?;
+            } else {
+                self.parse_record(&tokens, starts_with_blank, file)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_directive(
+        &mut self,
+        tokens: &[Token],
+        file: Option<&Path>,
+    ) -> Result<(), ZoneFileError> {
+        let directive = &tokens[0];
+        match (directive.text.to_ascii_uppercase().as_str(), &tokens[1..]) {
+            ("$ORIGIN", [name]) => self.origin = self.qualify(name, file)?,
+            ("$ORIGIN", _) => return Err(syntax(file, directive, "$ORIGIN takes a domain name")),
+            ("$TTL", [ttl]) => {
+                self.default_ttl = Some(ttl.text.parse().map_err(|e| invalid(file, ttl, e))?)
+            }
+            ("$TTL", _) => return Err(syntax(file, directive, "$TTL takes a TTL")),
+            ("$INCLUDE", [path, origin @ ..]) if origin.len() <= 1 => {
+                self.include(path, origin.first(), file)?
+            }
+            ("$INCLUDE", _) => {
+                return Err(syntax(
+                    file,
+                    directive,
+                    "$INCLUDE takes a file name and optionally an origin",
+                ))
+            }
+            ("$GENERATE", [range, template @ ..]) if template.len() >= 3 => {
+                let values: GenerateRange =
+                    range.text.parse().map_err(|e| invalid(file, range, e))?;
+                let remaining = MAX_GENERATE_RECORDS - self.generated_records;
+                if values.count() > remaining {
+                    let error = ParseError::RRError(format!(
+                        "$GENERATE range would create {} records, only {remaining} of the {MAX_GENERATE_RECORDS} allowed are left",
+                        values.count()
+                    ));
+                    return Err(invalid(file, range, error));
+                }
+                self.generated_records += values.count();
+                for value in values.values() {
+                    let tokens = template
+                        .iter()
+                        .map(|token| {
+                            Ok(Token {
+                                text: substitute(&token.text, value)
+                                    .map_err(|e| invalid(file, token, e))?,
+                                ..token.clone()
+                            })
+                        })
+                        .collect::<Result<Vec<Token>, ZoneFileError>>()?;
+                    self.parse_record(&tokens, false, file)?;
+                }
+            }
+            ("$GENERATE", _) => {
+                return Err(syntax(
+                    file,
+                    directive,
+                    "$GENERATE takes a range, an owner, an optional TTL and class, a type and data",
+                ))
+            }
+            _ => return Err(syntax(file, directive, "Unknown directive")),
+        }
+        Ok(())
+    }
+
+    /// The included file starts with the current origin unless one is given, and doesn't change the origin
+    /// of the including file (RFC 1035 section 5.1)
+    fn include(
+        &mut self,
+        path: &Token,
+        origin: Option<&Token>,
+        file: Option<&Path>,
+    ) -> Result<(), ZoneFileError> {
+        if self.include_depth >= MAX_INCLUDE_DEPTH {
+            return Err(syntax(file, path, "$INCLUDE is nested too deeply"));
+        }
+
+        let name = path.text.trim_matches('"');
+        let included = match file.and_then(Path::parent) {
+            Some(directory) => directory.join(name),
+            None => PathBuf::from(name),
+        };
+
+        let outer_origin = self.origin.clone();
+        if let Some(origin) = origin {
+            self.origin = self.qualify(origin, file)?;
+        }
+        self.include_depth += 1;
+        let result = self.read_file(&included);
+        self.include_depth -= 1;
+        self.origin = outer_origin;
+        result
+    }
+
+    /// Parses `[owner] [TTL] [class] type data`, where the TTL and class may come in either order
+    fn parse_record(
+        &mut self,
+        tokens: &[Token],
+        starts_with_blank: bool,
+        file: Option<&Path>,
+    ) -> Result<(), ZoneFileError> {
+        let mut rest = tokens;
+        let owner = match (starts_with_blank, rest.split_first()) {
+            (false, Some((owner, remaining))) => {
+                rest = remaining;
+                self.qualify(owner, file)?
+            }
+            _ => self.last_owner.clone().ok_or_else(|| {
+                syntax(
+                    file,
+                    &tokens[0],
+                    "Record has no owner and follows no other record",
+                )
+            })?,
+        };
+
+        let mut ttl = None;
+        let mut class = None;
+        let (rr_type, type_token) = loop {
+            let (token, remaining) = rest.split_first().ok_or_else(|| {
+                syntax(
+                    file,
+                    &tokens[tokens.len() - 1],
+                    "Record is missing its type",
+                )
+            })?;
+            rest = remaining;
+
+            if ttl.is_none() && token.text.starts_with(|c: char| c.is_ascii_digit()) {
+                ttl = Some(token.text.parse().map_err(|e| invalid(file, token, e))?);
+                continue;
+            }
+            if class.is_none() {
+                if let Ok(parsed) = token.text.parse::<Class>() {
+                    class = Some(parsed);
+                    continue;
+                }
+            }
+            let rr_type: RRType = token.text.parse().map_err(|e| invalid(file, token, e))?;
+            break (rr_type, token);
+        };
+
+        // Names in the data are qualified here as the data types only know of absolute names
+        let generic = rest.first().is_some_and(|t| t.text == "\\#");
+        let name_fields = if generic { &[] } else { name_fields(&rr_type) };
+        let mut data = Vec::with_capacity(rest.len());
+        for (index, token) in rest.iter().enumerate() {
+            match name_fields.contains(&index) {
+                true => data.push(self.qualify(token, file)?.to_string()),
+                false => data.push(token.text.clone()),
+            }
+        }
+        let rdata = RRData::from_presentation(&rr_type, &data.join(" "))
+            .map_err(|e| invalid(file, rest.first().unwrap_or(type_token), e))?;
+
+        let ttl = match ttl
+            .or_else(|| self.default_ttl.clone())
+            .or_else(|| self.last_ttl.clone())
+        {
+            Some(ttl) => ttl,
+            // Zone files from before $TTL used the minimum of the SOA record (RFC 2308 section 4)
+            None => match &rdata {
+                RRData::SOA(soa) => TTL::from(soa.minimum()),
+                _ => {
+                    return Err(syntax(
+                        file,
+                        type_token,
+                        "Record has no TTL and there is no $TTL or previous TTL",
+                    ))
+                }
+            },
+        };
+        let class = class
+            .or_else(|| self.last_class.clone())
+            .unwrap_or(Class::IN);
+
+        self.last_owner = Some(owner.clone());
+        self.last_ttl = Some(ttl.clone());
+        self.last_class = Some(class.clone());
+        self.records
+            .push(ResourceRecord::new(owner, class, ttl, rdata));
+        Ok(())
+    }
+
+    /// Resolves `@` to the origin and makes relative names relative to the origin
+    fn qualify(&self, token: &Token, file: Option<&Path>) -> Result<DomainName, ZoneFileError> {
+        if token.text == "@" {
+            return Ok(self.origin.clone());
+        }
+
+        let name: DomainName = token.text.parse().map_err(|e| invalid(file, token, e))?;
+        match name.is_absolute() {
+            true => Ok(name),
+            false => name
+                .append(&self.origin)
+                .map_err(|e| invalid(file, token, e)),
+        }
+    }
+}
+
+/// The positions of the fields of the data that are domain names, which may be relative in zone files
+fn name_fields(rr_type: &RRType) -> &'static [usize] {
+    match rr_type {
+        RRType::CNAME | RRType::NS | RRType::PTR | RRType::NSEC => &[0],
+        RRType::SOA => &[0, 1],
+        RRType::MX | RRType::SVCB | RRType::HTTPS => &[1],
+        RRType::SRV => &[3],
+        RRType::NAPTR => &[5],
+        RRType::RRSIG => &[7],
+        _ => &[],
+    }
+}
+
+fn position(file: Option<&Path>, token: &Token) -> Position {
+    Position {
+        file: file.map(Path::to_path_buf),
+        line: token.line,
+        column: token.column,
+    }
+}
+
+fn syntax(file: Option<&Path>, token: &Token, message: &str) -> ZoneFileError {
+    ZoneFileError::Syntax {
+        position: position(file, token),
+        message: message.to_string(),
+    }
+}
+
+fn invalid(file: Option<&Path>, token: &Token, error: ParseError) -> ZoneFileError {
+    ZoneFileError::Invalid {
+        position: position(file, token),
+        error,
+    }
+}
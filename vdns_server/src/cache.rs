@@ -7,8 +7,8 @@ use vdns_lib::{
 
 #[inline(always)]
 fn get_id(name: &DomainName, rr_type: &RRType) -> String {
-    let lower_name = name.to_string().to_lowercase(); // Domain names should be compared case-insensitively
-    format!("{rr_type}@{lower_name}")
+    // Domain names should be compared case-insensitively
+    format!("{rr_type}@{}", name.to_lowercase())
 }
 
 pub async fn cache_response(redis_pool: &Pool<RedisConnectionManager>, response: &Message) {
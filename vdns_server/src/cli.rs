@@ -0,0 +1,94 @@
+use std::{path::PathBuf, str::FromStr};
+
+use clap::{Parser, ValueEnum};
+use vdns_lib::{
+    common::domain_name::DomainName,
+    messages::{
+        edns::client_subnet::{DEFAULT_IPV4_SOURCE_PREFIX, DEFAULT_IPV6_SOURCE_PREFIX},
+        tsig::keyring::TsigKey,
+    },
+};
+
+/// DNS server that forwards queries to the upstream resolver and caches the answers
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+pub struct CLI {
+    /// How to handle EDNS Client Subnet information towards the upstream resolver
+    #[arg(long, value_enum, default_value_t = EcsMode::Strip)]
+    pub ecs: EcsMode,
+
+    /// The longest IPv4 source prefix sent upstream, longer client subnets are shortened to it
+    #[arg(
+        long,
+        value_name = "LENGTH",
+        default_value_t = DEFAULT_IPV4_SOURCE_PREFIX,
+        value_parser = clap::value_parser!(u8).range(0..=32)
+    )]
+    pub ecs_ipv4_prefix: u8,
+
+    /// The longest IPv6 source prefix sent upstream, longer client subnets are shortened to it
+    #[arg(
+        long,
+        value_name = "LENGTH",
+        default_value_t = DEFAULT_IPV6_SOURCE_PREFIX,
+        value_parser = clap::value_parser!(u8).range(0..=128)
+    )]
+    pub ecs_ipv6_prefix: u8,
+
+    /// Only send responses larger than 512 bytes over UDP to clients that present a valid server cookie
+    #[arg(long)]
+    pub require_cookie: bool,
+
+    /// Serve this zone locally instead of forwarding queries for it, may be given several times
+    #[arg(long = "zone", value_name = "ZONE")]
+    pub zones: Vec<DomainName>,
+
+    /// Serve a zone with the records of a zone file, given as zone:path. May be given several times
+    #[arg(long = "zone-file", value_name = "ZONE:PATH")]
+    pub zone_files: Vec<ZoneFile>,
+
+    /// Accept dynamic updates and zone transfers signed with this key, given as [algorithm:]name:base64-secret.
+    /// May be given several times
+    #[arg(long = "tsig-key", value_name = "KEY")]
+    pub tsig_keys: Vec<TsigKey>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EcsMode {
+    /// Never send client subnet information upstream
+    Strip,
+    /// Pass on client subnet information sent by the client, shortened to the configured prefix length
+    Forward,
+    /// Like forward, but derive it from the client address when the client did not send any,
+    /// unless the client is on a private network
+    Synthesize,
+}
+
+#[derive(Clone, Debug)]
+pub struct ZoneFile {
+    pub zone: DomainName,
+    pub path: PathBuf,
+}
+
+impl FromStr for ZoneFile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (zone, path) = s
+            .split_once(':')
+            .ok_or("Expected a zone file on the form zone:path".to_string())?;
+        let mut zone: DomainName = zone
+            .parse()
+            .map_err(|err| format!("Invalid zone name: {err}"))?;
+        if !zone.is_absolute() {
+            zone = zone
+                .append(&DomainName::root())
+                .map_err(|err| format!("Invalid zone name: {err}"))?;
+        }
+
+        Ok(ZoneFile {
+            zone,
+            path: PathBuf::from(path),
+        })
+    }
+}
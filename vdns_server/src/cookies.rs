@@ -0,0 +1,120 @@
+use std::{
+    net::IpAddr,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha2::Sha256;
+use vdns_lib::messages::edns::cookie::Cookie;
+
+const SECRET_ROTATION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+// How old and how far into the future a server cookie may be (RFC 9018 section 4.3)
+const MAX_COOKIE_AGE: u32 = 60 * 60;
+const MAX_COOKIE_CLOCK_SKEW: u32 = 5 * 60;
+
+const COOKIE_VERSION: u8 = 1;
+const HASH_LENGTH: usize = 8;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Issues and verifies server cookies, laid out as in RFC 9018 but with a truncated HMAC-SHA256 as the hash.
+/// The secret is rotated regularly, cookies made with the previous secret are still accepted.
+pub struct CookieSecrets {
+    current: [u8; 32],
+    previous: Option<[u8; 32]>,
+    rotated_at: Instant,
+}
+
+impl Default for CookieSecrets {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CookieSecrets {
+    pub fn new() -> Self {
+        Self {
+            current: rand::thread_rng().gen(),
+            previous: None,
+            rotated_at: Instant::now(),
+        }
+    }
+
+    fn rotate_if_needed(&mut self) {
+        if self.rotated_at.elapsed() >= SECRET_ROTATION_INTERVAL {
+            self.previous = Some(self.current);
+            self.current = rand::thread_rng().gen();
+            self.rotated_at = Instant::now();
+        }
+    }
+
+    /// The cookie to send back to the client, echoing its client cookie along with a fresh server cookie
+    pub fn response_cookie(&mut self, query_cookie: &Cookie, client_ip: IpAddr) -> Cookie {
+        self.rotate_if_needed();
+
+        let mut server = vec![COOKIE_VERSION, 0, 0, 0];
+        server.extend_from_slice(&now().to_be_bytes());
+        let hash = hash(&self.current, query_cookie.client(), &server, client_ip);
+        server.extend_from_slice(&hash);
+
+        Cookie::new(*query_cookie.client(), Some(server))
+            .expect("Server cookie should have a valid length")
+    }
+
+    /// Whether the query cookie includes a server cookie that we issued to this client recently
+    pub fn is_valid(&mut self, query_cookie: &Cookie, client_ip: IpAddr) -> bool {
+        self.rotate_if_needed();
+
+        let server = match query_cookie.server() {
+            Some(server) if server.len() == 8 + HASH_LENGTH && server[0] == COOKIE_VERSION => {
+                server
+            }
+            _ => return false,
+        };
+
+        let timestamp = u32::from_be_bytes([server[4], server[5], server[6], server[7]]);
+        let now = now();
+        if timestamp > now.wrapping_add(MAX_COOKIE_CLOCK_SKEW)
+            || now.wrapping_sub(timestamp) > MAX_COOKIE_AGE
+        {
+            return false;
+        }
+
+        let (prefix, received_hash) = server.split_at(8);
+        [Some(self.current), self.previous]
+            .into_iter()
+            .flatten()
+            .any(|secret| {
+                // Compares the truncated MAC in constant time
+                mac(&secret, query_cookie.client(), prefix, client_ip)
+                    .verify_truncated_left(received_hash)
+                    .is_ok()
+            })
+    }
+}
+
+fn now() -> u32 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as u32)
+        .unwrap_or_default()
+}
+
+fn mac(secret: &[u8], client_cookie: &[u8], prefix: &[u8], client_ip: IpAddr) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(client_cookie);
+    mac.update(prefix);
+    match client_ip {
+        IpAddr::V4(ip) => mac.update(&ip.octets()),
+        IpAddr::V6(ip) => mac.update(&ip.octets()),
+    }
+    mac
+}
+
+fn hash(secret: &[u8], client_cookie: &[u8], prefix: &[u8], client_ip: IpAddr) -> Vec<u8> {
+    mac(secret, client_cookie, prefix, client_ip)
+        .finalize()
+        .into_bytes()[..HASH_LENGTH]
+        .to_vec()
+}
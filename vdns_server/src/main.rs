@@ -1,21 +1,63 @@
-use std::net::{IpAddr, SocketAddr, UdpSocket};
+use std::{
+    net::{IpAddr, SocketAddr, TcpListener, UdpSocket},
+    sync::{Arc, Mutex},
+    thread,
+};
 
+use clap::Parser;
+use cli::{EcsMode, CLI};
+use cookies::CookieSecrets;
 use mobc::Pool;
 use mobc_redis::{redis, RedisConnectionManager};
+use tracing_subscriber::EnvFilter;
 use vdns_lib::{
-    lookup,
+    common::{class::Class, domain_name::DomainName, rr_type::RRType},
+    cookie_for,
     messages::{
-        header::flags::RCode, message::Message, resource_record::resource_record::ResourceRecord,
+        edns::{
+            client_subnet::{is_private_address, ClientSubnet},
+            edns::Edns,
+            extended_error::{ExtendedError, ExtendedErrorCode},
+        },
+        header::{
+            flags::{OpCode, RCode},
+            message_header::MessageHeader,
+        },
+        message::{Message, MessageBuilder, DNS_MAX_UDP_PAYLOAD_SIZE},
+        message_ref::MessageRef,
+        parsing::Reader,
+        resource_record::{resource_record::ResourceRecord, rr_data::RRData},
+        tsig::{
+            keyring::Keyring,
+            signing::{error_response, is_signed, TsigSession},
+        },
+        update::UpdateMessage,
     },
-    DNS_PORT,
+    send_query,
+    zone_file::parser::ZoneFileParser,
+    LookupError, DNS_PORT,
 };
+use zones::{Zone, ZoneStore};
 
 pub mod cache;
+pub mod cli;
+pub mod cookies;
+pub mod transfer;
+pub mod zones;
 
-const DNS_MAX_PACKAGE_SIZE: usize = 512;
+// Queries using EDNS may be larger than the 512 bytes of plain DNS
+const DNS_MAX_PACKAGE_SIZE: usize = 4096;
 
 #[tokio::main]
 pub async fn main() {
+    let args = CLI::parse();
+
+    // Parsing diagnostics are enabled with e.g. RUST_LOG=vdns_lib=debug
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+
     // Setup Redis cache
     let redis_client =
         redis::Client::open("redis://localhost:6379").expect("Failed to connect to redis");
@@ -25,6 +67,31 @@ pub async fn main() {
     let socket = UdpSocket::bind(SocketAddr::from(([0, 0, 0, 0], DNS_PORT)))
         .expect("Failed to bind to UDP port ");
     let router_address = IpAddr::from([192, 168, 1, 1]);
+    let mut cookie_secrets = CookieSecrets::new();
+    let mut local_zones: Vec<Zone> = args.zones.iter().cloned().map(Zone::new).collect();
+    for zone_file in args.zone_files.iter() {
+        let records = ZoneFileParser::new(zone_file.zone.clone())
+            .parse_file(&zone_file.path)
+            .unwrap_or_else(|err| panic!("Failed to load zone file: {err}"));
+        let zone = Zone::from_records(zone_file.zone.clone(), records)
+            .unwrap_or_else(|err| panic!("Invalid zone file {}: {err}", zone_file.path.display()));
+        println!(
+            "Loaded {} records for zone {}",
+            zone.record_count(),
+            zone.name()
+        );
+        local_zones.push(zone);
+    }
+    let zones = Arc::new(Mutex::new(ZoneStore::new(local_zones)));
+    let keyring = Keyring::new(args.tsig_keys.clone());
+
+    let transfer_listener = TcpListener::bind(SocketAddr::from(([0, 0, 0, 0], DNS_PORT)))
+        .expect("Failed to bind to TCP port");
+    let transfer_zones = zones.clone();
+    let transfer_keyring = keyring.clone();
+    thread::spawn(move || {
+        transfer::serve_transfers(transfer_listener, transfer_zones, transfer_keyring)
+    });
     println!("VDNS server started and listening on port {DNS_PORT}");
 
     loop {
@@ -33,29 +100,142 @@ pub async fn main() {
             .recv_from(&mut receive_buffer)
             .expect("Failed to receive UDP message");
 
-        let message = Message::parse(&receive_buffer[0..bytes_received])
-            .expect("Failed to parse DNS message");
+        let request = &receive_buffer[0..bytes_received];
+
+        // Check the framing and look up the questions in our zones and the cache before decoding everything
+        let (message, answer_source) = match MessageRef::parse(request) {
+            Ok(message_ref) => {
+                let source =
+                    find_answer_source(&args, &zones, &redis_pool, &message_ref, remote_addr.ip())
+                        .await;
+                (message_ref.to_message(), source)
+            }
+            Err(err) => (Err(err), AnswerSource::Upstream(vec![])),
+        };
+        let message = match message {
+            Ok(message) => message,
+            Err(err) => {
+                println!("Failed to parse DNS message from {remote_addr}: {err}");
+                if let Some(response) = format_error_response(request) {
+                    socket
+                        .send_to(&response, remote_addr)
+                        .expect("Failed to send response!");
+                }
+                continue;
+            }
+        };
 
         println!("Request received for {}", message.to_short_string());
 
         if message.is_query() {
-            let response = if message.do_recursion() {
-                let responses = get_answers(&redis_pool, &message, &router_address).await;
+            let mut tsig_session = None;
+            if is_signed(request) {
+                match TsigSession::accept_request(request, &keyring) {
+                    Ok(session) => tsig_session = Some(session),
+                    Err(err) => {
+                        println!("\t- Rejecting request with invalid signature: {err}");
+                        let response = MessageBuilder::response(&message)
+                            .r_code(RCode::NotAuth)
+                            .build();
+                        socket
+                            .send_to(
+                                &error_response(response.serialize(), request, &err),
+                                remote_addr,
+                            )
+                            .expect("Failed to send response!");
+                        continue;
+                    }
+                }
+            }
+            let signed = tsig_session
+                .as_ref()
+                .is_some_and(|s| *s.error() == RCode::NoError);
 
-                Message::new_response(&message, responses)
-            } else {
-                // Get it from the cache
-                todo!("Only recursion is available at the moment");
+            let mut response = match message.header.flags.op_code {
+                // The signature was valid but made at the wrong time, the error is carried in our signature
+                _ if tsig_session.is_some() && !signed => MessageBuilder::response(&message)
+                    .r_code(RCode::NotAuth)
+                    .build(),
+                OpCode::Query => match answer_source {
+                    AnswerSource::Zone(zone) => {
+                        answer_locally(&zones.lock().unwrap(), &zone, &message)
+                    }
+                    AnswerSource::Upstream(cached) => {
+                        handle_query(
+                            &args,
+                            &redis_pool,
+                            &router_address,
+                            &message,
+                            cached,
+                            remote_addr.ip(),
+                        )
+                        .await
+                    }
+                },
+                OpCode::Notify => handle_notify(&message),
+                OpCode::Update => handle_update(&message, &mut zones.lock().unwrap(), signed),
+                _ => handle_not_implemented(&message),
             };
 
-            println!("\t- Responding with {} answers", response.answer.len());
-            cache::cache_response(&redis_pool, &response).await;
+            let mut valid_cookie = false;
+            if let (Some(edns), Some(query_cookie)) = (
+                response.edns.as_mut(),
+                message.edns.as_ref().and_then(|e| e.cookie()),
+            ) {
+                valid_cookie = cookie_secrets.is_valid(query_cookie, remote_addr.ip());
+                edns.set_cookie(Some(
+                    cookie_secrets.response_cookie(query_cookie, remote_addr.ip()),
+                ));
+            }
+            let response_edns = response.edns.clone();
+
+            // Never send more than the client can receive, or more than we advertise (RFC 6891 section 6.2.5)
+            let mut max_size = match (message.edns.as_ref(), response.edns.as_ref()) {
+                (Some(query_edns), Some(response_edns)) => query_edns
+                    .max_udp_payload_size()
+                    .min(response_edns.max_udp_payload_size()),
+                _ => DNS_MAX_UDP_PAYLOAD_SIZE,
+            };
+            if let Some(session) = tsig_session.as_ref() {
+                max_size = max_size.saturating_sub(session.signature_length());
+            }
 
             // Send the response
-            let serialized = response.serialize();
+            let mut serialized = response.serialize_with_limit(max_size);
+            if args.require_cookie && !valid_cookie && serialized.len() > DNS_MAX_UDP_PAYLOAD_SIZE {
+                // Large responses could be used for reflection attacks, make the client come back with our cookie.
+                // Clients that sent a cookie get BADCOOKIE along with our cookie (RFC 7873 section 5.2.3),
+                // others can only be told to retry over TCP.
+                serialized = match response_edns.filter(|e| e.cookie().is_some()) {
+                    Some(edns) => {
+                        println!("\t- Responding BADCOOKIE to client without a valid cookie");
+                        MessageBuilder::response(&message)
+                            .r_code(RCode::BadCookie)
+                            .edns(edns)
+                            .build()
+                            .serialize()
+                    }
+                    None => {
+                        println!("\t- Truncating response to client without a cookie");
+                        MessageBuilder::response(&message)
+                            .truncated(true)
+                            .build()
+                            .serialize()
+                    }
+                };
+            }
 
             // Try to parse it to ensure that it looks alright
-            Message::parse(&serialized).expect("Failed to parse message to send!!!");
+            if let Err(err) = Message::parse(&serialized) {
+                println!("\t- Failed to parse response to send, sending SERVFAIL instead: {err}");
+                serialized = MessageBuilder::response(&message)
+                    .r_code(RCode::ServerFailure)
+                    .build()
+                    .serialize();
+            }
+            if let Some(session) = tsig_session.as_mut() {
+                serialized = session.sign(serialized);
+            }
 
             socket
                 .send_to(&serialized, remote_addr)
@@ -66,23 +246,365 @@ pub async fn main() {
     }
 }
 
-async fn get_answers(
+async fn handle_query(
+    args: &CLI,
+    redis_pool: &Pool<RedisConnectionManager>,
+    router_address: &IpAddr,
+    message: &Message,
+    cached: Vec<Option<ResourceRecord>>,
+    client_ip: IpAddr,
+) -> Message {
+    let (response, cacheable) = if message.do_recursion() {
+        let client_subnet = upstream_client_subnet(message.edns.as_ref(), args, client_ip);
+        let answers = get_answers(message, router_address, client_subnet, cached);
+
+        let cacheable = answers.cacheable();
+        let mut response = MessageBuilder::response(message)
+            .r_code(answers.r_code)
+            .answers(answers.records)
+            .build();
+        for error in answers.extended_errors.into_iter() {
+            add_extended_error(&mut response, error);
+        }
+        if let (Some(edns), Some(query_subnet)) = (
+            response.edns.as_mut(),
+            message.edns.as_ref().and_then(|e| e.client_subnet()),
+        ) {
+            if args.ecs != EcsMode::Strip {
+                // Tell the client which part of its subnet the answer applies to
+                let scope = answers
+                    .client_subnet
+                    .as_ref()
+                    .map(|s| s.scope_prefix())
+                    .unwrap_or_default();
+                edns.set_client_subnet(Some(query_subnet.with_scope_prefix(scope)));
+            }
+        }
+
+        (response, cacheable)
+    } else {
+        let mut response = MessageBuilder::response(message)
+            .r_code(RCode::Refused)
+            .build();
+        add_extended_error(
+            &mut response,
+            ExtendedError::new(
+                ExtendedErrorCode::NotSupported,
+                Some("Only recursive queries are supported".to_string()),
+            ),
+        );
+        (response, false)
+    };
+
+    println!("\t- Responding with {} answers", response.answer.len());
+    if cacheable {
+        cache::cache_response(redis_pool, &response).await;
+    }
+
+    response
+}
+
+/// We are not a secondary server for any zone, so there is nothing to be notified about
+fn handle_notify(message: &Message) -> Message {
+    let mut response = MessageBuilder::response(message)
+        .r_code(RCode::Refused)
+        .build();
+    add_extended_error(
+        &mut response,
+        ExtendedError::new(
+            ExtendedErrorCode::NotAuthoritative,
+            Some("Not a secondary server for any zone".to_string()),
+        ),
+    );
+    response
+}
+
+/// Where the answers to a query come from, found from the questions and the OPT record in the received buffer
+enum AnswerSource {
+    /// One of our own zones, by its name
+    Zone(DomainName),
+    /// The upstream resolver, with anything that was found in the cache for each question
+    Upstream(Vec<Option<ResourceRecord>>),
+}
+
+async fn find_answer_source(
+    args: &CLI,
+    zones: &Mutex<ZoneStore>,
     redis_pool: &Pool<RedisConnectionManager>,
+    message: &MessageRef<'_>,
+    client_ip: IpAddr,
+) -> AnswerSource {
+    let header = message.header();
+    if header.flags.op_code != OpCode::Query {
+        return AnswerSource::Upstream(vec![]);
+    }
+
+    let zone = message.questions().next().and_then(|question| {
+        let zones = zones.lock().unwrap();
+        let zone = zones.find_zone(&question.name().to_domain_name())?;
+        Some(zone.name().clone())
+    });
+    if let Some(zone) = zone {
+        return AnswerSource::Zone(zone);
+    }
+
+    // The cache only holds answers that apply to everyone
+    let edns = message.edns().ok().flatten();
+    if !header.do_recursion() || upstream_client_subnet(edns.as_ref(), args, client_ip).is_some() {
+        return AnswerSource::Upstream(vec![]);
+    }
+
+    let mut cached = vec![];
+    for question in message.questions() {
+        let name = question.name().to_domain_name();
+        cached.push(cache::lookup_cached(redis_pool, &name, &question.q_type()).await);
+    }
+    AnswerSource::Upstream(cached)
+}
+
+/// Answers authoritatively for one of our own zones
+fn answer_locally(zones: &ZoneStore, zone_name: &DomainName, message: &Message) -> Message {
+    let zone = zones.get_zone(zone_name).expect("Zones are never removed");
+
+    if message.questions[0].get_query_name_type().1 == RRType::AXFR {
+        let mut response = MessageBuilder::response(message)
+            .r_code(RCode::Refused)
+            .build();
+        add_extended_error(
+            &mut response,
+            ExtendedError::new(
+                ExtendedErrorCode::NotSupported,
+                Some("Zone transfers are only available over TCP".to_string()),
+            ),
+        );
+        return response;
+    }
+
+    answer_from_zone(message, zone)
+}
+
+fn answer_from_zone(message: &Message, zone: &Zone) -> Message {
+    let mut answers = vec![];
+    let mut name_exists = false;
+    for (name, rr_type) in message.question_names().iter() {
+        answers.extend(zone.lookup(name, rr_type).into_iter().cloned());
+        name_exists |= zone.name_in_use(name);
+    }
+
+    let answer_count = answers.len();
+    let mut response = MessageBuilder::response(message)
+        .authoritative(true)
+        .answers(answers);
+    if answer_count == 0 {
+        if !name_exists {
+            response = response.r_code(RCode::NameError);
+        }
+        // Lets the client cache the negative answer (RFC 2308)
+        if let Some(soa_record) = zone.soa() {
+            let mut soa_record = soa_record.clone();
+            if let RRData::SOA(soa) = soa_record.rdata() {
+                let ttl = soa.minimum() as usize;
+                soa_record.set_ttl(ttl.min(soa_record.seconds_until_expiration()));
+            }
+            response = response.authority(soa_record);
+        }
+    }
+
+    println!(
+        "\t- Responding from zone {} with {} answers",
+        zone.name(),
+        answer_count
+    );
+    response.build()
+}
+
+fn handle_update(message: &Message, zones: &mut ZoneStore, signed: bool) -> Message {
+    let response = MessageBuilder::response(message);
+    if !signed {
+        let mut response = response.r_code(RCode::Refused).build();
+        add_extended_error(
+            &mut response,
+            ExtendedError::new(
+                ExtendedErrorCode::Prohibited,
+                Some("Updates must be signed with TSIG".to_string()),
+            ),
+        );
+        return response;
+    }
+
+    let update = match UpdateMessage::from_message(message.clone()) {
+        Ok(update) => update,
+        Err(err) => {
+            println!("\t- Invalid update: {err}");
+            return response.r_code(RCode::FormatError).build();
+        }
+    };
+
+    // Updates must be sent to a zone we are authoritative for, not a subdomain of it
+    let zone = match zones.get_zone_mut(&update.zone) {
+        Some(zone) if update.zone_class == Class::IN => zone,
+        _ => {
+            let mut response = response.r_code(RCode::NotAuth).build();
+            add_extended_error(
+                &mut response,
+                ExtendedError::new(ExtendedErrorCode::NotAuthoritative, None),
+            );
+            return response;
+        }
+    };
+
+    let r_code = zone.apply_update(&update);
+    println!(
+        "\t- Applied {} updates to zone {} with result {}",
+        update.updates.len(),
+        zone.name(),
+        r_code
+    );
+    response.r_code(r_code).build()
+}
+
+/// Obsolete opcodes (IQUERY), ones we have no use for (STATUS, DSO) and unassigned ones
+fn handle_not_implemented(message: &Message) -> Message {
+    let op_code = &message.header.flags.op_code;
+    let mut response = MessageBuilder::response(message)
+        .r_code(RCode::NotImplemented)
+        .build();
+    add_extended_error(
+        &mut response,
+        ExtendedError::new(
+            ExtendedErrorCode::NotSupported,
+            Some(format!("Op code {op_code} is not supported")),
+        ),
+    );
+    response
+}
+
+/// Extended errors can only be sent to clients that speak EDNS
+fn add_extended_error(response: &mut Message, error: ExtendedError) {
+    println!("\t- {error}");
+    if let Some(edns) = response.edns.as_mut() {
+        edns.add_extended_error(error);
+    }
+}
+
+/// A bare FORMERR response for a message that could not be parsed, if at least its header could be
+fn format_error_response(buf: &[u8]) -> Option<Vec<u8>> {
+    let query_header = MessageHeader::parse(&mut Reader::new(buf)).ok()?;
+    if !query_header.is_query() {
+        return None;
+    }
+
+    let response = MessageBuilder::response_to_header(&query_header)
+        .r_code(RCode::FormatError)
+        .build();
+    Some(response.serialize())
+}
+
+/// The client subnet information to send to the upstream resolver for the query,
+/// never revealing more of the client's address than the configured prefix lengths
+fn upstream_client_subnet(
+    query_edns: Option<&Edns>,
+    args: &CLI,
+    client_address: IpAddr,
+) -> Option<ClientSubnet> {
+    let max_prefix = |address: &IpAddr| match address {
+        IpAddr::V4(_) => args.ecs_ipv4_prefix,
+        IpAddr::V6(_) => args.ecs_ipv6_prefix,
+    };
+    let query_subnet = query_edns
+        .and_then(|e| e.client_subnet())
+        .map(|s| s.with_max_source_prefix(max_prefix(&s.address())));
+
+    match args.ecs {
+        EcsMode::Strip => None,
+        EcsMode::Forward => query_subnet,
+        EcsMode::Synthesize => query_subnet.or_else(|| {
+            let client_address = client_address.to_canonical();
+            match is_private_address(&client_address) {
+                true => None,
+                false => ClientSubnet::new(client_address, max_prefix(&client_address)),
+            }
+        }),
+    }
+}
+
+struct Answers {
+    records: Vec<ResourceRecord>,
+    client_subnet: Option<ClientSubnet>, // As returned by the upstream resolver
+    r_code: RCode,
+    extended_errors: Vec<ExtendedError>,
+}
+
+impl Answers {
+    /// Answers that only apply to part of the internet shouldn't be given to everyone,
+    /// and answers that came with errors shouldn't be kept around
+    fn cacheable(&self) -> bool {
+        self.extended_errors.is_empty()
+            && self
+                .client_subnet
+                .as_ref()
+                .is_none_or(|s| s.scope_prefix() == 0)
+    }
+}
+
+/// Answers the questions from what was cached for them, or asks the upstream resolver
+fn get_answers(
     message: &Message,
     router_address: &IpAddr,
-) -> Vec<ResourceRecord> {
+    client_subnet: Option<ClientSubnet>,
+    cached: Vec<Option<ResourceRecord>>,
+) -> Answers {
     let mut records = vec![];
+    let mut response_subnet = None;
+    let mut r_code = RCode::NoError;
+    let mut extended_errors = vec![];
+    let mut cached = cached.into_iter();
     for (name, rr_type) in message.question_names().iter() {
-        if let Some(record) = cache::lookup_cached(&redis_pool, &name, &rr_type).await {
+        if let Some(record) = cached.next().flatten() {
             println!("\tUsing cached value for {name} {rr_type}");
             records.push(record);
         } else {
-            let resp = lookup(
-                &name.to_string(),
-                rr_type.clone(),
-                router_address.clone(),
-                true,
-            );
+            let mut edns = Edns::default();
+            edns.set_client_subnet(client_subnet.clone());
+            edns.set_cookie(Some(cookie_for(*router_address)));
+            let query = Message::new_query(name, rr_type.clone(), true, Some(edns));
+
+            let resp = match send_query(query, *router_address, None) {
+                Ok(resp) => resp,
+                // Records missing from the other sections don't make the answers any less valid
+                Err(LookupError::PartialResponse(partial)) if partial.has_complete_answer() => {
+                    for err in partial.errors.iter() {
+                        println!("\tIgnoring malformed record from upstream resolver: {err}");
+                    }
+                    partial.message
+                }
+                Err(err) => {
+                    println!("Failed to query upstream resolver for {name} {rr_type}: {err}");
+                    let code = match err {
+                        LookupError::IOError(_) => ExtendedErrorCode::NoReachableAuthority,
+                        LookupError::ParseError(_) | LookupError::PartialResponse(_) => {
+                            ExtendedErrorCode::InvalidData
+                        }
+                        LookupError::CookieMismatch
+                        | LookupError::MissingCookie
+                        | LookupError::TsigError(_)
+                        | LookupError::TransferFailed(_) => ExtendedErrorCode::NetworkError,
+                    };
+                    r_code = RCode::ServerFailure;
+                    extended_errors.push(ExtendedError::new(
+                        code,
+                        Some(format!("Upstream resolver failed: {err}")),
+                    ));
+                    continue;
+                }
+            };
+            if let Some(edns) = resp.edns.as_ref() {
+                if let Some(subnet) = edns.client_subnet() {
+                    response_subnet = Some(subnet.clone());
+                }
+                // Pass on the reasons the upstream resolver gave, e.g. DNSSEC validation failures
+                extended_errors.extend(edns.extended_errors().into_iter().cloned());
+            }
             if resp.header.flags.r_code == RCode::NoError {
                 for ans in resp.answer.into_iter() {
                     records.push(ans);
@@ -91,9 +613,19 @@ async fn get_answers(
                 println!(
                     "Got error response from remote DNS server: \n======\n{message}\n======\n"
                 );
+                r_code = match resp.header.flags.r_code {
+                    // These concern our own query to the upstream resolver rather than the client's
+                    RCode::BadVers | RCode::BadCookie => RCode::ServerFailure,
+                    other => other,
+                };
             }
         }
     }
 
-    records
+    Answers {
+        records,
+        client_subnet: response_subnet,
+        r_code,
+        extended_errors,
+    }
 }
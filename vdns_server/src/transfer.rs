@@ -0,0 +1,118 @@
+use std::{
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use vdns_lib::{
+    common::rr_type::RRType,
+    messages::{
+        header::flags::RCode,
+        message::{Message, MessageBuilder},
+        tsig::{
+            keyring::Keyring,
+            signing::{error_response, is_signed, TsigSession},
+        },
+    },
+    read_tcp_message, write_tcp_message, LookupError,
+};
+
+use crate::zones::ZoneStore;
+
+// How many records to send in each message of a transfer
+const RECORDS_PER_MESSAGE: usize = 100;
+
+/// Serves zone transfers (AXFR) of the local zones over TCP, every transfer must be signed with TSIG.
+/// Each connection is handled on its own thread so that a slow client doesn't hold up the others.
+pub fn serve_transfers(listener: TcpListener, zones: Arc<Mutex<ZoneStore>>, keyring: Keyring) {
+    let keyring = Arc::new(keyring);
+
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                println!("Failed to accept TCP connection, err: {err}");
+                continue;
+            }
+        };
+
+        let zones = zones.clone();
+        let keyring = keyring.clone();
+        thread::spawn(move || {
+            if let Err(err) = handle_transfer(&mut stream, &zones, &keyring) {
+                println!("Failed to serve zone transfer, err: {err}");
+            }
+        });
+    }
+}
+
+fn handle_transfer(
+    stream: &mut TcpStream,
+    zones: &Mutex<ZoneStore>,
+    keyring: &Keyring,
+) -> Result<(), LookupError> {
+    let request = read_tcp_message(stream)?;
+    let message = Message::parse(&request)?;
+    println!("Transfer requested for {}", message.to_short_string());
+
+    let mut session = match TsigSession::accept_request(&request, keyring) {
+        Ok(session) if *session.error() == RCode::NoError => session,
+        Ok(mut session) => {
+            return write_error(stream, &message, RCode::NotAuth, Some(&mut session));
+        }
+        Err(err) if is_signed(&request) => {
+            println!("\t- Rejecting transfer with invalid signature: {err}");
+            let response = MessageBuilder::response(&message)
+                .r_code(RCode::NotAuth)
+                .build();
+            write_tcp_message(
+                stream,
+                &error_response(response.serialize(), &request, &err),
+            )?;
+            return Ok(());
+        }
+        Err(_) => return write_error(stream, &message, RCode::Refused, None),
+    };
+
+    let records = match message.questions.first() {
+        Some(question) => {
+            let (name, rr_type) = question.get_query_name_type();
+            match zones.lock().unwrap().get_zone(&name) {
+                Some(zone) if rr_type == RRType::AXFR => Some(zone.transfer_records()),
+                _ => None,
+            }
+        }
+        None => None,
+    };
+    let records = match records {
+        Some(records) => records,
+        None => return write_error(stream, &message, RCode::NotAuth, Some(&mut session)),
+    };
+
+    // Each message is signed in turn, covering the signature of the message before it
+    for chunk in records.chunks(RECORDS_PER_MESSAGE) {
+        let response = MessageBuilder::response(&message)
+            .authoritative(true)
+            .answers(chunk.iter().cloned())
+            .build();
+        write_tcp_message(stream, &session.sign(response.serialize()))?;
+    }
+
+    Ok(())
+}
+
+fn write_error(
+    stream: &mut TcpStream,
+    message: &Message,
+    r_code: RCode,
+    session: Option<&mut TsigSession>,
+) -> Result<(), LookupError> {
+    let response = MessageBuilder::response(message).r_code(r_code).build();
+
+    let mut serialized = response.serialize();
+    if let Some(session) = session {
+        serialized = session.sign(serialized);
+    }
+    write_tcp_message(stream, &serialized)?;
+    Ok(())
+}
@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+
+use vdns_lib::{
+    common::{class::Class, domain_name::DomainName, rr_type::RRType, ttl::TTL},
+    messages::{
+        header::flags::RCode,
+        resource_record::{resource_record::ResourceRecord, rr_data::RRData, soa::SOA},
+        update::{Prerequisite, Update, UpdateMessage},
+    },
+};
+
+const DEFAULT_SOA_TTL: u32 = 3600;
+const DEFAULT_SOA_REFRESH: u32 = 3600;
+const DEFAULT_SOA_RETRY: u32 = 600;
+const DEFAULT_SOA_EXPIRE: u32 = 86400;
+const DEFAULT_SOA_MINIMUM: u32 = 300;
+
+/// The zones that we are authoritative for, kept in memory
+pub struct ZoneStore {
+    zones: HashMap<DomainName, Zone>,
+}
+
+impl ZoneStore {
+    pub fn new(zones: Vec<Zone>) -> Self {
+        Self {
+            zones: zones.into_iter().map(|z| (z.name.clone(), z)).collect(),
+        }
+    }
+
+    /// The most specific zone that the name belongs to
+    pub fn find_zone(&self, name: &DomainName) -> Option<&Zone> {
+        self.get_zone(&self.find_zone_name(name)?)
+    }
+
+    /// The zone with exactly this name
+    pub fn get_zone(&self, name: &DomainName) -> Option<&Zone> {
+        self.zones.get(name)
+    }
+
+    pub fn get_zone_mut(&mut self, name: &DomainName) -> Option<&mut Zone> {
+        self.zones.get_mut(name)
+    }
+
+    pub fn find_zone_mut(&mut self, name: &DomainName) -> Option<&mut Zone> {
+        let zone_name = self.find_zone_name(name)?;
+        self.get_zone_mut(&zone_name)
+    }
+
+    fn find_zone_name(&self, name: &DomainName) -> Option<DomainName> {
+        let mut current = Some(name.clone());
+        while let Some(name) = current {
+            if self.zones.contains_key(&name) {
+                return Some(name);
+            }
+            current = name.parent();
+        }
+        None
+    }
+}
+
+pub struct Zone {
+    name: DomainName,
+    records: Vec<ResourceRecord>,
+}
+
+impl Zone {
+    /// An empty zone with only a placeholder SOA record
+    pub fn new(name: DomainName) -> Self {
+        // Names too long to fit another label use the zone itself
+        let hostmaster = name
+            .prepend_label(b"hostmaster")
+            .unwrap_or_else(|_| name.clone());
+        let soa = SOA::new(
+            name.clone(),
+            hostmaster,
+            1,
+            DEFAULT_SOA_REFRESH,
+            DEFAULT_SOA_RETRY,
+            DEFAULT_SOA_EXPIRE,
+            DEFAULT_SOA_MINIMUM,
+        );
+
+        Self {
+            records: vec![ResourceRecord::new(
+                name.clone(),
+                Class::IN,
+                TTL::from(DEFAULT_SOA_TTL),
+                RRData::SOA(soa),
+            )],
+            name,
+        }
+    }
+
+    /// A zone with the records, e.g. from a zone file, which must have a single SOA record at the apex
+    /// and only names within the zone
+    pub fn from_records(name: DomainName, records: Vec<ResourceRecord>) -> Result<Self, String> {
+        let soa_records = records
+            .iter()
+            .filter(|r| *r.record_type() == RRType::SOA)
+            .collect::<Vec<&ResourceRecord>>();
+        match soa_records.as_slice() {
+            [soa] if soa.name() == &name => {}
+            [soa] => {
+                return Err(format!(
+                    "The SOA record of zone {name} is for {} rather than the apex",
+                    soa.name()
+                ))
+            }
+            [] => return Err(format!("Zone {name} has no SOA record")),
+            _ => return Err(format!("Zone {name} has more than one SOA record")),
+        }
+
+        if let Some(outside) = records.iter().find(|r| !r.name().is_subdomain_of(&name)) {
+            return Err(format!(
+                "Record for {} is outside of zone {name}",
+                outside.name()
+            ));
+        }
+
+        Ok(Self { name, records })
+    }
+
+    pub fn name(&self) -> &DomainName {
+        &self.name
+    }
+
+    pub fn record_count(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn soa(&self) -> Option<&ResourceRecord> {
+        self.records
+            .iter()
+            .find(|r| *r.record_type() == RRType::SOA)
+    }
+
+    /// The records for the name with the type, or all its records for the ANY type
+    pub fn lookup(&self, name: &DomainName, rr_type: &RRType) -> Vec<&ResourceRecord> {
+        self.records
+            .iter()
+            .filter(|r| r.name() == name)
+            .filter(|r| *rr_type == RRType::All || r.record_type() == rr_type)
+            .collect()
+    }
+
+    /// All records of the zone, starting and ending with the SOA record as in a zone transfer (RFC 5936 section 2.2)
+    pub fn transfer_records(&self) -> Vec<ResourceRecord> {
+        let soa = self.soa().cloned();
+        soa.iter()
+            .cloned()
+            .chain(
+                self.records
+                    .iter()
+                    .filter(|r| *r.record_type() != RRType::SOA)
+                    .cloned(),
+            )
+            .chain(soa.iter().cloned())
+            .collect()
+    }
+
+    pub fn name_in_use(&self, name: &DomainName) -> bool {
+        self.records.iter().any(|r| r.name() == name)
+    }
+
+    /// Checks the prerequisites and applies the updates if they hold (RFC 2136 section 3)
+    pub fn apply_update(&mut self, update: &UpdateMessage) -> RCode {
+        if let Err(r_code) = self.check_prerequisites(&update.prerequisites) {
+            return r_code;
+        }
+
+        if update
+            .updates
+            .iter()
+            .any(|u| !u.name().is_subdomain_of(&self.name))
+        {
+            return RCode::NotZone;
+        }
+
+        let serial = self.serial();
+        let mut changed = false;
+        for operation in update.updates.iter() {
+            changed |= self.apply(operation);
+        }
+
+        // An update that replaced the SOA has already set the serial (RFC 2136 section 3.6)
+        if changed && self.serial() == serial {
+            self.increment_serial();
+        }
+
+        RCode::NoError
+    }
+
+    fn check_prerequisites(&self, prerequisites: &[Prerequisite]) -> Result<(), RCode> {
+        let mut required_rrsets: Vec<Vec<&ResourceRecord>> = vec![];
+        for prerequisite in prerequisites.iter() {
+            if !prerequisite.name().is_subdomain_of(&self.name) {
+                return Err(RCode::NotZone);
+            }
+
+            match prerequisite {
+                Prerequisite::NameInUse(name) => {
+                    if !self.name_in_use(name) {
+                        return Err(RCode::NameError);
+                    }
+                }
+                Prerequisite::NameNotInUse(name) => {
+                    if self.name_in_use(name) {
+                        return Err(RCode::YXDomain);
+                    }
+                }
+                Prerequisite::RRSetExists(name, rr_type) => {
+                    if self.lookup(name, rr_type).is_empty() {
+                        return Err(RCode::NXRRSet);
+                    }
+                }
+                Prerequisite::RRSetDoesNotExist(name, rr_type) => {
+                    if !self.lookup(name, rr_type).is_empty() {
+                        return Err(RCode::YXRRSet);
+                    }
+                }
+                Prerequisite::RecordExists(record) => {
+                    match required_rrsets.iter_mut().find(|rrset| {
+                        rrset[0].name() == record.name()
+                            && rrset[0].record_type() == record.record_type()
+                    }) {
+                        Some(rrset) => rrset.push(record),
+                        None => required_rrsets.push(vec![record]),
+                    }
+                }
+            }
+        }
+
+        // Value dependent prerequisites must match the whole RRset
+        for required in required_rrsets.iter() {
+            let existing = self.lookup(required[0].name(), required[0].record_type());
+            let all_exist = required
+                .iter()
+                .all(|r| existing.iter().any(|e| e.rdata() == r.rdata()));
+            let all_required = existing
+                .iter()
+                .all(|e| required.iter().any(|r| e.rdata() == r.rdata()));
+            if !all_exist || !all_required {
+                return Err(RCode::NXRRSet);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the zone was changed
+    fn apply(&mut self, update: &Update) -> bool {
+        let count = self.records.len();
+        match update {
+            Update::Add(record) => return self.add(record),
+            Update::DeleteRRSet(name, rr_type) => {
+                // The apex must keep its SOA and NS records
+                if name == &self.name && matches!(rr_type, RRType::SOA | RRType::NS) {
+                    return false;
+                }
+                self.records
+                    .retain(|r| !(r.name() == name && r.record_type() == rr_type));
+            }
+            Update::DeleteName(name) => {
+                let apex = name == &self.name;
+                self.records.retain(|r| {
+                    r.name() != name
+                        || (apex && matches!(r.record_type(), RRType::SOA | RRType::NS))
+                });
+            }
+            Update::DeleteRecord(name, rdata) => {
+                let rr_type = rdata.rr_type();
+                if rr_type == RRType::SOA
+                    || (rr_type == RRType::NS
+                        && name == &self.name
+                        && self.lookup(name, &RRType::NS).len() <= 1)
+                {
+                    return false;
+                }
+                self.records.retain(|r| {
+                    !(r.name() == name && *r.record_type() == rr_type && r.rdata() == rdata)
+                });
+            }
+        }
+        self.records.len() != count
+    }
+
+    fn add(&mut self, record: &ResourceRecord) -> bool {
+        let name = record.name();
+        let rr_type = record.record_type();
+
+        // A CNAME can't coexist with other data (RFC 2136 section 3.4.2.2)
+        let has_cname = !self.lookup(name, &RRType::CNAME).is_empty();
+        let has_other = self
+            .lookup(name, &RRType::All)
+            .iter()
+            .any(|r| *r.record_type() != RRType::CNAME);
+        if (*rr_type == RRType::CNAME && has_other) || (*rr_type != RRType::CNAME && has_cname) {
+            return false;
+        }
+
+        match (rr_type, record.rdata()) {
+            (RRType::SOA, RRData::SOA(new_soa)) => {
+                if name != &self.name {
+                    return false;
+                }
+                if !serial_greater(new_soa.serial(), self.serial()) {
+                    return false;
+                }
+                self.records.retain(|r| *r.record_type() != RRType::SOA);
+            }
+            (RRType::CNAME, _) => self
+                .records
+                .retain(|r| !(r.name() == name && *r.record_type() == RRType::CNAME)),
+            _ => {
+                // Adding an existing record only updates its TTL
+                if let Some(existing) = self.records.iter_mut().find(|r| {
+                    r.name() == name && r.record_type() == rr_type && r.rdata() == record.rdata()
+                }) {
+                    let changed =
+                        existing.seconds_until_expiration() != record.seconds_until_expiration();
+                    existing.set_ttl(record.seconds_until_expiration());
+                    return changed;
+                }
+            }
+        }
+
+        self.records.push(record.clone());
+        true
+    }
+
+    fn serial(&self) -> u32 {
+        match self.soa().map(|r| r.rdata()) {
+            Some(RRData::SOA(soa)) => soa.serial(),
+            _ => 0,
+        }
+    }
+
+    fn increment_serial(&mut self) {
+        if let Some(soa_record) = self
+            .records
+            .iter_mut()
+            .find(|r| *r.record_type() == RRType::SOA)
+        {
+            if let RRData::SOA(soa) = soa_record.rdata() {
+                let mut soa = soa.clone();
+                soa.set_serial(soa.serial().wrapping_add(1));
+                *soa_record = ResourceRecord::new(
+                    soa_record.name().clone(),
+                    soa_record.class().clone(),
+                    soa_record.ttl().clone(),
+                    RRData::SOA(soa),
+                );
+            }
+        }
+    }
+}
+
+/// Serial number comparison (RFC 1982)
+fn serial_greater(a: u32, b: u32) -> bool {
+    a != b && a.wrapping_sub(b) < (1 << 31)
+}